@@ -0,0 +1,374 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::walk::walk_dir_pruning;
+
+/// How many levels below a project to look for recently edited files.
+const EDIT_DEPTH: usize = 6;
+
+/// Decides which projects are being worked on, so their build artifacts can
+/// be protected.
+///
+/// A project is active if its git repository has uncommitted changes to the
+/// project's files (untracked files included), or if a commit, checkout,
+/// staged change, or edit to one of its own files happened within the window.
+/// Each project is checked once.
+pub struct ActiveProjects {
+    within: Duration,
+    now: SystemTime,
+    /// Directory names not searched for edits: the artifacts themselves.
+    artifact_dirs: Arc<HashSet<String>>,
+    checked: Mutex<HashMap<PathBuf, Option<String>>>,
+}
+
+impl ActiveProjects {
+    /// Count activity within `within` of now. Edits inside directories named
+    /// in `artifact_dirs` (such as `target`) are not activity.
+    pub fn new<'a>(within: Duration, artifact_dirs: impl IntoIterator<Item = &'a str>) -> Self {
+        Self {
+            within,
+            now: SystemTime::now(),
+            artifact_dirs: Arc::new(artifact_dirs.into_iter().map(str::to_string).collect()),
+            checked: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Why `project` counts as active, or `None` if it doesn't.
+    pub fn reason(&self, project: &Path) -> Option<String> {
+        let cached = self.lock().get(project).cloned();
+        if let Some(reason) = cached {
+            return reason;
+        }
+        let reason = self.check(project);
+        self.lock().insert(project.to_path_buf(), reason.clone());
+        reason
+    }
+
+    fn check(&self, project: &Path) -> Option<String> {
+        if let Some(repo) = Repo::find(project) {
+            let last_git = ["logs/HEAD", "HEAD", "index"]
+                .iter()
+                .filter_map(|file| repo.git_dir.join(file).metadata().ok()?.modified().ok())
+                .max();
+            if let Some(age) = last_git.and_then(|t| self.recent(t)) {
+                return Some(format!("git activity {} ago", format_age(age)));
+            }
+            if repo.has_uncommitted_changes(project) {
+                return Some("uncommitted changes".into());
+            }
+        }
+
+        let artifact_dirs = Arc::clone(&self.artifact_dirs);
+        walk_dir_pruning(project, move |entry| {
+            let name = entry.file_name().to_string_lossy();
+            name.starts_with('.') || artifact_dirs.contains(name.as_ref())
+        })
+        .max_depth(EDIT_DEPTH)
+        .skip_hidden(false)
+        .into_iter()
+        .flatten()
+        .filter(|entry| entry.file_type().is_file())
+        .find_map(|entry| {
+            let age = self.recent(entry.metadata().ok()?.modified().ok()?)?;
+            let path = entry.path();
+            let name = path.strip_prefix(project).unwrap_or(&path);
+            Some(format!("{} edited {} ago", name.display(), format_age(age)))
+        })
+    }
+
+    /// How long ago `time` was, if within the window.
+    fn recent(&self, time: SystemTime) -> Option<Duration> {
+        let age = self.now.duration_since(time).unwrap_or(Duration::ZERO);
+        (age < self.within).then_some(age)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<PathBuf, Option<String>>> {
+        self.checked.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// "45m", "5h", or "3d".
+fn format_age(age: Duration) -> String {
+    let minutes = age.as_secs() / 60;
+    match minutes {
+        0..=59 => format!("{minutes}m"),
+        60..=1439 => format!("{}h", minutes / 60),
+        _ => format!("{}d", minutes / 1440),
+    }
+}
+
+/// A git working tree and the directory holding its metadata.
+struct Repo {
+    work_tree: PathBuf,
+    git_dir: PathBuf,
+}
+
+impl Repo {
+    /// The repository `path` belongs to, found through the nearest `.git`
+    /// directory or `gitdir:` file (worktrees and submodules) above it.
+    fn find(path: &Path) -> Option<Self> {
+        path.ancestors().find_map(|dir| {
+            let dot_git = dir.join(".git");
+            let metadata = dot_git.symlink_metadata().ok()?;
+            let git_dir = if metadata.is_dir() {
+                dot_git
+            } else {
+                let content = std::fs::read_to_string(&dot_git).ok()?;
+                dir.join(content.strip_prefix("gitdir:")?.trim())
+            };
+            Some(Self {
+                work_tree: dir.to_path_buf(),
+                git_dir,
+            })
+        })
+    }
+
+    /// Whether `git status` reports anything under `path`: changed, deleted,
+    /// or untracked files that aren't ignored. Without a working `git`, only
+    /// tracked files are compared with the index.
+    fn has_uncommitted_changes(&self, path: &Path) -> bool {
+        let status = Command::new("git")
+            // Refreshing the index would look like git activity to the next scan
+            .arg("--no-optional-locks")
+            .args(["status", "--porcelain", "--", "."])
+            .current_dir(path)
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output();
+        match status {
+            Ok(output) if output.status.success() => !output.stdout.is_empty(),
+            _ => self.index_differs(path),
+        }
+    }
+
+    /// Whether a tracked file under `path` was modified or deleted since it
+    /// was staged, judged the way `git status` does before reading contents:
+    /// by comparing its size and modification time with the index.
+    fn index_differs(&self, path: &Path) -> bool {
+        let Ok(prefix) = path.strip_prefix(&self.work_tree) else {
+            return false;
+        };
+        let Ok(index) = std::fs::read(self.git_dir.join("index")) else {
+            return false;
+        };
+        let Some(entries) = parse_index(&index) else {
+            log::debug!("Unreadable git index in {}", self.git_dir.display());
+            return false;
+        };
+        entries
+            .iter()
+            .filter(|entry| Path::new(&entry.path).starts_with(prefix))
+            .any(|entry| entry.is_modified(&self.work_tree))
+    }
+}
+
+/// The parts of a git index entry needed to spot modified files.
+#[derive(Debug, PartialEq, Eq)]
+struct IndexEntry {
+    path: String,
+    mtime_secs: u32,
+    mtime_nanos: u32,
+    size: u32,
+}
+
+impl IndexEntry {
+    fn is_modified(&self, work_tree: &Path) -> bool {
+        let Ok(metadata) = work_tree.join(&self.path).symlink_metadata() else {
+            return true;
+        };
+        // The index keeps the low 32 bits of sizes and times
+        if metadata.len() as u32 != self.size {
+            return true;
+        }
+        let Some(mtime) = metadata
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        else {
+            return false;
+        };
+        mtime.as_secs() as u32 != self.mtime_secs
+            || (self.mtime_nanos != 0 && mtime.subsec_nanos() != self.mtime_nanos)
+    }
+}
+
+/// Parse the entries of a version 2, 3, or 4 git index, leaving out
+/// submodules and entries marked skip-worktree.
+fn parse_index(data: &[u8]) -> Option<Vec<IndexEntry>> {
+    const SKIP_WORKTREE: u16 = 0x4000;
+    const EXTENDED: u16 = 0x4000;
+    const GITLINK: u32 = 0o160000;
+
+    let u32_at = |at: usize| -> Option<u32> {
+        Some(u32::from_be_bytes(data.get(at..at + 4)?.try_into().ok()?))
+    };
+    let u16_at = |at: usize| -> Option<u16> {
+        Some(u16::from_be_bytes(data.get(at..at + 2)?.try_into().ok()?))
+    };
+
+    if data.get(..4)? != b"DIRC" {
+        return None;
+    }
+    let version = u32_at(4)?;
+    if !(2..=4).contains(&version) {
+        return None;
+    }
+    let count = u32_at(8)? as usize;
+
+    // Every entry takes at least 62 bytes, whatever the header claims
+    let mut entries = Vec::with_capacity(count.min(data.len() / 62));
+    let mut at = 12;
+    let mut previous: Vec<u8> = Vec::new();
+    for _ in 0..count {
+        let mtime_secs = u32_at(at + 8)?;
+        let mtime_nanos = u32_at(at + 12)?;
+        let mode = u32_at(at + 24)?;
+        let size = u32_at(at + 36)?;
+        let flags = u16_at(at + 60)?;
+        let mut name_at = at + 62;
+        let mut skip = false;
+        if version >= 3 && flags & EXTENDED != 0 {
+            skip = u16_at(name_at)? & SKIP_WORKTREE != 0;
+            name_at += 2;
+        }
+
+        let path = if version == 4 {
+            // Each path drops some bytes from the end of the previous one and
+            // appends a NUL-terminated suffix.
+            let (strip, len) = read_varint(data.get(name_at..)?)?;
+            let suffix_at = name_at + len;
+            let end = suffix_at + data.get(suffix_at..)?.iter().position(|&b| b == 0)?;
+            previous.truncate(previous.len().checked_sub(strip)?);
+            previous.extend_from_slice(&data[suffix_at..end]);
+            at = end + 1;
+            previous.clone()
+        } else {
+            let end = name_at + data.get(name_at..)?.iter().position(|&b| b == 0)?;
+            let path = data[name_at..end].to_vec();
+            // Entries are NUL-padded to a multiple of eight bytes
+            at += (end - at + 8) & !7;
+            path
+        };
+
+        if !skip && mode & 0o170000 != GITLINK {
+            entries.push(IndexEntry {
+                path: String::from_utf8(path).ok()?,
+                mtime_secs,
+                mtime_nanos,
+                size,
+            });
+        }
+    }
+    Some(entries)
+}
+
+/// Git's offset varint: returns the value and the bytes it took.
+fn read_varint(data: &[u8]) -> Option<(usize, usize)> {
+    let mut bytes = data.iter();
+    let mut byte = *bytes.next()?;
+    let mut value = (byte & 0x7f) as usize;
+    let mut len = 1;
+    while byte & 0x80 != 0 {
+        byte = *bytes.next()?;
+        len += 1;
+        value = ((value + 1) << 7) | (byte & 0x7f) as usize;
+    }
+    Some((value, len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A version 2 index holding `files` with their current sizes and times.
+    fn write_index(work_tree: &Path, files: &[&str]) -> Vec<u8> {
+        let mut data = b"DIRC".to_vec();
+        data.extend(2u32.to_be_bytes());
+        data.extend((files.len() as u32).to_be_bytes());
+        for file in files {
+            let metadata = work_tree.join(file).metadata().unwrap();
+            let mtime = metadata
+                .modified()
+                .unwrap()
+                .duration_since(UNIX_EPOCH)
+                .unwrap();
+            let start = data.len();
+            data.extend([0; 8]);
+            data.extend((mtime.as_secs() as u32).to_be_bytes());
+            data.extend(mtime.subsec_nanos().to_be_bytes());
+            data.extend([0; 8]);
+            data.extend(0o100644u32.to_be_bytes());
+            data.extend([0; 8]);
+            data.extend((metadata.len() as u32).to_be_bytes());
+            data.extend([0; 20]);
+            data.extend((file.len() as u16).to_be_bytes());
+            data.extend(file.as_bytes());
+            let padding = 8 - (data.len() - start) % 8;
+            data.extend(vec![0; padding]);
+        }
+        data
+    }
+
+    #[test]
+    fn test_parse_index_versions() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let dir = tmp.path();
+        std::fs::create_dir_all(dir.join("src")).unwrap();
+        std::fs::write(dir.join("Cargo.toml"), "[package]").unwrap();
+        std::fs::write(dir.join("src/main.rs"), "fn main() {}").unwrap();
+        let v2 = parse_index(&write_index(dir, &["Cargo.toml", "src/main.rs"])).unwrap();
+
+        assert_eq!(
+            v2.iter().map(|e| e.path.as_str()).collect::<Vec<_>>(),
+            ["Cargo.toml", "src/main.rs"]
+        );
+        assert_eq!(v2[1].size, 12);
+
+        // Version 4: "src/lib.rs" then "src/main.rs" sharing "src/"
+        let mut v4 = b"DIRC".to_vec();
+        v4.extend(4u32.to_be_bytes());
+        v4.extend(2u32.to_be_bytes());
+        for (strip, suffix) in [(0u8, "src/lib.rs"), (6, "main.rs")] {
+            v4.extend([0; 24]);
+            v4.extend(0o100644u32.to_be_bytes());
+            v4.extend([0; 8]);
+            v4.extend(7u32.to_be_bytes());
+            v4.extend([0; 20]);
+            v4.extend((suffix.len() as u16).to_be_bytes());
+            v4.push(strip);
+            v4.extend(suffix.as_bytes());
+            v4.push(0);
+        }
+        let entries = parse_index(&v4).unwrap();
+        assert_eq!(entries[0].path, "src/lib.rs");
+        assert_eq!(entries[1].path, "src/main.rs");
+        assert!(parse_index(b"DIRC\0\0\0\x09").is_none());
+    }
+
+    #[test]
+    fn test_index_differs_after_edits() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let root = tmp.path();
+        let project = root.join("app");
+        std::fs::create_dir_all(root.join(".git")).unwrap();
+        std::fs::create_dir_all(root.join("other")).unwrap();
+        std::fs::create_dir_all(&project).unwrap();
+        std::fs::write(project.join("main.rs"), "fn main() {}").unwrap();
+        std::fs::write(root.join("other/notes.md"), "notes").unwrap();
+        std::fs::write(
+            root.join(".git/index"),
+            write_index(root, &["app/main.rs", "other/notes.md"]),
+        )
+        .unwrap();
+
+        let repo = Repo::find(&project).unwrap();
+        assert!(!repo.index_differs(&project));
+        std::fs::write(root.join("other/notes.md"), "more notes").unwrap();
+        assert!(!repo.index_differs(&project));
+        std::fs::write(project.join("main.rs"), "fn main() { todo!() }").unwrap();
+        assert!(repo.index_differs(&project));
+    }
+}
@@ -0,0 +1,398 @@
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::fs::Metadata;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, SystemTime};
+
+use crate::error::{Error, Result};
+use crate::size::{self, DirUsage, Tally};
+use crate::walk;
+
+/// Bumped whenever the file layout changes; older files are discarded.
+const VERSION: u32 = 3;
+
+/// Recorded directories are re-read after this long even if unchanged, so
+/// files rewritten in place (which leave directory mtimes alone) are picked up
+/// eventually.
+const MAX_AGE: Duration = Duration::from_secs(7 * 86_400);
+
+thread_local! {
+    static CURRENT: RefCell<Option<ScanCache>> = const { RefCell::new(None) };
+}
+
+/// Identifies one state of a directory's entry list.
+///
+/// Adding, removing, or renaming an entry changes the directory's mtime, and
+/// replacing the directory changes its inode, so a matching fingerprint means
+/// the same files and subdirectories are still there.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct Fingerprint {
+    dev: u64,
+    inode: u64,
+    mtime: SystemTime,
+}
+
+impl Fingerprint {
+    fn of(metadata: &Metadata) -> Option<Self> {
+        #[cfg(unix)]
+        let inode = {
+            use std::os::unix::fs::MetadataExt;
+            metadata.ino()
+        };
+        #[cfg(not(unix))]
+        let inode = 0;
+
+        Some(Self {
+            dev: walk::device(metadata),
+            inode,
+            mtime: metadata.modified().ok()?,
+        })
+    }
+}
+
+/// What one directory holds directly.
+#[derive(Debug, Clone)]
+struct DirRecord {
+    fingerprint: Fingerprint,
+    /// Total size and last use of the files directly inside that have a
+    /// single link, plus the directory's own mtime.
+    files: DirUsage,
+    /// Files directly inside with several hard links, as (inode, link count,
+    /// usage). They are counted once per tree however many directories link
+    /// them, and only if the tree holds every link.
+    linked: Vec<(u64, u64, DirUsage)>,
+    subdirs: Vec<OsString>,
+    read_at: SystemTime,
+}
+
+/// On-disk form of the cache.
+#[derive(Serialize, Deserialize)]
+struct CacheFile {
+    version: u32,
+    dirs: HashMap<String, StoredRecord>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredRecord {
+    fingerprint: Fingerprint,
+    files: DirUsage,
+    linked: Vec<(u64, u64, DirUsage)>,
+    subdirs: Vec<String>,
+    read_at: SystemTime,
+}
+
+#[derive(Debug)]
+struct Inner {
+    path: Option<PathBuf>,
+    dirs: Mutex<HashMap<PathBuf, DirRecord>>,
+}
+
+/// Directory sizes remembered between scans.
+///
+/// Every directory of a measured tree is recorded with its fingerprint, the
+/// size of the files directly inside it, and its subdirectories. A later
+/// measurement only stats directories: each one whose fingerprint still
+/// matches reuses its recorded files instead of statting them again, and only
+/// changed directories are re-read.
+///
+/// Inside [`ScanCache::enter`], [`size::dir_usage`] goes through the cache.
+#[derive(Debug, Clone)]
+pub struct ScanCache {
+    inner: Arc<Inner>,
+    /// Whether recorded directories may be reused, or only recorded.
+    reuse: bool,
+}
+
+impl ScanCache {
+    /// Standard cache path: `<cache dir>/diskard/scan-cache.json`
+    /// (`~/.cache` on Linux, `~/Library/Caches` on macOS).
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::cache_dir().map(|d| d.join("diskard").join("scan-cache.json"))
+    }
+
+    /// The cache at the standard path.
+    pub fn open_default() -> Result<Self> {
+        Self::default_path()
+            .map(Self::at)
+            .ok_or_else(|| Error::Cache("Cannot determine cache directory".into()))
+    }
+
+    /// The cache stored at `path`, loaded now. A missing, unreadable, or
+    /// outdated file gives an empty cache; [`save`](Self::save) replaces it.
+    pub fn at(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let dirs = load(&path).unwrap_or_default();
+        Self::with(Some(path), dirs)
+    }
+
+    /// A cache that lives only as long as this value and its clones.
+    pub fn in_memory() -> Self {
+        Self::with(None, HashMap::new())
+    }
+
+    fn with(path: Option<PathBuf>, dirs: HashMap<PathBuf, DirRecord>) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                path,
+                dirs: Mutex::new(dirs),
+            }),
+            reuse: true,
+        }
+    }
+
+    /// This cache, re-reading every directory instead of reusing its record
+    /// and recording what it reads.
+    ///
+    /// A reused record keeps the last use its files had when it was read, so
+    /// anything judging age by [`DirUsage::last_used`] needs this.
+    pub fn refreshing(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+            reuse: false,
+        }
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.inner.path.as_deref()
+    }
+
+    /// Number of directories recorded.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Write the cache back to its file, dropping records too old to be reused.
+    /// Does nothing for an in-memory cache.
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = &self.inner.path else {
+            return Ok(());
+        };
+
+        let now = SystemTime::now();
+        let mut dirs = self.lock();
+        dirs.retain(|_, record| is_fresh(record, now));
+        let file = CacheFile {
+            version: VERSION,
+            // Non-UTF-8 paths can't be stored in JSON; they are re-read each scan
+            dirs: dirs
+                .iter()
+                .filter_map(|(path, record)| Some((path.to_str()?.to_string(), store(record)?)))
+                .collect(),
+        };
+        drop(dirs);
+
+        let json = serde_json::to_string(&file)
+            .map_err(|e| Error::Cache(format!("{}: {e}", path.display())))?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| Error::io(parent, e))?;
+        }
+        // Write then rename, so an interrupted save never leaves a torn file
+        let partial = path.with_extension("json.tmp");
+        std::fs::write(&partial, json).map_err(|e| Error::io(&partial, e))?;
+        std::fs::rename(&partial, path).map_err(|e| Error::io(path, e))
+    }
+
+    /// Forget every record and delete the cache file. Returns whether a file
+    /// was deleted.
+    pub fn clear(&self) -> Result<bool> {
+        self.lock().clear();
+        let Some(path) = &self.inner.path else {
+            return Ok(false);
+        };
+        match std::fs::remove_file(path) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(Error::io(path, e)),
+        }
+    }
+
+    /// Run `f` with every [`size::dir_usage`] call it makes on this thread
+    /// answered through this cache.
+    pub fn enter<R>(&self, f: impl FnOnce() -> R) -> R {
+        let previous = CURRENT.with(|current| current.replace(Some(self.clone())));
+        let result = f();
+        CURRENT.with(|current| *current.borrow_mut() = previous);
+        result
+    }
+
+    /// Total size and last use of the directory tree at `root`, re-reading
+    /// only directories that changed since they were recorded.
+    pub(crate) fn dir_usage(&self, root: &Path) -> DirUsage {
+        let now = SystemTime::now();
+        let boundary = walk::boundary(root);
+        walk::install(|| self.tree_usage(root, boundary, now)).total(root)
+    }
+
+    /// `boundary` is the device to stay on, if the walk mustn't cross mounts.
+    fn tree_usage(&self, dir: &Path, boundary: Option<u64>, now: SystemTime) -> Tally {
+        let Ok(metadata) = std::fs::symlink_metadata(dir) else {
+            self.lock().remove(dir);
+            return Tally::default();
+        };
+        let Some(fingerprint) = Fingerprint::of(&metadata) else {
+            return Tally::default();
+        };
+        if boundary.is_some_and(|dev| dev != fingerprint.dev) {
+            return Tally::default();
+        }
+
+        let cached = self
+            .lock()
+            .get(dir)
+            .filter(|record| {
+                self.reuse && record.fingerprint == fingerprint && is_fresh(record, now)
+            })
+            .cloned();
+
+        let record = match cached {
+            Some(record) => record,
+            None => {
+                let (files, linked, subdirs) = read_dir(dir, &metadata);
+                let record = DirRecord {
+                    fingerprint,
+                    files,
+                    linked,
+                    subdirs,
+                    read_at: now,
+                };
+                let mut dirs = self.lock();
+                if let Some(previous) = dirs.insert(dir.to_path_buf(), record.clone()) {
+                    // Forget the trees under subdirectories that are gone
+                    let gone: Vec<PathBuf> = previous
+                        .subdirs
+                        .iter()
+                        .filter(|name| !record.subdirs.contains(name))
+                        .map(|name| dir.join(name))
+                        .collect();
+                    if !gone.is_empty() {
+                        dirs.retain(|path, _| !gone.iter().any(|g| path.starts_with(g)));
+                    }
+                }
+                record
+            }
+        };
+
+        let mut tally = Tally::default();
+        tally.add_usage(record.files);
+        for (inode, links, file) in record.linked {
+            tally.add_linked((fingerprint.dev, inode), links, file);
+        }
+        record
+            .subdirs
+            .par_iter()
+            .map(|name| self.tree_usage(&dir.join(name), boundary, now))
+            .reduce(Tally::default, Tally::merge)
+            .merge(tally)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<PathBuf, DirRecord>> {
+        // Records are inserted whole, so a panic elsewhere can't leave one torn
+        self.inner
+            .dirs
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// The cache entered on this thread, if any.
+pub(crate) fn current() -> Option<ScanCache> {
+    CURRENT.with(|current| current.borrow().clone())
+}
+
+/// Singly linked files, multiply linked files by inode and link count, and
+/// subdirectory names.
+type Listing = (DirUsage, Vec<(u64, u64, DirUsage)>, Vec<OsString>);
+
+/// Stat the files directly inside `dir` and list its subdirectories.
+fn read_dir(dir: &Path, metadata: &Metadata) -> Listing {
+    // Listing a directory bumps its access time, so only its modification
+    // time says anything about use.
+    let mut files = DirUsage {
+        last_used: metadata.modified().ok(),
+        ..Default::default()
+    };
+    let mut linked = Vec::new();
+    let mut subdirs = Vec::new();
+
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return (files, linked, subdirs);
+    };
+    for entry in entries.flatten() {
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            subdirs.push(entry.file_name());
+        } else if file_type.is_file() {
+            if let Ok(metadata) = entry.metadata() {
+                let file = DirUsage::of_file(&metadata);
+                match size::hard_link(&metadata) {
+                    Some(((_, inode), links)) => linked.push((inode, links, file)),
+                    None => files = files.combine(file),
+                }
+            }
+        }
+    }
+    (files, linked, subdirs)
+}
+
+fn is_fresh(record: &DirRecord, now: SystemTime) -> bool {
+    now.duration_since(record.read_at)
+        .is_ok_and(|age| age < MAX_AGE)
+}
+
+fn load(path: &Path) -> Option<HashMap<PathBuf, DirRecord>> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return None,
+        Err(e) => {
+            log::warn!("Ignoring scan cache {}: {e}", path.display());
+            return None;
+        }
+    };
+    match serde_json::from_str::<CacheFile>(&content) {
+        Ok(file) if file.version == VERSION => Some(
+            file.dirs
+                .into_iter()
+                .map(|(path, record)| (PathBuf::from(path), unstore(record)))
+                .collect(),
+        ),
+        Ok(_) => None,
+        Err(e) => {
+            log::warn!("Ignoring scan cache {}: {e}", path.display());
+            None
+        }
+    }
+}
+
+fn store(record: &DirRecord) -> Option<StoredRecord> {
+    Some(StoredRecord {
+        fingerprint: record.fingerprint,
+        files: record.files,
+        linked: record.linked.clone(),
+        subdirs: record
+            .subdirs
+            .iter()
+            .map(|name| name.to_str().map(str::to_string))
+            .collect::<Option<_>>()?,
+        read_at: record.read_at,
+    })
+}
+
+fn unstore(record: StoredRecord) -> DirRecord {
+    DirRecord {
+        fingerprint: record.fingerprint,
+        files: record.files,
+        linked: record.linked,
+        subdirs: record.subdirs.into_iter().map(OsString::from).collect(),
+        read_at: record.read_at,
+    }
+}
@@ -1,78 +1,757 @@
-use std::path::Path;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use crate::error::{Error, Result};
-use crate::finding::Finding;
+use crate::error::{Error, ErrorKind, ErrorRecord, Result};
+use crate::finding::{self, Finding};
+use crate::journal::{self, Journal, NewEntry};
+use crate::processes;
+use crate::quarantine::Quarantine;
+use crate::size::{self, dir_size, dir_usage};
 
 /// How to delete files.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum DeleteMode {
     /// Move to system trash (default, recoverable).
     Trash,
     /// Permanently delete (irreversible).
     Permanent,
+    /// Rename into diskard's quarantine directory until purged (recoverable).
+    Quarantine,
     /// Only show what would be deleted.
     DryRun,
+    /// Run the finding's native clean command, such as `cargo clean`. Findings
+    /// without one, or whose tool isn't installed, are deleted the
+    /// [`CleanOptions::native_fallback`] way.
+    Native,
+}
+
+impl fmt::Display for DeleteMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Trash => write!(f, "trash"),
+            Self::Permanent => write!(f, "permanent"),
+            Self::Quarantine => write!(f, "quarantine"),
+            Self::DryRun => write!(f, "dryrun"),
+            Self::Native => write!(f, "native"),
+        }
+    }
+}
+
+/// What to do with a finding that running processes have files open in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum InUse {
+    /// Leave it in place and report the processes as an error.
+    #[default]
+    Refuse,
+    /// Delete it anyway and report the processes as a warning.
+    Warn,
+}
+
+/// Progress of a clean operation.
+#[derive(Debug, Clone)]
+pub enum CleanEvent {
+    /// Cleaning is about to start on `findings` items.
+    Started { findings: usize, total_bytes: u64 },
+    /// More was removed. Counts are totals for the whole clean so far; files
+    /// only count when deleted permanently.
+    Progress {
+        files_removed: u64,
+        bytes_freed: u64,
+    },
+    /// A finding is done with, whether or not it could be removed.
+    FindingFinished { path: PathBuf },
+}
+
+/// Receives [`CleanEvent`]s, possibly from several deleting threads at once.
+pub type CleanEventHandler = Arc<dyn Fn(CleanEvent) + Send + Sync>;
+
+/// Options for controlling clean behavior.
+#[derive(Clone)]
+pub struct CleanOptions {
+    pub mode: DeleteMode,
+    /// Findings running processes are using (Linux only).
+    pub in_use: InUse,
+    /// Permanently delete findings the trash refuses, rather than leaving
+    /// them. Only set this with the user's consent.
+    pub trash_fallback: bool,
+    /// Where to record what was cleaned. `None` keeps no record.
+    pub journal: Option<Journal>,
+    /// Where quarantined items go. `None` uses the default quarantine directory.
+    pub quarantine: Option<Quarantine>,
+    /// Called with progress as findings are removed.
+    pub on_event: Option<CleanEventHandler>,
+    /// How `Native` mode deletes findings it can't run a command for.
+    pub native_fallback: DeleteMode,
+    /// How long a native command may run before it is killed.
+    pub native_timeout: Duration,
+}
+
+impl Default for CleanOptions {
+    fn default() -> Self {
+        Self {
+            mode: DeleteMode::Trash,
+            in_use: InUse::default(),
+            trash_fallback: false,
+            journal: None,
+            quarantine: None,
+            on_event: None,
+            native_fallback: DeleteMode::Trash,
+            native_timeout: Duration::from_secs(300),
+        }
+    }
 }
 
 /// Results from a clean operation.
+#[derive(Debug, Serialize)]
 pub struct CleanResult {
     pub deleted_count: usize,
     pub freed_bytes: u64,
-    pub errors: Vec<(String, String)>,
+    pub errors: Vec<ErrorRecord>,
+    /// Findings deleted despite a concern, such as processes using them.
+    pub warnings: Vec<ErrorRecord>,
+    /// Findings only partly deleted, with what is left of them. Each also
+    /// has an entry in `errors`.
+    pub remaining: Vec<Remainder>,
+}
+
+/// What is left of a finding a permanent delete only got partway through.
+#[derive(Debug, Clone, Serialize)]
+pub struct Remainder {
+    pub path: PathBuf,
+    pub recognizer_id: &'static str,
+    /// Bytes freed before giving up on the rest.
+    pub freed_bytes: u64,
+    /// Bytes still on disk under the path.
+    pub remaining_bytes: u64,
+    /// Entries that couldn't be removed.
+    pub failed_entries: usize,
 }
 
-/// Delete the given findings using the specified mode.
+/// What a permanent delete removed and what it couldn't.
+#[derive(Debug, Default)]
+pub struct Removal {
+    /// Bytes freed. A hard-linked file counts once its last link is gone.
+    pub freed_bytes: u64,
+    /// Entries that couldn't be removed or listed.
+    pub failures: Vec<(PathBuf, std::io::Error)>,
+}
+
+/// Delete the given findings using the specified mode, without journaling.
 pub fn clean(findings: &[Finding], mode: DeleteMode) -> Result<CleanResult> {
+    clean_with(
+        findings,
+        &CleanOptions {
+            mode,
+            ..Default::default()
+        },
+    )
+}
+
+/// Delete the given findings and record each removed item in the journal.
+///
+/// Items going to the trash or quarantine are moved one at a time. Those
+/// deleted permanently are removed concurrently, each tree by a parallel
+/// bottom-up walk. Native commands run one at a time.
+pub fn clean_with(findings: &[Finding], options: &CleanOptions) -> Result<CleanResult> {
+    let mode = match options.mode {
+        // Native mode deletes what has no usable command some other way
+        DeleteMode::Native => match options.native_fallback {
+            DeleteMode::Native => DeleteMode::Trash,
+            fallback => fallback,
+        },
+        mode => mode,
+    };
     let mut deleted_count = 0;
-    let mut freed_bytes = 0;
-    let mut errors: Vec<(String, String)> = Vec::new();
+    let mut errors: Vec<ErrorRecord> = Vec::new();
+    let mut warnings: Vec<ErrorRecord> = Vec::new();
+    let mut remaining: Vec<Remainder> = Vec::new();
+    let mut removed: Vec<Removed> = Vec::new();
+    // Findings to delete permanently, once the rest are done
+    let mut to_remove: Vec<&Finding> = Vec::new();
+
+    let quarantine = match (mode, &options.quarantine) {
+        (DeleteMode::Quarantine, Some(quarantine)) => Some(quarantine.clone()),
+        (DeleteMode::Quarantine, None) => Some(Quarantine::open_default()?),
+        _ => None,
+    };
+
+    // A finding inside another is freed along with it, so only the outermost
+    // count toward what moving or a dry run frees
+    let outermost = finding::outermost(findings);
+    let counted: HashSet<&Path> = outermost.iter().map(|f| f.path.as_path()).collect();
+    let frees = |finding: &Finding, bytes: u64| {
+        if counted.contains(finding.path.as_path()) {
+            bytes
+        } else {
+            0
+        }
+    };
+
+    let progress = Progress::new(options.on_event.as_ref());
+    progress.emit(CleanEvent::Started {
+        findings: findings.len(),
+        total_bytes: outermost.iter().map(|f| f.size_bytes).sum(),
+    });
+
+    let paths: Vec<&Path> = findings.iter().map(|f| f.path.as_path()).collect();
+    let in_use = processes::processes_using(&paths);
+
+    for (finding, users) in findings.iter().zip(in_use) {
+        if !users.is_empty() {
+            let record = ErrorRecord::new(
+                ErrorKind::InUse,
+                format!("In use by {}", processes::describe(&users)),
+            )
+            .at(&finding.path)
+            .recognizer(finding.recognizer_id);
+            match options.in_use {
+                InUse::Refuse => {
+                    errors.push(record);
+                    progress.finished(finding);
+                    continue;
+                }
+                InUse::Warn => warnings.push(record),
+            }
+        }
 
-    for finding in findings {
         if mode == DeleteMode::DryRun {
             deleted_count += 1;
-            freed_bytes += finding.size_bytes;
+            progress.moved(frees(finding, finding.size_bytes));
+            progress.finished(finding);
+            continue;
+        }
+
+        // Already gone: nothing to free
+        if !finding.path.exists() {
+            deleted_count += 1;
+            progress.finished(finding);
             continue;
         }
 
-        match delete_path(&finding.path, mode) {
-            Ok(()) => {
-                deleted_count += 1;
-                freed_bytes += finding.size_bytes;
+        if options.mode == DeleteMode::Native {
+            if let Some((program, argv)) = finding.native.as_ref().and_then(|native| {
+                let argv = native.argv(&finding.path);
+                Some((find_program(argv.first()?)?, argv))
+            }) {
+                let before = dir_usage(&finding.path).size_bytes;
+                match run_native(&program, &argv, &finding.path, options.native_timeout) {
+                    Ok(()) => {
+                        deleted_count += 1;
+                        let after = if finding.path.exists() {
+                            dir_usage(&finding.path).size_bytes
+                        } else {
+                            0
+                        };
+                        progress.moved(before.saturating_sub(after));
+                        removed.push(Removed::new(finding, DeleteMode::Native, None));
+                    }
+                    Err(message) => errors.push(
+                        ErrorRecord::new(
+                            ErrorKind::Native,
+                            format!("`{}` {message}", argv.join(" ")),
+                        )
+                        .at(&finding.path)
+                        .recognizer(finding.recognizer_id),
+                    ),
+                }
+                progress.finished(finding);
+                continue;
             }
-            Err(e) => {
-                errors.push((finding.path.display().to_string(), e.to_string()));
+        }
+
+        if let Some(quarantine) = &quarantine {
+            let stored: Result<()> =
+                Removed::parts(finding, mode)
+                    .into_iter()
+                    .try_for_each(|part| {
+                        let entry =
+                            quarantine.store(part.path, part.size_bytes, finding.recognizer_id)?;
+                        progress.moved(frees(finding, part.size_bytes));
+                        removed.push(Removed {
+                            location: Some(entry.stored_path),
+                            ..part
+                        });
+                        Ok(())
+                    });
+            match stored {
+                Ok(()) => deleted_count += 1,
+                Err(e) => errors.push(failure(finding, &e)),
+            }
+            progress.finished(finding);
+            continue;
+        }
+
+        if mode == DeleteMode::Trash {
+            let trashed: Result<()> =
+                Removed::parts(finding, mode)
+                    .into_iter()
+                    .try_for_each(|part| {
+                        trash::delete(part.path).map_err(trash_error)?;
+                        progress.moved(frees(finding, part.size_bytes));
+                        removed.push(part);
+                        Ok(())
+                    });
+            match trashed {
+                Ok(()) => {
+                    deleted_count += 1;
+                    progress.finished(finding);
+                    continue;
+                }
+                Err(e) if options.trash_fallback && e.kind() == ErrorKind::TrashUnavailable => {
+                    let mut warning = failure(finding, &e);
+                    warning.message = format!("Deleted permanently instead: {}", warning.message);
+                    warnings.push(warning);
+                }
+                Err(e) => {
+                    errors.push(failure(finding, &e));
+                    progress.finished(finding);
+                    continue;
+                }
+            }
+        }
+
+        to_remove.push(finding);
+    }
+
+    let removals: Vec<Removal> = to_remove
+        .par_iter()
+        .map(|finding| {
+            let removal = finding
+                .paths()
+                .map(|path| remove_tracked(path, &progress))
+                .fold(Removal::default(), Removal::merge);
+            progress.finished(finding);
+            removal
+        })
+        .collect();
+
+    for (finding, removal) in to_remove.into_iter().zip(removals) {
+        if removal.failures.is_empty() {
+            deleted_count += 1;
+            removed.extend(Removed::parts(finding, DeleteMode::Permanent));
+            continue;
+        }
+        let (failed, error) = &removal.failures[0];
+        let mut record = ErrorRecord::new(ErrorKind::of_io(error), error.to_string())
+            .at(&finding.path)
+            .recognizer(finding.recognizer_id);
+        record.message = format!(
+            "{} entries could not be removed, e.g. {}: {}",
+            removal.failures.len(),
+            failed.display(),
+            record.message,
+        );
+        errors.push(record);
+        remaining.push(Remainder {
+            path: finding.path.clone(),
+            recognizer_id: finding.recognizer_id,
+            freed_bytes: removal.freed_bytes,
+            remaining_bytes: finding.paths().map(|p| dir_usage(p).size_bytes).sum(),
+            failed_entries: removal.failures.len(),
+        });
+    }
+
+    if let Some(journal) = &options.journal {
+        let mut trashed: Vec<_> = removed
+            .iter_mut()
+            .filter(|part| part.mode == DeleteMode::Trash)
+            .collect();
+        if !trashed.is_empty() {
+            let paths: Vec<&Path> = trashed.iter().map(|part| part.path).collect();
+            let locations = journal::locate_trashed(&paths);
+            for (part, found) in trashed.iter_mut().zip(locations) {
+                part.location = found;
             }
         }
+        let entries = removed
+            .into_iter()
+            .map(|part| NewEntry {
+                path: part.path.to_path_buf(),
+                size_bytes: part.size_bytes,
+                recognizer_id: part.recognizer_id.to_string(),
+                mode: part.mode,
+                trash_location: part.location,
+            })
+            .collect();
+        // The items are gone either way, so the result must still get back
+        if let Err(e) = journal.append(entries) {
+            let mut record = ErrorRecord::from_error(&e);
+            record.message = format!(
+                "Cleaned, but not recorded in the journal: {}",
+                record.message
+            );
+            errors.push(record);
+        }
     }
 
     Ok(CleanResult {
         deleted_count,
-        freed_bytes,
+        freed_bytes: progress.bytes_freed.load(Ordering::Relaxed),
         errors,
+        warnings,
+        remaining,
     })
 }
 
-/// Delete a single path using the specified mode.
-pub fn delete_path(path: &Path, mode: DeleteMode) -> Result<()> {
+/// A path a clean removed, for the journal.
+struct Removed<'a> {
+    path: &'a Path,
+    /// The path's share of its finding's size.
+    size_bytes: u64,
+    recognizer_id: &'static str,
+    mode: DeleteMode,
+    /// Where it ended up, when diskard knows.
+    location: Option<PathBuf>,
+}
+
+impl<'a> Removed<'a> {
+    /// A finding removed as a whole.
+    fn new(finding: &'a Finding, mode: DeleteMode, location: Option<PathBuf>) -> Self {
+        Self {
+            path: &finding.path,
+            size_bytes: finding.size_bytes,
+            recognizer_id: finding.recognizer_id,
+            mode,
+            location,
+        }
+    }
+
+    /// Each of a finding's paths, with the size of its extra paths measured
+    /// and the rest attributed to the main one.
+    fn parts(finding: &'a Finding, mode: DeleteMode) -> Vec<Self> {
+        let extras: Vec<Self> = finding
+            .extra_paths
+            .iter()
+            .map(|path| Self {
+                path,
+                size_bytes: dir_size(path),
+                ..Self::new(finding, mode, None)
+            })
+            .collect();
+        let main = Self {
+            size_bytes: finding
+                .size_bytes
+                .saturating_sub(extras.iter().map(|part| part.size_bytes).sum()),
+            ..Self::new(finding, mode, None)
+        };
+        std::iter::once(main).chain(extras).collect()
+    }
+}
+
+/// Running totals of a clean, reported to its event handler.
+struct Progress<'a> {
+    files_removed: AtomicU64,
+    bytes_freed: AtomicU64,
+    on_event: Option<&'a CleanEventHandler>,
+}
+
+impl<'a> Progress<'a> {
+    fn new(on_event: Option<&'a CleanEventHandler>) -> Self {
+        Self {
+            files_removed: AtomicU64::new(0),
+            bytes_freed: AtomicU64::new(0),
+            on_event,
+        }
+    }
+
+    fn emit(&self, event: CleanEvent) {
+        if let Some(on_event) = self.on_event {
+            on_event(event);
+        }
+    }
+
+    /// Count files deleted permanently.
+    fn removed(&self, files: u64, bytes: u64) {
+        let files_removed = self.files_removed.fetch_add(files, Ordering::Relaxed) + files;
+        let bytes_freed = self.bytes_freed.fetch_add(bytes, Ordering::Relaxed) + bytes;
+        self.emit(CleanEvent::Progress {
+            files_removed,
+            bytes_freed,
+        });
+    }
+
+    /// Count a finding moved away whole.
+    fn moved(&self, bytes: u64) {
+        self.removed(0, bytes);
+    }
+
+    fn finished(&self, finding: &Finding) {
+        self.emit(CleanEvent::FindingFinished {
+            path: finding.path.clone(),
+        });
+    }
+}
+
+/// Record why `finding` couldn't be removed.
+fn failure(finding: &Finding, error: &Error) -> ErrorRecord {
+    let mut record = ErrorRecord::from_error(error).recognizer(finding.recognizer_id);
+    // Report the finding; a file inside it that failed goes in the message
+    if let Some(failed) = record.path.replace(finding.path.clone()) {
+        if failed != finding.path {
+            record.message = format!("{}: {}", failed.display(), record.message);
+        }
+    }
+    record
+}
+
+/// Delete a single path using the specified mode. A quarantined path is
+/// recorded as found by `recognizer_id`.
+///
+/// `Native` fails: native commands belong to findings, not lone paths.
+pub fn delete_path(path: &Path, mode: DeleteMode, recognizer_id: &str) -> Result<()> {
     if !path.exists() {
         return Ok(());
     }
 
     match mode {
         DeleteMode::Trash => {
-            trash::delete(path).map_err(|e| Error::Trash(e.to_string()))?;
+            trash::delete(path).map_err(trash_error)?;
         }
         DeleteMode::Permanent => {
-            if path.is_dir() {
-                std::fs::remove_dir_all(path).map_err(|e| Error::io(path, e))?;
-            } else {
-                std::fs::remove_file(path).map_err(|e| Error::io(path, e))?;
+            if let Some((failed, e)) = remove_tree(path).failures.into_iter().next() {
+                return Err(Error::io(failed, e));
             }
         }
-        DeleteMode::DryRun => {
-            // No-op
+        DeleteMode::Quarantine => {
+            Quarantine::open_default()?.store(path, dir_size(path), recognizer_id)?;
+        }
+        DeleteMode::DryRun => {}
+        DeleteMode::Native => {
+            return Err(Error::Native(format!(
+                "{} has no native clean command",
+                path.display()
+            )));
         }
     }
 
     Ok(())
 }
+
+/// Permanently delete `path` and everything under it, carrying on past
+/// entries that can't be removed so as much as possible is freed.
+///
+/// Subdirectories are emptied in parallel, each removed once its contents
+/// are gone. Symbolic links are removed, never followed.
+pub fn remove_tree(path: &Path) -> Removal {
+    remove_tracked(path, &Progress::new(None))
+}
+
+fn remove_tracked(path: &Path, progress: &Progress) -> Removal {
+    let metadata = match std::fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Removal::default(),
+        Err(e) => return Removal::failed(path, e),
+    };
+    if metadata.is_dir() {
+        remove_dir(path, progress)
+    } else {
+        let mut removal = Removal::default();
+        remove_file(path, &metadata, &mut removal);
+        progress.removed(u64::from(removal.failures.is_empty()), removal.freed_bytes);
+        removal
+    }
+}
+
+/// Remove a directory's files, then its subdirectories in parallel, then
+/// the directory itself.
+fn remove_dir(path: &Path, progress: &Progress) -> Removal {
+    let entries = match std::fs::read_dir(path) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Removal::default(),
+        Err(e) => return Removal::failed(path, e),
+    };
+
+    let mut removal = Removal::default();
+    let mut files = 0;
+    let mut subdirs = Vec::new();
+    for entry in entries {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                removal.failures.push((path.to_path_buf(), e));
+                continue;
+            }
+        };
+        let entry_path = entry.path();
+        match entry.metadata() {
+            Ok(metadata) if metadata.is_dir() => subdirs.push(entry_path),
+            Ok(metadata) => {
+                let failed = removal.failures.len();
+                remove_file(&entry_path, &metadata, &mut removal);
+                files += u64::from(removal.failures.len() == failed);
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => removal.failures.push((entry_path, e)),
+        }
+    }
+    progress.removed(files, removal.freed_bytes);
+
+    let nested = subdirs
+        .par_iter()
+        .map(|subdir| remove_dir(subdir, progress))
+        .reduce(Removal::default, Removal::merge);
+    removal = removal.merge(nested);
+
+    // A directory with something left in it can't go
+    if removal.failures.is_empty() {
+        match std::fs::remove_dir(path) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => removal.failures.push((path.to_path_buf(), e)),
+        }
+    }
+    removal
+}
+
+fn remove_file(path: &Path, metadata: &std::fs::Metadata, removal: &mut Removal) {
+    match std::fs::remove_file(path) {
+        Ok(()) => {
+            // Other links keep the data on disk
+            if size::hard_link(metadata).is_none() {
+                removal.freed_bytes += size::allocated_bytes(metadata);
+            }
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => removal.failures.push((path.to_path_buf(), e)),
+    }
+}
+
+impl Removal {
+    fn failed(path: &Path, error: std::io::Error) -> Self {
+        Self {
+            freed_bytes: 0,
+            failures: vec![(path.to_path_buf(), error)],
+        }
+    }
+
+    fn merge(mut self, other: Self) -> Self {
+        self.freed_bytes += other.freed_bytes;
+        self.failures.extend(other.failures);
+        self
+    }
+}
+
+/// Where `program` would be run from: itself if it is a path, otherwise the
+/// first executable of that name on `PATH`.
+fn find_program(program: &str) -> Option<PathBuf> {
+    if program.contains(std::path::is_separator) {
+        let path = PathBuf::from(program);
+        return is_executable(&path).then_some(path);
+    }
+    std::env::split_paths(&std::env::var_os("PATH")?)
+        .map(|dir| dir.join(program))
+        .find(|path| is_executable(path))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path).is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file() || path.with_extension("exe").is_file()
+}
+
+/// Run a native clean command for `path` from the directory holding it,
+/// killing it after `timeout`. Fails with why, in words.
+fn run_native(
+    program: &Path,
+    argv: &[String],
+    path: &Path,
+    timeout: Duration,
+) -> std::result::Result<(), String> {
+    let mut command = Command::new(program);
+    command
+        .args(&argv[1..])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped());
+    // In a process group of its own, so a timeout stops whatever it started
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut command, 0);
+    if let Some(dir) = path.parent().filter(|dir| dir.is_dir()) {
+        command.current_dir(dir);
+    }
+    let mut child = command
+        .spawn()
+        .map_err(|e| format!("could not start: {e}"))?;
+
+    // Drain stderr as it comes so a chatty tool can't fill the pipe and stall
+    let mut stderr = child.stderr.take();
+    let reader = std::thread::spawn(move || {
+        let mut output = String::new();
+        if let Some(stderr) = &mut stderr {
+            let _ = stderr.read_to_string(&mut output);
+        }
+        output
+    });
+
+    let deadline = Instant::now() + timeout;
+    let waited = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break Ok(status),
+            Ok(None) if Instant::now() >= deadline => {
+                break Err(format!("timed out after {}s", timeout.as_secs()))
+            }
+            Ok(None) => std::thread::sleep(Duration::from_millis(20)),
+            Err(e) => break Err(e.to_string()),
+        }
+    };
+    let status = match waited {
+        Ok(status) => status,
+        Err(message) => {
+            // Leave nothing running: no stray processes, no zombie, no reader
+            kill_tree(&mut child);
+            let _ = child.wait();
+            let _ = reader.join();
+            return Err(message);
+        }
+    };
+    let stderr = reader.join().unwrap_or_default();
+    if status.success() {
+        return Ok(());
+    }
+    match stderr.lines().rev().find(|line| !line.trim().is_empty()) {
+        Some(line) => Err(format!("failed ({status}): {}", line.trim())),
+        None => Err(format!("failed ({status})")),
+    }
+}
+
+/// Kill `child` and, on Unix, the rest of the process group it leads.
+fn kill_tree(child: &mut Child) {
+    #[cfg(unix)]
+    if let Ok(group) = libc::pid_t::try_from(child.id()) {
+        // SAFETY: killpg only sends a signal; the child leads its own group
+        // (see `process_group(0)`) and is not yet reaped, so `group` names it.
+        if unsafe { libc::killpg(group, libc::SIGKILL) } == 0 {
+            return;
+        }
+    }
+    let _ = child.kill();
+}
+
+/// Keep file-system failures from the trash as I/O errors, so they are
+/// classified like those of permanent deletes.
+fn trash_error(error: trash::Error) -> Error {
+    match error {
+        #[cfg(all(
+            unix,
+            not(target_os = "macos"),
+            not(target_os = "ios"),
+            not(target_os = "android")
+        ))]
+        trash::Error::FileSystem { path, source } => Error::io(path, source),
+        error => Error::Trash(error.to_string()),
+    }
+}
@@ -1,17 +1,24 @@
-use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
+use crate::cleaner::DeleteMode;
 use crate::error::{Error, Result};
 use crate::finding::RiskLevel;
+use crate::size;
 
 /// Top-level configuration.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(default)]
 pub struct Config {
     pub defaults: Defaults,
+    pub scan: ScanConfig,
     pub ignore: IgnoreConfig,
     pub recognizers: RecognizerConfig,
+    /// Recognizers defined with `[[custom]]` tables.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub custom: Vec<CustomRecognizerConfig>,
 }
 
 /// Default behavior settings.
@@ -20,10 +27,45 @@ pub struct Config {
 pub struct Defaults {
     /// Maximum risk level to show by default.
     pub risk_tolerance: String,
-    /// "trash" or "permanent".
+    /// "trash", "permanent", "quarantine", or "native".
     pub delete_mode: String,
     /// Minimum size in bytes to report.
     pub min_size: u64,
+    /// How long a native clean command may run before it is stopped,
+    /// e.g. "5m".
+    pub native_timeout: String,
+}
+
+/// Where project-based recognizers look for projects.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScanConfig {
+    /// Directories to search for projects. A leading `~` is the home directory.
+    pub roots: Vec<PathBuf>,
+    /// How many directory levels below each root to search.
+    pub max_depth: usize,
+    /// Follow symbolic links while walking project trees.
+    pub follow_symlinks: bool,
+    /// Don't descend into other mounted file systems (external volumes,
+    /// network shares) while walking project trees or measuring findings.
+    pub same_file_system: bool,
+    /// Protect the artifacts of projects with uncommitted changes, or with
+    /// commits, checkouts, or edits this recent (e.g. "3d"). Unset turns
+    /// protection off.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub protect_active_within: Option<String>,
+    /// How active projects are protected: "raise" their findings' risk one
+    /// level, or "exclude" them from results.
+    pub protect_active: String,
+}
+
+/// Parsed `[scan]` protection of active projects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ActiveProtection {
+    /// Projects with activity this recent count as active.
+    pub within: Duration,
+    /// Drop protected findings instead of raising their risk.
+    pub exclude: bool,
 }
 
 /// Paths and patterns to ignore.
@@ -32,6 +74,10 @@ pub struct Defaults {
 pub struct IgnoreConfig {
     /// Absolute paths to never scan or delete.
     pub paths: Vec<PathBuf>,
+    /// `.gitignore`-style globs such as `**/vendor/**` or
+    /// `~/work/critical-*/node_modules`. A leading `!` re-includes a path.
+    /// See [`IgnoreRules`](crate::ignore::IgnoreRules).
+    pub patterns: Vec<String>,
 }
 
 /// Per-recognizer configuration.
@@ -40,6 +86,117 @@ pub struct IgnoreConfig {
 pub struct RecognizerConfig {
     /// Recognizer IDs to disable.
     pub disabled: HashSet<String>,
+    /// Settings for single recognizers, from `[recognizers.<id>]` tables.
+    #[serde(flatten)]
+    pub settings: HashMap<String, RecognizerSettings>,
+}
+
+/// Overrides for one recognizer. Unset fields keep the recognizer's own
+/// behavior.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RecognizerSettings {
+    /// Risk reported for every finding, replacing the recognizer's judgment.
+    #[serde(
+        deserialize_with = "deserialize_risk",
+        serialize_with = "serialize_risk",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub risk: Option<RiskLevel>,
+    /// Smallest finding to report, in bytes or as a size like "50MB".
+    #[serde(
+        deserialize_with = "deserialize_size",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub min_size: Option<u64>,
+    /// Project roots searched by a project-based recognizer instead of
+    /// `[scan] roots`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub roots: Option<Vec<PathBuf>>,
+    /// How many of the newest versions to keep where a recognizer reports
+    /// old versions (VS Code extensions). At least 1.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keep_latest: Option<usize>,
+    /// Command that clears a finding natively, replacing the recognizer's
+    /// own, e.g. "cargo clean --manifest-path {project}/Cargo.toml".
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub native: Option<String>,
+}
+
+const NO_SETTINGS: &RecognizerSettings = &RecognizerSettings {
+    risk: None,
+    min_size: None,
+    roots: None,
+    keep_latest: None,
+    native: None,
+};
+
+fn deserialize_risk<'de, D: Deserializer<'de>>(
+    d: D,
+) -> std::result::Result<Option<RiskLevel>, D::Error> {
+    let risk = String::deserialize(d)?;
+    risk.parse().map(Some).map_err(serde::de::Error::custom)
+}
+
+fn serialize_risk<S: Serializer>(
+    risk: &Option<RiskLevel>,
+    s: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    match risk {
+        Some(risk) => s.serialize_str(&risk.to_string()),
+        None => s.serialize_none(),
+    }
+}
+
+fn deserialize_size<'de, D: Deserializer<'de>>(d: D) -> std::result::Result<Option<u64>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Size {
+        Bytes(u64),
+        Text(String),
+    }
+    match Size::deserialize(d)? {
+        Size::Bytes(bytes) => Ok(Some(bytes)),
+        Size::Text(text) => size::parse_size(&text)
+            .map(Some)
+            .map_err(serde::de::Error::custom),
+    }
+}
+
+/// A recognizer defined in the config file.
+///
+/// Without `markers`, each of `paths` is a glob for directories to report,
+/// where a leading `~` and `$VAR` or `${VAR}` are expanded. With `markers`,
+/// the recognizer joins the project walk instead: every directory under the
+/// `[scan]` roots holding one of the marker files is a project, and `paths`
+/// are globs relative to it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomRecognizerConfig {
+    /// Identifier used in `[recognizers] disabled` and JSON output.
+    pub id: String,
+    /// Human-readable name shown by `diskard list targets`.
+    pub name: String,
+    /// One of the built-in categories, e.g. "node" or "generic".
+    #[serde(default = "default_custom_category")]
+    pub category: String,
+    pub paths: Vec<String>,
+    /// File names marking a project directory, e.g. "turbo.json".
+    #[serde(default)]
+    pub markers: Vec<String>,
+    /// "safe", "moderate", or "risky".
+    #[serde(default = "default_custom_risk")]
+    pub risk: String,
+    /// Shown with each finding.
+    #[serde(default)]
+    pub description: String,
+}
+
+fn default_custom_category() -> String {
+    "generic".to_string()
+}
+
+fn default_custom_risk() -> String {
+    "moderate".to_string()
 }
 
 impl Default for Defaults {
@@ -48,6 +205,29 @@ impl Default for Defaults {
             risk_tolerance: "moderate".to_string(),
             delete_mode: "trash".to_string(),
             min_size: 0,
+            native_timeout: "5m".to_string(),
+        }
+    }
+}
+
+impl Default for ScanConfig {
+    fn default() -> Self {
+        Self {
+            roots: [
+                "~/Developer",
+                "~/Projects",
+                "~/src",
+                "~/Documents",
+                "~/Desktop",
+            ]
+            .into_iter()
+            .map(PathBuf::from)
+            .collect(),
+            max_depth: 5,
+            follow_symlinks: false,
+            same_file_system: true,
+            protect_active_within: None,
+            protect_active: "raise".to_string(),
         }
     }
 }
@@ -70,6 +250,28 @@ impl Config {
     pub fn load_from(path: &Path) -> Result<Self> {
         let content = std::fs::read_to_string(path).map_err(|e| Error::io(path, e))?;
         let config: Config = toml::from_str(&content)?;
+        if let Some(within) = &config.scan.protect_active_within {
+            parse_duration(within).map_err(Error::Config)?;
+        }
+        if !["raise", "exclude"].contains(&config.scan.protect_active.to_lowercase().as_str()) {
+            return Err(Error::Config(format!(
+                "Invalid protect_active '{}': expected \"raise\" or \"exclude\"",
+                config.scan.protect_active
+            )));
+        }
+        parse_duration(&config.defaults.native_timeout)
+            .map_err(|e| Error::Config(format!("Invalid native_timeout: {e}")))?;
+        for (id, settings) in &config.recognizers.settings {
+            if settings.keep_latest == Some(0) {
+                return Err(Error::Config(format!(
+                    "Invalid keep_latest for recognizer '{id}': keep at least 1 version"
+                )));
+            }
+        }
+        for pattern in &config.ignore.patterns {
+            glob::Pattern::new(pattern.trim_start_matches('!'))
+                .map_err(|e| Error::Config(format!("Invalid ignore pattern '{pattern}': {e}")))?;
+        }
         Ok(config)
     }
 
@@ -95,12 +297,45 @@ impl Config {
         }
     }
 
+    /// Parse the delete mode string into a DeleteMode, defaulting to the trash.
+    pub fn delete_mode(&self) -> DeleteMode {
+        match self.defaults.delete_mode.to_lowercase().as_str() {
+            "permanent" => DeleteMode::Permanent,
+            "quarantine" => DeleteMode::Quarantine,
+            "native" => DeleteMode::Native,
+            _ => DeleteMode::Trash,
+        }
+    }
+
+    /// How long a native clean command may run, five minutes unless
+    /// `native_timeout` says otherwise.
+    pub fn native_timeout(&self) -> Duration {
+        parse_duration(&self.defaults.native_timeout).unwrap_or(Duration::from_secs(300))
+    }
+
+    /// How to protect active projects, if `[scan] protect_active_within` is set
+    /// to a valid duration.
+    pub fn active_protection(&self) -> Option<ActiveProtection> {
+        let within = parse_duration(self.scan.protect_active_within.as_deref()?).ok()?;
+        Some(ActiveProtection {
+            within,
+            exclude: self.scan.protect_active.eq_ignore_ascii_case("exclude"),
+        })
+    }
+
     /// Check if a recognizer is enabled.
     pub fn is_recognizer_enabled(&self, id: &str) -> bool {
         !self.recognizers.disabled.contains(id)
     }
 
-    /// Check if a path is ignored.
+    /// Overrides for the recognizer with `id`, empty if it has no table.
+    pub fn recognizer_settings(&self, id: &str) -> &RecognizerSettings {
+        self.recognizers.settings.get(id).unwrap_or(NO_SETTINGS)
+    }
+
+    /// Check if a path is under one of the ignored `paths`. Scans also apply
+    /// the patterns and ignore files, through
+    /// [`ScanContext::is_ignored`](crate::recognizer::ScanContext::is_ignored).
     pub fn is_path_ignored(&self, path: &Path) -> bool {
         self.ignore
             .paths
@@ -108,3 +343,28 @@ impl Config {
             .any(|ignored| path.starts_with(ignored))
     }
 }
+
+/// Parse a duration such as "3d", "12h", "30m", or "2w".
+pub fn parse_duration(s: &str) -> std::result::Result<Duration, String> {
+    let s = s.trim().to_lowercase();
+
+    let (num_str, multiplier) = if let Some(n) = s.strip_suffix('d') {
+        (n, 86_400u64)
+    } else if let Some(n) = s.strip_suffix('h') {
+        (n, 3_600u64)
+    } else if let Some(n) = s.strip_suffix('m') {
+        (n, 60u64)
+    } else if let Some(n) = s.strip_suffix('w') {
+        (n, 604_800u64)
+    } else if let Some(n) = s.strip_suffix('s') {
+        (n, 1u64)
+    } else {
+        return Err(format!("Invalid duration: {s}. Use e.g. 7d, 30d, 1h, 2w"));
+    };
+
+    let num: u64 = num_str
+        .parse()
+        .map_err(|_| format!("Invalid duration number: {num_str}"))?;
+
+    Ok(Duration::from_secs(num * multiplier))
+}
@@ -1,4 +1,7 @@
+use serde::Serialize;
+use std::fmt;
 use std::path::PathBuf;
+use std::str::FromStr;
 
 /// All errors that can occur in diskard-core.
 #[derive(Debug, thiserror::Error)]
@@ -18,6 +21,21 @@ pub enum Error {
     #[error("Trash error: {0}")]
     Trash(String),
 
+    #[error("Journal error: {0}")]
+    Journal(String),
+
+    #[error("Quarantine error: {0}")]
+    Quarantine(String),
+
+    #[error("Restore error: {0}")]
+    Restore(String),
+
+    #[error("Native clean error: {0}")]
+    Native(String),
+
+    #[error("Cache error: {0}")]
+    Cache(String),
+
     #[error("Scanner error: {0}")]
     Scanner(String),
 }
@@ -29,6 +47,172 @@ impl Error {
             source,
         }
     }
+
+    /// Classify the error for reports.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Io { source, .. } => ErrorKind::of_io(source),
+            Self::Trash(_) => ErrorKind::TrashUnavailable,
+            Self::Quarantine(_) => ErrorKind::Quarantine,
+            Self::Native(_) => ErrorKind::Native,
+            _ => ErrorKind::Other,
+        }
+    }
+}
+
+/// What went wrong with one path or recognizer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    PermissionDenied,
+    /// The path vanished, or something it needs doesn't exist.
+    NotFound,
+    /// Running processes are using the path.
+    InUse,
+    /// The system trash refused the path or isn't available.
+    TrashUnavailable,
+    Quarantine,
+    /// A native clean command failed or timed out.
+    Native,
+    /// Any other I/O failure.
+    Io,
+    Other,
+}
+
+impl ErrorKind {
+    /// Classify an I/O error.
+    pub fn of_io(error: &std::io::Error) -> Self {
+        match error.kind() {
+            std::io::ErrorKind::PermissionDenied => Self::PermissionDenied,
+            std::io::ErrorKind::NotFound => Self::NotFound,
+            _ => Self::Io,
+        }
+    }
+
+    pub const ALL: [Self; 8] = [
+        Self::PermissionDenied,
+        Self::NotFound,
+        Self::InUse,
+        Self::TrashUnavailable,
+        Self::Quarantine,
+        Self::Native,
+        Self::Io,
+        Self::Other,
+    ];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::PermissionDenied => "permission_denied",
+            Self::NotFound => "not_found",
+            Self::InUse => "in_use",
+            Self::TrashUnavailable => "trash_unavailable",
+            Self::Quarantine => "quarantine",
+            Self::Native => "native",
+            Self::Io => "io",
+            Self::Other => "other",
+        }
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ErrorKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let s = s.to_lowercase().replace('-', "_");
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.as_str() == s)
+            .ok_or_else(|| {
+                let known: Vec<&str> = Self::ALL.iter().map(|kind| kind.as_str()).collect();
+                format!(
+                    "unknown error kind '{s}' (expected one of {})",
+                    known.join(", ")
+                )
+            })
+    }
+}
+
+/// An error reported alongside scan or clean results rather than aborting
+/// them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ErrorRecord {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recognizer_id: Option<&'static str>,
+    pub kind: ErrorKind,
+    /// What the underlying error said, without the path.
+    pub message: String,
+}
+
+impl ErrorRecord {
+    pub fn new(kind: ErrorKind, message: impl Into<String>) -> Self {
+        Self {
+            path: None,
+            recognizer_id: None,
+            kind,
+            message: message.into(),
+        }
+    }
+
+    /// Record `error`, taking the path from it when it has one.
+    pub fn from_error(error: &Error) -> Self {
+        match error {
+            Error::Io { path, source } => Self::new(error.kind(), source.to_string()).at(path),
+            _ => Self::new(error.kind(), error.to_string()),
+        }
+    }
+
+    /// Set the path the error concerns.
+    pub fn at(mut self, path: impl Into<PathBuf>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    /// Set the recognizer the error concerns.
+    pub fn recognizer(mut self, id: &'static str) -> Self {
+        self.recognizer_id = Some(id);
+        self
+    }
+}
+
+impl fmt::Display for ErrorRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.path, self.recognizer_id) {
+            (Some(path), _) => write!(f, "{}: {}", path.display(), self.message),
+            (None, Some(id)) => write!(f, "{id}: {}", self.message),
+            (None, None) => f.write_str(&self.message),
+        }
+    }
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_kinds() {
+        for kind in ErrorKind::ALL {
+            assert_eq!(kind.to_string().parse::<ErrorKind>(), Ok(kind));
+        }
+        assert_eq!("Permission-Denied".parse(), Ok(ErrorKind::PermissionDenied));
+        assert!("gone".parse::<ErrorKind>().is_err());
+
+        let vanished = std::io::Error::from(std::io::ErrorKind::NotFound);
+        let record = ErrorRecord::from_error(&Error::io("/tmp/x", vanished));
+        assert_eq!(record.kind, ErrorKind::NotFound);
+        assert_eq!(record.path, Some(PathBuf::from("/tmp/x")));
+        assert_eq!(
+            Error::Trash("no trash".into()).kind(),
+            ErrorKind::TrashUnavailable
+        );
+    }
+}
@@ -1,6 +1,9 @@
-use serde::Serialize;
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Serializer};
+use std::collections::HashSet;
 use std::fmt;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use std::time::SystemTime;
 
 /// Risk level for a finding — how safe it is to delete.
@@ -24,6 +27,21 @@ impl fmt::Display for RiskLevel {
     }
 }
 
+impl FromStr for RiskLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "safe" => Ok(Self::Safe),
+            "moderate" => Ok(Self::Moderate),
+            "risky" => Ok(Self::Risky),
+            _ => Err(format!(
+                "unknown risk level '{s}' (expected safe, moderate, or risky)"
+            )),
+        }
+    }
+}
+
 impl RiskLevel {
     pub fn emoji(&self) -> &'static str {
         match self {
@@ -32,6 +50,14 @@ impl RiskLevel {
             Self::Risky => "🔴",
         }
     }
+
+    /// The next riskier level; `Risky` stays `Risky`.
+    pub fn raised(self) -> Self {
+        match self {
+            Self::Safe => Self::Moderate,
+            Self::Moderate | Self::Risky => Self::Risky,
+        }
+    }
 }
 
 /// Category of a finding — which tool/ecosystem it belongs to.
@@ -72,6 +98,38 @@ impl fmt::Display for Category {
     }
 }
 
+impl FromStr for Category {
+    type Err = String;
+
+    /// Parse a category by its display name or its `--category` spelling,
+    /// ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let all = [
+            Self::Xcode,
+            Self::Node,
+            Self::Homebrew,
+            Self::Python,
+            Self::Rust,
+            Self::Docker,
+            Self::Ollama,
+            Self::HuggingFace,
+            Self::Claude,
+            Self::VSCode,
+            Self::Gradle,
+            Self::CocoaPods,
+            Self::Generic,
+        ];
+        let squash = |name: &str| name.to_lowercase().replace([' ', '.'], "");
+        let wanted = squash(s);
+        all.into_iter()
+            .find(|c| {
+                let name = squash(&c.to_string());
+                name == wanted || name.strip_suffix("js") == Some(wanted.as_str())
+            })
+            .ok_or_else(|| format!("unknown category '{s}'"))
+    }
+}
+
 /// A single finding — a path that can be cleaned up.
 #[derive(Debug, Clone, Serialize)]
 pub struct Finding {
@@ -79,18 +137,89 @@ pub struct Finding {
     pub path: PathBuf,
     /// Which ecosystem this belongs to.
     pub category: Category,
+    /// ID of the recognizer that produced this finding.
+    pub recognizer_id: &'static str,
     /// How risky it is to delete.
     pub risk: RiskLevel,
-    /// Size in bytes.
+    /// Bytes freed by deleting it: space allocated on disk, counting
+    /// hard-linked files once.
     pub size_bytes: u64,
+    /// Sum of file lengths, as `ls` reports them. Exceeds `size_bytes` for
+    /// sparse files.
+    pub apparent_bytes: u64,
     /// Human-readable description of what this is.
     pub description: String,
-    /// Last modification time, if available.
-    #[serde(skip)]
+    /// When the item was last used or rebuilt, if known. Serialized as RFC 3339.
+    #[serde(serialize_with = "serialize_time")]
     pub last_modified: Option<SystemTime>,
+    /// How the owning tool clears it, for [`DeleteMode::Native`].
+    ///
+    /// [`DeleteMode::Native`]: crate::cleaner::DeleteMode::Native
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub native: Option<NativeAction>,
+    /// Other paths deleted along with `path`, such as blobs only it uses.
+    /// Their bytes are included in `size_bytes`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub extra_paths: Vec<PathBuf>,
+    /// Projects it was built from, such as those sharing a target directory
+    /// outside them. Checked for activity along with the project containing
+    /// `path`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub projects: Vec<PathBuf>,
+}
+
+/// A command the owning tool provides for clearing a finding, such as
+/// `cargo clean` or `npm cache clean --force`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NativeAction {
+    /// Program and arguments separated by spaces. In each argument `{path}`
+    /// stands for the finding's path and `{project}` for the directory
+    /// containing it.
+    pub command: String,
+}
+
+impl NativeAction {
+    pub fn new(command: impl Into<String>) -> Self {
+        Self {
+            command: command.into(),
+        }
+    }
+
+    /// The command line for the finding at `path`, program first.
+    pub fn argv(&self, path: &Path) -> Vec<String> {
+        let project = path.parent().unwrap_or(path);
+        self.command
+            .split_whitespace()
+            .map(|arg| {
+                arg.replace("{path}", &path.to_string_lossy())
+                    .replace("{project}", &project.to_string_lossy())
+            })
+            .collect()
+    }
+}
+
+/// The findings not inside another finding.
+pub(crate) fn outermost(findings: &[Finding]) -> Vec<&Finding> {
+    let paths: HashSet<&Path> = findings.iter().map(|f| f.path.as_path()).collect();
+    findings
+        .iter()
+        .filter(|f| !f.path.ancestors().skip(1).any(|a| paths.contains(a)))
+        .collect()
+}
+
+fn serialize_time<S: Serializer>(
+    time: &Option<SystemTime>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    time.map(DateTime::<Utc>::from).serialize(serializer)
 }
 
 impl Finding {
+    /// Every path deleting the finding removes: `path`, then `extra_paths`.
+    pub fn paths(&self) -> impl Iterator<Item = &Path> {
+        std::iter::once(self.path.as_path()).chain(self.extra_paths.iter().map(PathBuf::as_path))
+    }
+
     pub fn size_human(&self) -> String {
         crate::size::format_bytes(self.size_bytes)
     }
@@ -0,0 +1,239 @@
+use glob::{MatchOptions, Pattern};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+
+use crate::config::IgnoreConfig;
+use crate::paths::Locations;
+
+/// Name of the ignore file honored in any directory.
+pub const IGNORE_FILE: &str = ".diskardignore";
+
+/// `*` stays within one path component; only `**` crosses directories.
+const MATCH: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+/// One compiled pattern.
+#[derive(Debug)]
+struct Rule {
+    pattern: Pattern,
+    negated: bool,
+}
+
+impl Rule {
+    /// Compile a gitignore-style pattern.
+    ///
+    /// `base` is the escaped directory an ignore file lives in: its patterns
+    /// containing a `/` are relative to it, and the others match a name at any
+    /// depth below it. Config patterns have no base; relative ones match at
+    /// any depth.
+    fn new(line: &str, base: Option<&str>) -> Option<Self> {
+        let (negated, pattern) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        let pattern = pattern.trim_end_matches('/');
+        let full = match base {
+            None if pattern.starts_with('/') => pattern.to_string(),
+            None => format!("/**/{}", pattern.trim_start_matches("**/")),
+            Some(base) if pattern.contains('/') => {
+                format!("{base}/{}", pattern.trim_start_matches('/'))
+            }
+            Some(base) => format!("{base}/**/{}", pattern.trim_start_matches("**/")),
+        };
+        match Pattern::new(&full) {
+            Ok(pattern) => Some(Self { pattern, negated }),
+            Err(e) => {
+                log::warn!("Skipping ignore pattern '{line}': {e}");
+                None
+            }
+        }
+    }
+}
+
+/// The `[ignore]` settings together with any `.diskardignore` files.
+///
+/// Patterns follow `.gitignore`: a leading `!` re-includes what an earlier
+/// pattern ignored, the last matching pattern wins, and nothing can be
+/// re-included inside an ignored directory. Ignore files are read the first
+/// time a path below them is checked, or once per directory as a walk enters
+/// it (see [`IgnoreScope`]). One without patterns ignores its whole
+/// directory.
+#[derive(Debug)]
+pub struct IgnoreRules {
+    paths: Vec<PathBuf>,
+    rules: Vec<Rule>,
+    /// Rules from the ignore file in each directory looked at, if it has one.
+    files: Mutex<HashMap<PathBuf, Option<Arc<Vec<Rule>>>>>,
+}
+
+impl IgnoreRules {
+    /// Compile `[ignore]`, expanding `~` and environment variables in patterns.
+    pub fn new(config: &IgnoreConfig, locations: &Locations) -> Self {
+        let rules = config
+            .patterns
+            .iter()
+            .filter_map(|line| {
+                let (bang, pattern) = match line.strip_prefix('!') {
+                    Some(rest) => ("!", rest),
+                    None => ("", line.as_str()),
+                };
+                let Some(expanded) = locations.expand_pattern(pattern) else {
+                    log::warn!("Skipping ignore pattern '{line}': unset variable");
+                    return None;
+                };
+                Rule::new(&format!("{bang}{expanded}"), None)
+            })
+            .collect();
+        Self {
+            paths: config.paths.clone(),
+            rules,
+            files: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Whether `path`, or a directory containing it, is ignored.
+    pub fn is_ignored(&self, path: &Path) -> bool {
+        if self.paths.iter().any(|ignored| path.starts_with(ignored)) {
+            return true;
+        }
+
+        let mut files: Vec<Arc<Vec<Rule>>> = Vec::new();
+        let mut ancestors: Vec<&Path> = path.ancestors().collect();
+        ancestors.reverse();
+        for dir in ancestors {
+            if let Some(rules) = self.file_rules(dir) {
+                files.push(rules);
+            }
+            if self.matches(&files, dir) {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// The scope a walk of `root` starts in, reading the ignore file of every
+    /// directory above it now.
+    pub fn scope_above(&self, root: &Path) -> IgnoreScope {
+        let mut scope = IgnoreScope::default();
+        let mut ancestors: Vec<&Path> = root.ancestors().skip(1).collect();
+        ancestors.reverse();
+        for dir in ancestors {
+            if self.enter(&mut scope, dir) {
+                break;
+            }
+        }
+        scope
+    }
+
+    /// Add `dir`'s ignore file to `scope` as a walk enters `dir`, which must
+    /// hold the directories above it. Returns whether `dir` is ignored.
+    pub fn enter(&self, scope: &mut IgnoreScope, dir: &Path) -> bool {
+        if !scope.ignored {
+            if let Some(rules) = read_ignore_file(dir) {
+                scope.files.push(Arc::new(rules));
+            }
+            scope.ignored = self.is_ignored_in(scope, dir);
+        }
+        scope.ignored
+    }
+
+    /// Whether `path` is ignored, where `scope` holds the directories above
+    /// it. `path`'s own ignore file is only read when it is entered.
+    pub fn is_ignored_in(&self, scope: &IgnoreScope, path: &Path) -> bool {
+        scope.ignored
+            || self.paths.iter().any(|ignored| path.starts_with(ignored))
+            || self.matches(&scope.files, path)
+    }
+
+    /// Whether the last of the config rules and then `files` matching `dir`
+    /// ignores it.
+    fn matches(&self, files: &[Arc<Vec<Rule>>], dir: &Path) -> bool {
+        let last = self
+            .rules
+            .iter()
+            .chain(files.iter().flat_map(|rules| rules.iter()))
+            .rfind(|rule| rule.pattern.matches_path_with(dir, MATCH));
+        last.is_some_and(|rule| !rule.negated)
+    }
+
+    fn file_rules(&self, dir: &Path) -> Option<Arc<Vec<Rule>>> {
+        let mut files = self.files.lock().unwrap_or_else(PoisonError::into_inner);
+        files
+            .entry(dir.to_path_buf())
+            .or_insert_with(|| read_ignore_file(dir).map(Arc::new))
+            .clone()
+    }
+}
+
+/// The ignore files of the directories a walk passed through to reach one,
+/// carried down the walk so each is read once.
+#[derive(Debug, Clone, Default)]
+pub struct IgnoreScope {
+    files: Vec<Arc<Vec<Rule>>>,
+    /// Whether one of those directories is ignored.
+    ignored: bool,
+}
+
+/// Rules from `dir`'s ignore file, if it has one.
+fn read_ignore_file(dir: &Path) -> Option<Vec<Rule>> {
+    let content = std::fs::read_to_string(dir.join(IGNORE_FILE)).ok()?;
+    let base = Pattern::escape(dir.to_str()?);
+    let lines: Vec<&str> = content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .collect();
+    if lines.is_empty() {
+        return Some(vec![Rule {
+            pattern: Pattern::new(&base).ok()?,
+            negated: false,
+        }]);
+    }
+    Some(
+        lines
+            .iter()
+            .filter_map(|line| Rule::new(line, Some(&base)))
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::paths::Platform;
+
+    fn rules(patterns: &[&str]) -> IgnoreRules {
+        let config = IgnoreConfig {
+            paths: vec![PathBuf::from("/srv/keep")],
+            patterns: patterns.iter().map(|p| p.to_string()).collect(),
+        };
+        IgnoreRules::new(&config, &Locations::new("/home/me", Platform::Linux))
+    }
+
+    #[test]
+    fn test_patterns_match_like_gitignore() {
+        let rules = rules(&[
+            "**/vendor/**",
+            "~/work/critical-*/node_modules",
+            "*.keep",
+            "build",
+            "!/home/me/src/app/build",
+        ]);
+        let ignored = |path: &str| rules.is_ignored(Path::new(path));
+
+        assert!(ignored("/srv/keep/a"));
+        assert!(ignored("/home/me/src/app/vendor/cache"));
+        assert!(ignored("/home/me/work/critical-api/node_modules"));
+        assert!(ignored("/home/me/work/critical-api/node_modules/x"));
+        assert!(!ignored("/home/me/work/critical/api/node_modules"));
+        assert!(!ignored("/home/me/work/other/node_modules"));
+        assert!(ignored("/tmp/a.keep/target"));
+        assert!(ignored("/home/me/src/lib/build"));
+        assert!(!ignored("/home/me/src/app/build"));
+        assert!(!ignored("/home/me/src/app/target"));
+    }
+}
@@ -0,0 +1,349 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::cleaner::DeleteMode;
+use crate::error::{Error, Result};
+use crate::quarantine::Quarantine;
+
+/// One cleaned item recorded in the undo journal.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    /// Sequential identifier, unique within the journal.
+    pub id: u64,
+    /// When the item was cleaned.
+    pub timestamp: DateTime<Utc>,
+    /// Original location of the item.
+    pub path: PathBuf,
+    /// Size reported by the scan that found it.
+    pub size_bytes: u64,
+    /// Recognizer that produced the finding.
+    pub recognizer_id: String,
+    /// How the item was removed.
+    pub mode: DeleteMode,
+    /// Where the item ended up, when known: the path inside the quarantine, or
+    /// for trashed items the `.trashinfo` file on Linux, the shell item on
+    /// Windows, the path under `~/.Trash` on macOS.
+    pub trash_location: Option<PathBuf>,
+    /// When the item was restored, if it has been.
+    pub restored_at: Option<DateTime<Utc>>,
+    /// When the item was purged from the quarantine, if it has been.
+    #[serde(default)]
+    pub purged_at: Option<DateTime<Utc>>,
+}
+
+impl JournalEntry {
+    /// Whether `diskard restore` can bring this item back.
+    pub fn is_restorable(&self) -> bool {
+        matches!(self.mode, DeleteMode::Trash | DeleteMode::Quarantine)
+            && self.restored_at.is_none()
+            && self.purged_at.is_none()
+    }
+}
+
+/// A record of a clean operation, before the journal assigns it an ID.
+pub struct NewEntry {
+    pub path: PathBuf,
+    pub size_bytes: u64,
+    pub recognizer_id: String,
+    pub mode: DeleteMode,
+    pub trash_location: Option<PathBuf>,
+}
+
+/// Append-only log of everything diskard has cleaned, stored as JSON lines.
+#[derive(Debug, Clone)]
+pub struct Journal {
+    path: PathBuf,
+}
+
+impl Journal {
+    /// Standard journal path: `<data dir>/diskard/journal.jsonl`
+    /// (`~/.local/share` on Linux, `~/Library/Application Support` on macOS).
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|d| d.join("diskard").join("journal.jsonl"))
+    }
+
+    /// The journal at the standard path.
+    pub fn open_default() -> Result<Self> {
+        Self::default_path()
+            .map(Self::at)
+            .ok_or_else(|| Error::Journal("Cannot determine data directory".into()))
+    }
+
+    /// A journal stored at `path`. The file is created on first append.
+    pub fn at(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// All entries, oldest first. A missing journal file has no entries.
+    pub fn entries(&self) -> Result<Vec<JournalEntry>> {
+        let content = match std::fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(Error::io(&self.path, e)),
+        };
+
+        content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                serde_json::from_str(line)
+                    .map_err(|e| Error::Journal(format!("{}: {e}", self.path.display())))
+            })
+            .collect()
+    }
+
+    /// Record newly cleaned items, assigning each the next free ID.
+    pub fn append(&self, new_entries: Vec<NewEntry>) -> Result<Vec<JournalEntry>> {
+        if new_entries.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let next_id = self.entries()?.iter().map(|e| e.id).max().unwrap_or(0) + 1;
+        let timestamp = Utc::now();
+        let entries: Vec<JournalEntry> = new_entries
+            .into_iter()
+            .zip(next_id..)
+            .map(|(new, id)| JournalEntry {
+                id,
+                timestamp,
+                path: new.path,
+                size_bytes: new.size_bytes,
+                recognizer_id: new.recognizer_id,
+                mode: new.mode,
+                trash_location: new.trash_location,
+                restored_at: None,
+                purged_at: None,
+            })
+            .collect();
+
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| Error::io(parent, e))?;
+        }
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| Error::io(&self.path, e))?;
+        file.write_all(to_lines(&entries)?.as_bytes())
+            .map_err(|e| Error::io(&self.path, e))?;
+
+        Ok(entries)
+    }
+
+    /// Look up an entry by numeric ID, or the most recent restorable entry for a path.
+    pub fn find(&self, id_or_path: &str) -> Result<Option<JournalEntry>> {
+        let entries = self.entries()?;
+        if let Ok(id) = id_or_path.parse::<u64>() {
+            return Ok(entries.into_iter().find(|e| e.id == id));
+        }
+
+        let path = std::path::absolute(id_or_path).map_err(|e| Error::io(id_or_path, e))?;
+        Ok(entries
+            .into_iter()
+            .rev()
+            .find(|e| e.path == path && e.is_restorable()))
+    }
+
+    /// Look up the restorable entry for an item that ended up at `location`.
+    pub fn find_by_location(&self, location: &Path) -> Result<Option<JournalEntry>> {
+        Ok(self
+            .entries()?
+            .into_iter()
+            .rev()
+            .find(|e| e.is_restorable() && e.trash_location.as_deref() == Some(location)))
+    }
+
+    /// Mark an entry as restored now.
+    pub fn mark_restored(&self, id: u64) -> Result<()> {
+        self.update(id, |entry| entry.restored_at = Some(Utc::now()))
+    }
+
+    /// Mark an entry as purged from the quarantine now.
+    pub fn mark_purged(&self, id: u64) -> Result<()> {
+        self.update(id, |entry| entry.purged_at = Some(Utc::now()))
+    }
+
+    /// Apply `change` to the entry with `id` and rewrite the journal.
+    fn update(&self, id: u64, change: impl FnOnce(&mut JournalEntry)) -> Result<()> {
+        let mut entries = self.entries()?;
+        let Some(entry) = entries.iter_mut().find(|e| e.id == id) else {
+            return Err(Error::Journal(format!("No journal entry with ID {id}")));
+        };
+        change(entry);
+
+        // Write then rename, so an interrupted rewrite never loses the journal
+        let partial = self.path.with_extension("jsonl.tmp");
+        std::fs::write(&partial, to_lines(&entries)?).map_err(|e| Error::io(&partial, e))?;
+        std::fs::rename(&partial, &self.path).map_err(|e| Error::io(&self.path, e))
+    }
+}
+
+/// Serialize entries as JSON lines.
+fn to_lines(entries: &[JournalEntry]) -> Result<String> {
+    let mut buf = String::new();
+    for entry in entries {
+        buf.push_str(&serde_json::to_string(entry).map_err(|e| Error::Journal(e.to_string()))?);
+        buf.push('\n');
+    }
+    Ok(buf)
+}
+
+/// Move a trashed or quarantined item back to its original path and mark it restored.
+pub fn restore(journal: &Journal, quarantine: &Quarantine, entry: &JournalEntry) -> Result<()> {
+    if entry.restored_at.is_some() {
+        return Err(Error::Restore(format!(
+            "{} was already restored",
+            entry.path.display()
+        )));
+    }
+    if entry.purged_at.is_some() {
+        return Err(Error::Restore(format!(
+            "{} was purged from the quarantine",
+            entry.path.display()
+        )));
+    }
+    if !entry.is_restorable() {
+        return Err(Error::Restore(format!(
+            "{} was deleted with mode '{}' and cannot be restored",
+            entry.path.display(),
+            entry.mode,
+        )));
+    }
+    if entry.path.exists() {
+        return Err(Error::Restore(format!(
+            "{} already exists — move it away first",
+            entry.path.display()
+        )));
+    }
+
+    if entry.mode == DeleteMode::Quarantine {
+        let held = match &entry.trash_location {
+            Some(location) => quarantine.find_stored(location)?,
+            None => None,
+        };
+        let Some(held) = held else {
+            return Err(Error::Restore(format!(
+                "{} is no longer in quarantine",
+                entry.path.display()
+            )));
+        };
+        quarantine.restore(&held)?;
+    } else {
+        platform::restore(entry)?;
+    }
+    journal.mark_restored(entry.id)
+}
+
+/// Locate the trash entries for paths that were just moved to the trash.
+pub(crate) fn locate_trashed(paths: &[&Path]) -> Vec<Option<PathBuf>> {
+    platform::locate_trashed(paths)
+}
+
+#[cfg(any(
+    target_os = "windows",
+    all(
+        unix,
+        not(target_os = "macos"),
+        not(target_os = "ios"),
+        not(target_os = "android")
+    )
+))]
+mod platform {
+    use super::JournalEntry;
+    use crate::error::{Error, Result};
+    use std::path::{Path, PathBuf};
+    use trash::os_limited;
+
+    pub fn locate_trashed(paths: &[&Path]) -> Vec<Option<PathBuf>> {
+        let items = os_limited::list().unwrap_or_default();
+        paths
+            .iter()
+            .map(|path| {
+                items
+                    .iter()
+                    .filter(|item| item.original_path() == *path)
+                    .max_by_key(|item| item.time_deleted)
+                    .map(|item| PathBuf::from(&item.id))
+            })
+            .collect()
+    }
+
+    pub fn restore(entry: &JournalEntry) -> Result<()> {
+        let items = os_limited::list().map_err(|e| Error::Trash(e.to_string()))?;
+        let candidates = items
+            .into_iter()
+            .filter(|item| item.original_path() == entry.path);
+        let item = match &entry.trash_location {
+            Some(location) => candidates
+                .filter(|item| Path::new(&item.id) == location)
+                .max_by_key(|item| item.time_deleted),
+            None => candidates.max_by_key(|item| item.time_deleted),
+        };
+        let Some(item) = item else {
+            return Err(Error::Restore(format!(
+                "{} is no longer in the trash",
+                entry.path.display()
+            )));
+        };
+        os_limited::restore_all([item]).map_err(|e| Error::Trash(e.to_string()))
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::JournalEntry;
+    use crate::error::{Error, Result};
+    use std::path::{Path, PathBuf};
+
+    /// Finder moves items to `~/.Trash`, keeping the name unless it collides.
+    pub fn locate_trashed(paths: &[&Path]) -> Vec<Option<PathBuf>> {
+        let trash_dir = dirs::home_dir().map(|home| home.join(".Trash"));
+        paths
+            .iter()
+            .map(|path| {
+                let trashed = trash_dir.as_ref()?.join(path.file_name()?);
+                trashed.exists().then_some(trashed)
+            })
+            .collect()
+    }
+
+    pub fn restore(entry: &JournalEntry) -> Result<()> {
+        let Some(location) = entry.trash_location.as_ref().filter(|l| l.exists()) else {
+            return Err(Error::Restore(format!(
+                "{} is no longer in the trash",
+                entry.path.display()
+            )));
+        };
+        if let Some(parent) = entry.path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| Error::io(parent, e))?;
+        }
+        std::fs::rename(location, &entry.path).map_err(|e| Error::io(location, e))
+    }
+}
+
+#[cfg(not(any(
+    target_os = "windows",
+    target_os = "macos",
+    all(unix, not(target_os = "ios"), not(target_os = "android"))
+)))]
+mod platform {
+    use super::JournalEntry;
+    use crate::error::{Error, Result};
+    use std::path::{Path, PathBuf};
+
+    pub fn locate_trashed(paths: &[&Path]) -> Vec<Option<PathBuf>> {
+        vec![None; paths.len()]
+    }
+
+    pub fn restore(_entry: &JournalEntry) -> Result<()> {
+        Err(Error::Restore(
+            "Restoring from the trash is not supported on this platform".into(),
+        ))
+    }
+}
@@ -1,8 +1,18 @@
+pub mod activity;
+pub mod cache;
 pub mod cleaner;
 pub mod config;
 pub mod error;
 pub mod finding;
+pub mod ignore;
+pub mod journal;
+pub mod mounts;
+pub mod paths;
+pub mod processes;
+pub mod project;
+pub mod quarantine;
 pub mod recognizer;
 pub mod recognizers;
 pub mod scanner;
 pub mod size;
+pub mod walk;
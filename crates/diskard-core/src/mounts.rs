@@ -0,0 +1,98 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::finding::Finding;
+use crate::size::disk_usage;
+use crate::walk;
+
+/// Space on one mounted file system, and how much of it the findings there
+/// would free.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MountUsage {
+    /// Top directory of the file system.
+    pub mount_point: PathBuf,
+    pub total_bytes: u64,
+    pub free_bytes: u64,
+    /// Combined size of the findings on this file system.
+    pub reclaimable_bytes: u64,
+    /// Number of findings on this file system.
+    pub findings: usize,
+}
+
+impl MountUsage {
+    /// Usage of the file system holding `path`, with nothing reclaimable yet.
+    pub fn of(path: &Path) -> Option<Self> {
+        let mount_point = mount_point(path)?;
+        let (total_bytes, free_bytes) = disk_usage(&mount_point)?;
+        Some(Self {
+            mount_point,
+            total_bytes,
+            free_bytes,
+            reclaimable_bytes: 0,
+            findings: 0,
+        })
+    }
+
+    pub fn used_bytes(&self) -> u64 {
+        self.total_bytes.saturating_sub(self.free_bytes)
+    }
+}
+
+/// Top directory of the file system holding `path`: its highest ancestor on
+/// the same device. Paths that don't exist yet resolve through their nearest
+/// existing ancestor.
+pub fn mount_point(path: &Path) -> Option<PathBuf> {
+    let existing = path
+        .ancestors()
+        .find(|p| std::fs::symlink_metadata(p).is_ok())?;
+    let existing = existing
+        .canonicalize()
+        .unwrap_or_else(|_| existing.to_path_buf());
+    let dev_of = |p: &Path| std::fs::symlink_metadata(p).map(|m| walk::device(&m)).ok();
+    let dev = dev_of(&existing)?;
+
+    let mut top = existing.as_path();
+    while let Some(parent) = top.parent() {
+        if dev_of(parent) != Some(dev) {
+            break;
+        }
+        top = parent;
+    }
+    Some(top.to_path_buf())
+}
+
+/// Group findings by the file system they live on, largest reclaimable first.
+/// Findings whose file system can't be determined are left out.
+pub fn group_by_mount<'a>(findings: impl IntoIterator<Item = &'a Finding>) -> Vec<MountUsage> {
+    // Resolving a mount point stats every ancestor, so do it once per parent
+    // directory rather than once per finding.
+    let mut by_parent: HashMap<PathBuf, Option<PathBuf>> = HashMap::new();
+    let mut mounts: Vec<MountUsage> = Vec::new();
+
+    for finding in findings {
+        let parent = finding.path.parent().unwrap_or(&finding.path);
+        let mount = by_parent
+            .entry(parent.to_path_buf())
+            .or_insert_with(|| mount_point(&finding.path));
+        let Some(mount) = mount else {
+            continue;
+        };
+
+        let index = match mounts.iter().position(|m| &m.mount_point == mount) {
+            Some(index) => index,
+            None => match MountUsage::of(mount) {
+                Some(usage) => {
+                    mounts.push(usage);
+                    mounts.len() - 1
+                }
+                None => continue,
+            },
+        };
+        mounts[index].reclaimable_bytes += finding.size_bytes;
+        mounts[index].findings += 1;
+    }
+
+    mounts.sort_by_key(|m| std::cmp::Reverse(m.reclaimable_bytes));
+    mounts
+}
@@ -0,0 +1,211 @@
+use std::collections::HashMap;
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+
+/// Operating system family — decides which per-platform cache locations apply.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Platform {
+    MacOs,
+    Linux,
+    Other,
+}
+
+impl Platform {
+    /// The platform diskard was built for.
+    pub fn current() -> Self {
+        if cfg!(target_os = "macos") {
+            Self::MacOs
+        } else if cfg!(target_os = "linux") {
+            Self::Linux
+        } else {
+            Self::Other
+        }
+    }
+}
+
+/// Home directory, platform and environment that recognizers resolve paths against.
+///
+/// [`Locations::detect`] snapshots the real process environment; tests build one
+/// with [`Locations::new`] pointing at a temporary home directory.
+#[derive(Debug, Clone)]
+pub struct Locations {
+    home: PathBuf,
+    platform: Platform,
+    env: HashMap<OsString, OsString>,
+}
+
+impl Locations {
+    /// Detect locations from the current user and process environment.
+    pub fn detect() -> Option<Self> {
+        let home = dirs::home_dir()?;
+        Some(Self {
+            home,
+            platform: Platform::current(),
+            env: std::env::vars_os().collect(),
+        })
+    }
+
+    /// Locations rooted at `home` with an empty environment.
+    pub fn new(home: impl Into<PathBuf>, platform: Platform) -> Self {
+        Self {
+            home: home.into(),
+            platform,
+            env: HashMap::new(),
+        }
+    }
+
+    /// Set an environment variable visible to recognizers.
+    pub fn with_var(mut self, key: &str, value: impl Into<OsString>) -> Self {
+        self.env.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn home(&self) -> &Path {
+        &self.home
+    }
+
+    pub fn platform(&self) -> Platform {
+        self.platform
+    }
+
+    /// An environment variable interpreted as a directory.
+    ///
+    /// A leading `~` is expanded to the home directory. Unset, empty and
+    /// relative values are ignored, as the XDG spec requires.
+    pub fn var_path(&self, key: &str) -> Option<PathBuf> {
+        let value = self.env.get(OsStr::new(key))?;
+        let value = value
+            .to_str()
+            .map_or_else(|| PathBuf::from(value), |s| self.expand_tilde(s));
+        value.is_absolute().then_some(value)
+    }
+
+    /// Expand a leading `~` or `~/` against the home directory.
+    pub fn expand_tilde(&self, path: &str) -> PathBuf {
+        if path == "~" {
+            self.home.clone()
+        } else if let Some(rest) = path.strip_prefix("~/") {
+            self.home.join(rest)
+        } else {
+            PathBuf::from(path)
+        }
+    }
+
+    /// Expand a leading `~` and `$VAR` or `${VAR}` references in a glob
+    /// pattern. Substituted text is escaped so it only matches literally.
+    /// Returns `None` if a referenced variable is unset or not UTF-8.
+    pub fn expand_pattern(&self, pattern: &str) -> Option<String> {
+        let mut expanded = String::new();
+        let mut rest = pattern;
+        if rest == "~" || rest.starts_with("~/") {
+            expanded.push_str(&glob::Pattern::escape(self.home.to_str()?));
+            rest = &rest[1..];
+        }
+
+        while let Some(start) = rest.find('$') {
+            expanded.push_str(&rest[..start]);
+            let after = &rest[start + 1..];
+            let (name, remainder) = match after.strip_prefix('{') {
+                Some(braced) => {
+                    let end = braced.find('}')?;
+                    (&braced[..end], &braced[end + 1..])
+                }
+                None => {
+                    let end = after
+                        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                        .unwrap_or(after.len());
+                    (&after[..end], &after[end..])
+                }
+            };
+            if name.is_empty() {
+                expanded.push('$');
+            } else {
+                let value = self.env.get(OsStr::new(name))?.to_str()?;
+                expanded.push_str(&glob::Pattern::escape(value));
+            }
+            rest = remainder;
+        }
+        expanded.push_str(rest);
+        Some(expanded)
+    }
+
+    /// `$XDG_CACHE_HOME`, falling back to `~/.cache`.
+    pub fn xdg_cache_home(&self) -> PathBuf {
+        self.var_path("XDG_CACHE_HOME")
+            .unwrap_or_else(|| self.home.join(".cache"))
+    }
+
+    /// `$XDG_DATA_HOME`, falling back to `~/.local/share`.
+    pub fn xdg_data_home(&self) -> PathBuf {
+        self.var_path("XDG_DATA_HOME")
+            .unwrap_or_else(|| self.home.join(".local/share"))
+    }
+
+    /// Per-user cache root: `~/Library/Caches` on macOS, the XDG cache home elsewhere.
+    pub fn cache_dir(&self) -> PathBuf {
+        match self.platform {
+            Platform::MacOs => self.home.join("Library/Caches"),
+            _ => self.xdg_cache_home(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_dir_per_platform() {
+        let mac = Locations::new("/Users/me", Platform::MacOs);
+        assert_eq!(mac.cache_dir(), PathBuf::from("/Users/me/Library/Caches"));
+
+        let linux = Locations::new("/home/me", Platform::Linux);
+        assert_eq!(linux.cache_dir(), PathBuf::from("/home/me/.cache"));
+        assert_eq!(
+            linux.xdg_data_home(),
+            PathBuf::from("/home/me/.local/share")
+        );
+    }
+
+    #[test]
+    fn test_xdg_overrides() {
+        let loc = Locations::new("/home/me", Platform::Linux)
+            .with_var("XDG_CACHE_HOME", "/var/cache/me")
+            .with_var("XDG_DATA_HOME", "relative/ignored");
+        assert_eq!(loc.cache_dir(), PathBuf::from("/var/cache/me"));
+        assert_eq!(loc.xdg_data_home(), PathBuf::from("/home/me/.local/share"));
+    }
+
+    #[test]
+    fn test_var_path_expands_tilde_and_skips_empty() {
+        let loc = Locations::new("/home/me", Platform::Linux)
+            .with_var("CARGO_HOME", "~/tools/cargo")
+            .with_var("PIP_CACHE_DIR", "");
+        assert_eq!(
+            loc.var_path("CARGO_HOME"),
+            Some(PathBuf::from("/home/me/tools/cargo"))
+        );
+        assert_eq!(loc.var_path("PIP_CACHE_DIR"), None);
+        assert_eq!(loc.var_path("UNSET"), None);
+    }
+
+    #[test]
+    fn test_expand_pattern() {
+        let loc = Locations::new("/home/me", Platform::Linux)
+            .with_var("TOOL_HOME", "/opt/tool[1]")
+            .with_var("USER", "me");
+        assert_eq!(
+            loc.expand_pattern("~/.cache/tool-*").as_deref(),
+            Some("/home/me/.cache/tool-*")
+        );
+        assert_eq!(
+            loc.expand_pattern("$TOOL_HOME/cache/${USER}_*").as_deref(),
+            Some("/opt/tool[[]1[]]/cache/me_*")
+        );
+        assert_eq!(
+            loc.expand_pattern("/tmp/~x/$").as_deref(),
+            Some("/tmp/~x/$")
+        );
+        assert_eq!(loc.expand_pattern("$UNSET/cache"), None);
+    }
+}
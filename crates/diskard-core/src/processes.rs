@@ -0,0 +1,101 @@
+use serde::Serialize;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// A running process with a file open, or its working directory, inside a
+/// path about to be deleted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ProcessUse {
+    pub pid: u32,
+    /// Command name, as `ps` shows it.
+    pub name: String,
+}
+
+impl fmt::Display for ProcessUse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (pid {})", self.name, self.pid)
+    }
+}
+
+/// Join process uses for a message: "ollama (pid 812), java (pid 90)".
+pub fn describe(uses: &[ProcessUse]) -> String {
+    uses.iter()
+        .map(ProcessUse::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// For each of `paths`, the other processes using something inside it.
+///
+/// Reads `/proc/*/fd` and `/proc/*/cwd` once for all paths. Processes of
+/// other users can't be inspected without privileges and are missed. Off
+/// Linux nothing is found.
+pub fn processes_using(paths: &[&Path]) -> Vec<Vec<ProcessUse>> {
+    let mut uses = vec![Vec::new(); paths.len()];
+    let processes = open_paths();
+    if processes.is_empty() {
+        return uses;
+    }
+
+    // /proc reports resolved paths, so compare against resolved ones
+    let resolved: Vec<PathBuf> = paths
+        .iter()
+        .map(|p| p.canonicalize().unwrap_or_else(|_| p.to_path_buf()))
+        .collect();
+    for (process, open) in &processes {
+        for (path, uses) in resolved.iter().zip(uses.iter_mut()) {
+            if open.iter().any(|o| o.starts_with(path)) {
+                uses.push(process.clone());
+            }
+        }
+    }
+    uses
+}
+
+/// Every readable process except this one, with its working directory and
+/// the files it has open.
+#[cfg(target_os = "linux")]
+fn open_paths() -> Vec<(ProcessUse, Vec<PathBuf>)> {
+    let Ok(entries) = std::fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+    let me = std::process::id();
+
+    let mut processes = Vec::new();
+    for entry in entries.flatten() {
+        let Some(pid) = entry.file_name().to_str().and_then(|n| n.parse().ok()) else {
+            continue;
+        };
+        if pid == me {
+            continue;
+        }
+        let dir = entry.path();
+
+        let mut open: Vec<PathBuf> = std::fs::read_link(dir.join("cwd")).into_iter().collect();
+        if let Ok(fds) = std::fs::read_dir(dir.join("fd")) {
+            // Sockets, pipes and the like read back as "socket:[1234]"; files
+            // deleted while open are already gone from the tree
+            open.extend(
+                fds.flatten()
+                    .filter_map(|fd| std::fs::read_link(fd.path()).ok())
+                    .filter(|target| {
+                        target.is_absolute() && !target.to_string_lossy().ends_with(" (deleted)")
+                    }),
+            );
+        }
+        if open.is_empty() {
+            continue;
+        }
+
+        let name = std::fs::read_to_string(dir.join("comm"))
+            .map(|comm| comm.trim_end().to_string())
+            .unwrap_or_else(|_| "?".into());
+        processes.push((ProcessUse { pid, name }, open));
+    }
+    processes
+}
+
+#[cfg(not(target_os = "linux"))]
+fn open_paths() -> Vec<(ProcessUse, Vec<PathBuf>)> {
+    Vec::new()
+}
@@ -0,0 +1,165 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use crate::finding::Finding;
+use crate::recognizer::ScanContext;
+use crate::walk::walk_dir;
+
+/// Tells whether a directory holds a detector's artifacts.
+pub type ArtifactCheck = fn(&Path) -> bool;
+
+/// Detects build artifacts of one ecosystem inside project directories.
+///
+/// Detectors don't walk the filesystem themselves. The scanner walks the
+/// configured project roots once for all of them, and hands each detector the
+/// directories that contain one of its marker files.
+pub trait ProjectDetector: Send + Sync {
+    /// File names whose presence marks a project directory (e.g. `Cargo.toml`).
+    fn markers(&self) -> &[&'static str];
+
+    /// Directory names holding artifacts (e.g. `target`). The walker reports
+    /// them but doesn't descend into those [`artifact_check`] accepts.
+    ///
+    /// [`artifact_check`]: ProjectDetector::artifact_check
+    fn artifact_dirs(&self) -> &[&'static str] {
+        &[]
+    }
+
+    /// Whether a directory named in `artifact_dirs` really holds this
+    /// detector's artifacts. Override for names ordinary directories share, so
+    /// that other projects inside those are still found.
+    fn artifact_check(&self) -> ArtifactCheck {
+        |_| true
+    }
+
+    /// Whether findings are protected when their project is active (see
+    /// [`ActiveProjects`](crate::activity::ActiveProjects)). Turn off for
+    /// findings that aren't build artifacts.
+    fn guards_active_projects(&self) -> bool {
+        true
+    }
+
+    /// Turn the directories containing a marker into findings.
+    fn detect(&self, ctx: &ScanContext, projects: &[PathBuf]) -> Vec<Finding>;
+}
+
+/// Walk every project root once and collect, for each detector, the
+/// directories containing one of its markers.
+///
+/// The result is index-aligned with `detectors`. Hidden directories and
+/// artifact directories are not descended into; hidden files are still seen.
+pub fn find_projects(ctx: &ScanContext, detectors: &[&dyn ProjectDetector]) -> Vec<Vec<PathBuf>> {
+    let roots = ctx.project_roots();
+    let searches: Vec<(&dyn ProjectDetector, Vec<PathBuf>)> =
+        detectors.iter().map(|d| (*d, roots.clone())).collect();
+    find_projects_in(ctx, &searches)
+}
+
+/// Like [`find_projects`], with each detector searching its own roots.
+///
+/// Roots shared by several detectors, or nested inside another detector's
+/// root, are still walked only once.
+pub fn find_projects_in(
+    ctx: &ScanContext,
+    searches: &[(&dyn ProjectDetector, Vec<PathBuf>)],
+) -> Vec<Vec<PathBuf>> {
+    let mut projects: Vec<Vec<PathBuf>> = vec![Vec::new(); searches.len()];
+    if searches.is_empty() {
+        return projects;
+    }
+    let detectors: Vec<&dyn ProjectDetector> = searches.iter().map(|(d, _)| *d).collect();
+
+    let mut by_marker: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (i, detector) in detectors.iter().enumerate() {
+        for marker in detector.markers() {
+            by_marker.entry(marker).or_default().push(i);
+        }
+    }
+
+    // Each artifact name with the checks of the detectors claiming it
+    let mut pruned: HashMap<String, Vec<ArtifactCheck>> = HashMap::new();
+    for detector in &detectors {
+        for name in detector.artifact_dirs() {
+            pruned
+                .entry(name.to_string())
+                .or_default()
+                .push(detector.artifact_check());
+        }
+    }
+    let pruned = Arc::new(pruned);
+
+    let mut seen: HashSet<(usize, PathBuf)> = HashSet::new();
+
+    let mut walked: Vec<PathBuf> = Vec::new();
+    let mut roots: Vec<&PathBuf> = searches.iter().flat_map(|(_, roots)| roots).collect();
+    roots.sort();
+    for root in roots {
+        if !walked.iter().any(|kept| root.starts_with(kept)) {
+            walked.push(root.clone());
+        }
+    }
+
+    for root in walked {
+        let pruned = Arc::clone(&pruned);
+        let walker = ctx
+            .project_walker(&root, move |entry| {
+                let name = entry.file_name().to_string_lossy();
+                name.starts_with('.')
+                    || pruned.get(name.as_ref()).is_some_and(|checks| {
+                        let path = entry.path();
+                        checks.iter().any(|check| check(&path))
+                    })
+            })
+            .skip_hidden(false);
+
+        for entry in walker.into_iter().flatten() {
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let Some(indices) = entry.file_name().to_str().and_then(|n| by_marker.get(n)) else {
+                continue;
+            };
+            let Some(dir) = entry.path().parent().map(|p| p.to_path_buf()) else {
+                continue;
+            };
+            for &i in indices {
+                let (_, roots) = &searches[i];
+                if !roots.iter().any(|r| dir.starts_with(r)) {
+                    continue;
+                }
+                if seen.insert((i, dir.clone())) {
+                    projects[i].push(dir.clone());
+                }
+            }
+        }
+    }
+
+    projects
+}
+
+/// Newest modification time among a project's own `entries` (files or
+/// directories, relative to `project`), such as its manifest and sources.
+///
+/// An artifact directory is only as stale as the project it was built from, so
+/// detectors combine this with the artifact's own last use. Access times are
+/// ignored here: editors and search tools read sources without using them.
+pub fn last_activity(project: &Path, entries: &[&str]) -> Option<SystemTime> {
+    entries
+        .iter()
+        .map(|entry| project.join(entry))
+        .filter_map(|path| {
+            if path.is_dir() {
+                walk_dir(&path)
+                    .skip_hidden(false)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|e| e.metadata().ok()?.modified().ok())
+                    .max()
+            } else {
+                path.metadata().ok()?.modified().ok()
+            }
+        })
+        .max()
+}
@@ -0,0 +1,322 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::error::{Error, ErrorRecord, Result};
+use crate::journal::Journal;
+#[cfg(unix)]
+use crate::mounts::mount_point;
+
+/// Name of the per-filesystem holding directory used when an item lives on a
+/// different filesystem than the quarantine root.
+const FOREIGN_DIR: &str = ".diskard-quarantine";
+
+/// One item held in quarantine.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuarantineEntry {
+    /// Sequential identifier, unique within the quarantine.
+    pub id: u64,
+    /// Where the item lived before it was quarantined.
+    pub original_path: PathBuf,
+    /// Where the item lives now.
+    pub stored_path: PathBuf,
+    /// Size reported by the scan that found it.
+    pub size_bytes: u64,
+    /// Recognizer that produced the finding.
+    pub recognizer_id: String,
+    /// When the item was moved into quarantine.
+    pub quarantined_at: DateTime<Utc>,
+}
+
+impl QuarantineEntry {
+    /// Time spent in quarantine so far.
+    pub fn age(&self) -> Duration {
+        (Utc::now() - self.quarantined_at)
+            .to_std()
+            .unwrap_or_default()
+    }
+}
+
+/// Results from purging expired quarantine entries.
+pub struct PurgeResult {
+    pub purged_count: usize,
+    pub freed_bytes: u64,
+    pub errors: Vec<ErrorRecord>,
+}
+
+/// A diskard-managed holding area for cleaned items.
+///
+/// Items are renamed into the quarantine, never copied, so quarantining is
+/// atomic and instant. Each item gets a numbered slot directory holding it
+/// under its original name, and a `<id>.json` metadata file in the root.
+/// Items on another filesystem are held in a `.diskard-quarantine` directory at
+/// the top of that filesystem; their metadata still lives in the root.
+#[derive(Debug, Clone)]
+pub struct Quarantine {
+    root: PathBuf,
+}
+
+impl Quarantine {
+    /// Standard quarantine path: `<data dir>/diskard/quarantine`.
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|d| d.join("diskard").join("quarantine"))
+    }
+
+    /// The quarantine at the standard path.
+    pub fn open_default() -> Result<Self> {
+        Self::default_path()
+            .map(Self::at)
+            .ok_or_else(|| Error::Quarantine("Cannot determine data directory".into()))
+    }
+
+    /// A quarantine rooted at `root`. The directory is created on first use.
+    pub fn at(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Move `path` into quarantine and record where it came from.
+    pub fn store(
+        &self,
+        path: &Path,
+        size_bytes: u64,
+        recognizer_id: &str,
+    ) -> Result<QuarantineEntry> {
+        std::fs::create_dir_all(&self.root).map_err(|e| Error::io(&self.root, e))?;
+        let original_path = std::path::absolute(path).map_err(|e| Error::io(path, e))?;
+        let holding_dir = self.holding_dir_for(&original_path)?;
+
+        let mut id = self.entries()?.iter().map(|e| e.id).max().unwrap_or(0) + 1;
+        let slot = loop {
+            let slot = holding_dir.join(id.to_string());
+            match std::fs::create_dir(&slot) {
+                Ok(()) if !self.meta_path(id).exists() => break slot,
+                Ok(()) => {
+                    let _ = std::fs::remove_dir(&slot);
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {}
+                Err(e) => return Err(Error::io(&slot, e)),
+            }
+            id += 1;
+        };
+
+        let name = original_path
+            .file_name()
+            .map(ToOwned::to_owned)
+            .unwrap_or_else(|| "item".into());
+        let stored_path = slot.join(name);
+
+        if let Err(e) = std::fs::rename(&original_path, &stored_path) {
+            let _ = std::fs::remove_dir(&slot);
+            if e.kind() == std::io::ErrorKind::CrossesDevices {
+                return Err(Error::Quarantine(format!(
+                    "{} is on a different filesystem than {}",
+                    original_path.display(),
+                    holding_dir.display(),
+                )));
+            }
+            return Err(Error::io(&original_path, e));
+        }
+
+        let entry = QuarantineEntry {
+            id,
+            original_path,
+            stored_path,
+            size_bytes,
+            recognizer_id: recognizer_id.to_string(),
+            quarantined_at: Utc::now(),
+        };
+        if let Err(e) = self.write_meta(&entry) {
+            // Without metadata the item could never be found again, so undo the move
+            let _ = std::fs::rename(&entry.stored_path, &entry.original_path);
+            let _ = std::fs::remove_dir(&slot);
+            return Err(e);
+        }
+
+        Ok(entry)
+    }
+
+    /// All quarantined items, oldest first. A missing quarantine has no entries.
+    pub fn entries(&self) -> Result<Vec<QuarantineEntry>> {
+        let read_dir = match std::fs::read_dir(&self.root) {
+            Ok(read_dir) => read_dir,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(Error::io(&self.root, e)),
+        };
+
+        let mut entries = Vec::new();
+        for dir_entry in read_dir {
+            let path = dir_entry.map_err(|e| Error::io(&self.root, e))?.path();
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            let content = std::fs::read_to_string(&path).map_err(|e| Error::io(&path, e))?;
+            let entry: QuarantineEntry = serde_json::from_str(&content)
+                .map_err(|e| Error::Quarantine(format!("{}: {e}", path.display())))?;
+            entries.push(entry);
+        }
+
+        entries.sort_by_key(|e| (e.quarantined_at, e.id));
+        Ok(entries)
+    }
+
+    /// Look up an entry by numeric ID, or the most recent entry for an original path.
+    pub fn find(&self, id_or_path: &str) -> Result<Option<QuarantineEntry>> {
+        let entries = self.entries()?;
+        if let Ok(id) = id_or_path.parse::<u64>() {
+            return Ok(entries.into_iter().find(|e| e.id == id));
+        }
+
+        let path = std::path::absolute(id_or_path).map_err(|e| Error::io(id_or_path, e))?;
+        Ok(entries.into_iter().rev().find(|e| e.original_path == path))
+    }
+
+    /// Look up the entry holding `stored_path`.
+    pub fn find_stored(&self, stored_path: &Path) -> Result<Option<QuarantineEntry>> {
+        Ok(self
+            .entries()?
+            .into_iter()
+            .find(|e| e.stored_path == stored_path))
+    }
+
+    /// Move a quarantined item back to its original path.
+    pub fn restore(&self, entry: &QuarantineEntry) -> Result<()> {
+        if entry.original_path.exists() {
+            return Err(Error::Restore(format!(
+                "{} already exists — move it away first",
+                entry.original_path.display()
+            )));
+        }
+        if std::fs::symlink_metadata(&entry.stored_path).is_err() {
+            return Err(Error::Restore(format!(
+                "{} is no longer in quarantine",
+                entry.original_path.display()
+            )));
+        }
+
+        if let Some(parent) = entry.original_path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| Error::io(parent, e))?;
+        }
+        std::fs::rename(&entry.stored_path, &entry.original_path)
+            .map_err(|e| Error::io(&entry.stored_path, e))?;
+        self.forget(entry)
+    }
+
+    /// Permanently delete a quarantined item.
+    pub fn remove(&self, entry: &QuarantineEntry) -> Result<()> {
+        let Some(slot) = entry.stored_path.parent() else {
+            return self.forget(entry);
+        };
+        match std::fs::remove_dir_all(slot) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(Error::io(slot, e)),
+        }
+        self.forget(entry)
+    }
+
+    /// Permanently delete every item that has been quarantined for at least
+    /// `older_than`, marking each one purged in `journal` so it is no longer
+    /// offered for restore.
+    pub fn purge(&self, older_than: Duration, journal: Option<&Journal>) -> Result<PurgeResult> {
+        let mut result = PurgeResult {
+            purged_count: 0,
+            freed_bytes: 0,
+            errors: Vec::new(),
+        };
+
+        for entry in self.entries()? {
+            if entry.age() < older_than {
+                continue;
+            }
+            if let Err(e) = self.remove(&entry) {
+                result
+                    .errors
+                    .push(ErrorRecord::from_error(&e).at(&entry.original_path));
+                continue;
+            }
+            result.purged_count += 1;
+            result.freed_bytes += entry.size_bytes;
+
+            let Some(journal) = journal else { continue };
+            let marked = journal
+                .find_by_location(&entry.stored_path)
+                .and_then(|recorded| match recorded {
+                    Some(recorded) => journal.mark_purged(recorded.id),
+                    None => Ok(()),
+                });
+            if let Err(e) = marked {
+                let mut record = ErrorRecord::from_error(&e).at(&entry.original_path);
+                record.message = format!(
+                    "Purged, but not recorded in the journal: {}",
+                    record.message
+                );
+                result.errors.push(record);
+            }
+        }
+
+        Ok(result)
+    }
+
+    fn meta_path(&self, id: u64) -> PathBuf {
+        self.root.join(format!("{id}.json"))
+    }
+
+    fn write_meta(&self, entry: &QuarantineEntry) -> Result<()> {
+        let path = self.meta_path(entry.id);
+        let content =
+            serde_json::to_string_pretty(entry).map_err(|e| Error::Quarantine(e.to_string()))?;
+        std::fs::write(&path, content).map_err(|e| Error::io(&path, e))
+    }
+
+    /// Drop an entry's metadata and its (now empty) slot directory.
+    fn forget(&self, entry: &QuarantineEntry) -> Result<()> {
+        if let Some(slot) = entry.stored_path.parent() {
+            let _ = std::fs::remove_dir(slot);
+        }
+        let meta = self.meta_path(entry.id);
+        match std::fs::remove_file(&meta) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(Error::io(&meta, e)),
+        }
+    }
+
+    /// Directory that slots for `path` go in: the root when it shares a
+    /// filesystem with `path`, else a holding directory at the top of `path`'s
+    /// filesystem, so the move is always a rename.
+    #[cfg(unix)]
+    fn holding_dir_for(&self, path: &Path) -> Result<PathBuf> {
+        use std::os::unix::fs::MetadataExt;
+
+        let dev_of = |p: &Path| std::fs::symlink_metadata(p).map(|m| m.dev());
+        let root_dev = dev_of(&self.root).map_err(|e| Error::io(&self.root, e))?;
+        let item_dev = dev_of(path).map_err(|e| Error::io(path, e))?;
+        if item_dev == root_dev {
+            return Ok(self.root.clone());
+        }
+
+        let top = mount_point(path).ok_or_else(|| {
+            Error::Quarantine(format!("Cannot find the file system of {}", path.display()))
+        })?;
+        if path.canonicalize().is_ok_and(|path| path == top) {
+            return Err(Error::Quarantine(format!(
+                "{} is a mount point and cannot be quarantined",
+                path.display()
+            )));
+        }
+
+        let holding_dir = top.join(FOREIGN_DIR);
+        std::fs::create_dir_all(&holding_dir).map_err(|e| Error::io(&holding_dir, e))?;
+        Ok(holding_dir)
+    }
+
+    #[cfg(not(unix))]
+    fn holding_dir_for(&self, _path: &Path) -> Result<PathBuf> {
+        Ok(self.root.clone())
+    }
+}
@@ -1,11 +1,114 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::config::Config;
 use crate::error::Result;
 use crate::finding::{Category, Finding};
+use crate::ignore::{IgnoreRules, IgnoreScope};
+use crate::paths::Locations;
+use crate::project::ProjectDetector;
+use crate::walk::walk_dir_carrying;
+
+/// An entry of a [`ScanContext::project_walker`] walk.
+pub type ProjectEntry = jwalk::DirEntry<(IgnoreScope, ())>;
+
+/// Environment a recognizer scans against.
+pub struct ScanContext {
+    /// Home directory, platform and environment overrides.
+    pub locations: Locations,
+    /// User configuration, including project scan roots.
+    pub config: Config,
+    ignore: Arc<IgnoreRules>,
+}
+
+impl ScanContext {
+    pub fn new(locations: Locations, config: Config) -> Self {
+        let ignore = Arc::new(IgnoreRules::new(&config.ignore, &locations));
+        Self {
+            locations,
+            config,
+            ignore,
+        }
+    }
+
+    /// Build a context from the current user's environment.
+    pub fn detect(config: Config) -> Option<Self> {
+        Locations::detect().map(|locations| Self::new(locations, config))
+    }
+
+    /// Existing project roots from `[scan] roots`, with `~` expanded.
+    ///
+    /// Roots nested inside another root are dropped so no tree is walked twice.
+    pub fn project_roots(&self) -> Vec<PathBuf> {
+        self.existing_roots(&self.config.scan.roots)
+    }
+
+    /// Project roots for the recognizer with `id`: its
+    /// `[recognizers.<id>] roots` if set, else [`project_roots`](Self::project_roots).
+    pub fn project_roots_for(&self, id: &str) -> Vec<PathBuf> {
+        match &self.config.recognizer_settings(id).roots {
+            Some(roots) => self.existing_roots(roots),
+            None => self.project_roots(),
+        }
+    }
+
+    fn existing_roots(&self, roots: &[PathBuf]) -> Vec<PathBuf> {
+        let mut roots: Vec<PathBuf> = roots
+            .iter()
+            .map(|root| self.locations.expand_tilde(&root.to_string_lossy()))
+            .filter(|root| root.is_dir())
+            .collect();
+        roots.sort();
+
+        let mut unique: Vec<PathBuf> = Vec::new();
+        for root in roots {
+            if !unique.iter().any(|kept| root.starts_with(kept)) {
+                unique.push(root);
+            }
+        }
+        unique
+    }
+
+    /// Whether `[ignore]` or a `.diskardignore` file excludes `path`.
+    pub fn is_ignored(&self, path: &Path) -> bool {
+        self.ignore.is_ignored(path)
+    }
+
+    /// A directory walker for `root` honoring `[scan] max_depth` and
+    /// `follow_symlinks`, not descending into directories `prune` rejects or
+    /// that are ignored.
+    ///
+    /// Ignore files are read once, above `root` when the walk starts and
+    /// below it as each directory is entered.
+    pub fn project_walker(
+        &self,
+        root: &Path,
+        prune: impl Fn(&ProjectEntry) -> bool + Send + Sync + 'static,
+    ) -> jwalk::WalkDirGeneric<(IgnoreScope, ())> {
+        let entering = Arc::clone(&self.ignore);
+        let ignore = Arc::clone(&self.ignore);
+        walk_dir_carrying(
+            root,
+            self.ignore.scope_above(root),
+            move |dir, scope, children| {
+                // An ignored directory is reported, but nothing inside it
+                if entering.enter(scope, dir) {
+                    children.clear();
+                }
+            },
+            move |scope, entry| prune(entry) || ignore.is_ignored_in(scope, &entry.path()),
+        )
+        .max_depth(self.config.scan.max_depth)
+        .follow_links(self.config.scan.follow_symlinks)
+    }
+}
 
 /// A recognizer detects reclaimable disk space from a specific tool or ecosystem.
 ///
 /// Recognizers come in two flavors:
 /// - **Path-based**: scan known fixed paths (caches, model stores)
-/// - **Project-based**: scan directories for marker files (build artifacts)
+/// - **Project-based**: register a [`ProjectDetector`] that receives the
+///   directories holding its marker files from the shared project walk
 pub trait Recognizer: Send + Sync {
     /// Human-readable name (e.g., "Xcode DerivedData").
     fn name(&self) -> &'static str;
@@ -16,6 +119,11 @@ pub trait Recognizer: Send + Sync {
     /// Which category this recognizer belongs to.
     fn category(&self) -> Category;
 
-    /// Scan for findings. Returns an empty vec if nothing found.
-    fn scan(&self) -> Result<Vec<Finding>>;
+    /// Scan fixed paths for findings. Returns an empty vec if nothing found.
+    fn scan(&self, ctx: &ScanContext) -> Result<Vec<Finding>>;
+
+    /// The detector this recognizer contributes to the shared project walk, if any.
+    fn project_detector(&self) -> Option<&dyn ProjectDetector> {
+        None
+    }
 }
@@ -1,7 +1,9 @@
 use crate::error::Result;
 use crate::finding::{Category, Finding, RiskLevel};
-use crate::recognizer::Recognizer;
-use crate::size::dir_size;
+use crate::paths::Locations;
+use crate::recognizer::{Recognizer, ScanContext};
+use crate::size::dir_usage;
+use std::path::PathBuf;
 
 /// Claude Code session and debug data.
 pub struct ClaudeData;
@@ -19,41 +21,49 @@ impl Recognizer for ClaudeData {
         Category::Claude
     }
 
-    fn scan(&self) -> Result<Vec<Finding>> {
-        let Some(home) = dirs::home_dir() else {
-            return Ok(vec![]);
-        };
+    fn scan(&self, ctx: &ScanContext) -> Result<Vec<Finding>> {
+        let claude_dir = claude_config_dir(&ctx.locations);
 
         let mut findings = Vec::new();
 
         // Debug logs
-        let debug_path = home.join(".claude/debug");
+        let debug_path = claude_dir.join("debug");
         if debug_path.exists() {
-            let size = dir_size(&debug_path);
-            if size > 0 {
+            let usage = dir_usage(&debug_path);
+            if usage.size_bytes > 0 {
                 findings.push(Finding {
                     path: debug_path,
                     category: Category::Claude,
+                    recognizer_id: self.id(),
                     risk: RiskLevel::Safe,
-                    size_bytes: size,
+                    size_bytes: usage.size_bytes,
+                    apparent_bytes: usage.apparent_bytes,
                     description: "Claude Code debug logs".into(),
-                    last_modified: None,
+                    last_modified: usage.last_used,
+                    native: None,
+                    extra_paths: Vec::new(),
+                    projects: Vec::new(),
                 });
             }
         }
 
         // Project session transcripts
-        let projects_path = home.join(".claude/projects");
+        let projects_path = claude_dir.join("projects");
         if projects_path.exists() {
-            let size = dir_size(&projects_path);
-            if size > 0 {
+            let usage = dir_usage(&projects_path);
+            if usage.size_bytes > 0 {
                 findings.push(Finding {
                     path: projects_path,
                     category: Category::Claude,
+                    recognizer_id: self.id(),
                     risk: RiskLevel::Moderate,
-                    size_bytes: size,
+                    size_bytes: usage.size_bytes,
+                    apparent_bytes: usage.apparent_bytes,
                     description: "Claude Code session transcripts and project data".into(),
-                    last_modified: None,
+                    last_modified: usage.last_used,
+                    native: None,
+                    extra_paths: Vec::new(),
+                    projects: Vec::new(),
                 });
             }
         }
@@ -61,3 +71,9 @@ impl Recognizer for ClaudeData {
         Ok(findings)
     }
 }
+
+/// `$CLAUDE_CONFIG_DIR`, else `~/.claude`.
+fn claude_config_dir(loc: &Locations) -> PathBuf {
+    loc.var_path("CLAUDE_CONFIG_DIR")
+        .unwrap_or_else(|| loc.home().join(".claude"))
+}
@@ -1,7 +1,9 @@
 use crate::error::Result;
-use crate::finding::{Category, Finding, RiskLevel};
-use crate::recognizer::Recognizer;
-use crate::size::dir_size;
+use crate::finding::{Category, Finding, NativeAction, RiskLevel};
+use crate::paths::Locations;
+use crate::recognizer::{Recognizer, ScanContext};
+use crate::size::dir_usage;
+use std::path::PathBuf;
 
 /// CocoaPods download cache.
 pub struct CocoaPodsCache;
@@ -19,27 +21,35 @@ impl Recognizer for CocoaPodsCache {
         Category::CocoaPods
     }
 
-    fn scan(&self) -> Result<Vec<Finding>> {
-        let Some(home) = dirs::home_dir() else {
-            return Ok(vec![]);
-        };
-        let path = home.join("Library/Caches/CocoaPods");
+    fn scan(&self, ctx: &ScanContext) -> Result<Vec<Finding>> {
+        let path = cocoapods_cache_dir(&ctx.locations);
         if !path.exists() {
             return Ok(vec![]);
         }
 
-        let size = dir_size(&path);
-        if size == 0 {
+        let usage = dir_usage(&path);
+        if usage.size_bytes == 0 {
             return Ok(vec![]);
         }
 
         Ok(vec![Finding {
             path,
             category: Category::CocoaPods,
+            recognizer_id: self.id(),
             risk: RiskLevel::Safe,
-            size_bytes: size,
+            size_bytes: usage.size_bytes,
+            apparent_bytes: usage.apparent_bytes,
             description: "CocoaPods download cache — re-downloaded on next pod install".into(),
-            last_modified: None,
+            last_modified: usage.last_used,
+            native: Some(NativeAction::new("pod cache clean --all")),
+            extra_paths: Vec::new(),
+            projects: Vec::new(),
         }])
     }
 }
+
+/// `$CP_CACHE_DIR`, else `CocoaPods` under the platform cache dir.
+fn cocoapods_cache_dir(loc: &Locations) -> PathBuf {
+    loc.var_path("CP_CACHE_DIR")
+        .unwrap_or_else(|| loc.cache_dir().join("CocoaPods"))
+}
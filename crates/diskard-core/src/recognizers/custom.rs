@@ -0,0 +1,182 @@
+use crate::config::CustomRecognizerConfig;
+use crate::error::{Error, Result};
+use crate::finding::{Category, Finding, RiskLevel};
+use crate::project::ProjectDetector;
+use crate::recognizer::{Recognizer, ScanContext};
+use crate::size::dir_usage;
+use std::path::{Path, PathBuf};
+
+/// A recognizer defined by a `[[custom]]` table in the config file.
+pub struct CustomRecognizer {
+    id: &'static str,
+    name: &'static str,
+    category: Category,
+    risk: RiskLevel,
+    description: String,
+    paths: Vec<String>,
+    markers: Vec<&'static str>,
+    artifact_dirs: Vec<&'static str>,
+}
+
+impl CustomRecognizer {
+    /// Validate a `[[custom]]` table.
+    ///
+    /// The recognizer traits hand out `'static` names, so the few strings
+    /// they need are leaked; custom recognizers are built once per run.
+    pub fn new(config: &CustomRecognizerConfig) -> Result<Self> {
+        let invalid =
+            |reason: String| Error::Config(format!("Custom recognizer '{}': {reason}", config.id));
+
+        if config.id.is_empty() || config.id.contains(char::is_whitespace) {
+            return Err(invalid("id must be non-empty without spaces".into()));
+        }
+        if config.paths.is_empty() {
+            return Err(invalid("no paths given".into()));
+        }
+        for pattern in &config.paths {
+            glob::Pattern::new(pattern)
+                .map_err(|e| invalid(format!("bad glob '{pattern}': {e}")))?;
+            if !config.markers.is_empty() && Path::new(pattern).is_absolute() {
+                return Err(invalid(format!(
+                    "'{pattern}' must be relative to the project when markers are given"
+                )));
+            }
+        }
+
+        // Literal leading directories of project paths (`.turbo` in
+        // `.turbo/cache`) hold artifacts; the project walk needn't enter them.
+        let artifact_dirs = if config.markers.is_empty() {
+            Vec::new()
+        } else {
+            config
+                .paths
+                .iter()
+                .filter_map(|pattern| Path::new(pattern).components().next())
+                .filter_map(|first| first.as_os_str().to_str())
+                .filter(|first| !first.contains(['*', '?', '[']))
+                .map(leak)
+                .collect()
+        };
+
+        Ok(Self {
+            id: leak(&config.id),
+            name: leak(&config.name),
+            category: config.category.parse().map_err(invalid)?,
+            risk: config.risk.parse().map_err(invalid)?,
+            description: if config.description.is_empty() {
+                config.name.clone()
+            } else {
+                config.description.clone()
+            },
+            paths: config.paths.clone(),
+            markers: config.markers.iter().map(|m| leak(m)).collect(),
+            artifact_dirs,
+        })
+    }
+
+    fn finding(&self, path: PathBuf, description: String) -> Option<Finding> {
+        let usage = dir_usage(&path);
+        (usage.size_bytes > 0).then_some(Finding {
+            path,
+            category: self.category,
+            recognizer_id: self.id,
+            risk: self.risk,
+            size_bytes: usage.size_bytes,
+            apparent_bytes: usage.apparent_bytes,
+            description,
+            last_modified: usage.last_used,
+            native: None,
+            extra_paths: Vec::new(),
+            projects: Vec::new(),
+        })
+    }
+}
+
+fn leak(s: &str) -> &'static str {
+    Box::leak(s.to_owned().into_boxed_str())
+}
+
+/// Existing paths matching `pattern`, in sorted order.
+fn matches(pattern: &str) -> Vec<PathBuf> {
+    match glob::glob(pattern) {
+        Ok(paths) => paths.filter_map(|p| p.ok()).collect(),
+        Err(e) => {
+            log::debug!("Skipping glob '{pattern}': {e}");
+            Vec::new()
+        }
+    }
+}
+
+impl Recognizer for CustomRecognizer {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn id(&self) -> &'static str {
+        self.id
+    }
+
+    fn category(&self) -> Category {
+        self.category
+    }
+
+    fn scan(&self, ctx: &ScanContext) -> Result<Vec<Finding>> {
+        if !self.markers.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let mut paths: Vec<PathBuf> = self
+            .paths
+            .iter()
+            .filter_map(|pattern| ctx.locations.expand_pattern(pattern))
+            .flat_map(|pattern| matches(&pattern))
+            .collect();
+        paths.sort();
+        paths.dedup();
+
+        Ok(paths
+            .into_iter()
+            .filter_map(|path| self.finding(path, self.description.clone()))
+            .collect())
+    }
+
+    fn project_detector(&self) -> Option<&dyn ProjectDetector> {
+        (!self.markers.is_empty()).then_some(self as &dyn ProjectDetector)
+    }
+}
+
+impl ProjectDetector for CustomRecognizer {
+    fn markers(&self) -> &[&'static str] {
+        &self.markers
+    }
+
+    fn artifact_dirs(&self) -> &[&'static str] {
+        &self.artifact_dirs
+    }
+
+    fn detect(&self, _ctx: &ScanContext, projects: &[PathBuf]) -> Vec<Finding> {
+        let mut findings = Vec::new();
+
+        for project in projects {
+            let Some(root) = project.to_str().map(glob::Pattern::escape) else {
+                continue;
+            };
+            let name = project.file_name().unwrap_or_default().to_string_lossy();
+            let mut paths: Vec<PathBuf> = self
+                .paths
+                .iter()
+                .flat_map(|pattern| matches(&format!("{root}/{pattern}")))
+                .collect();
+            paths.sort();
+            paths.dedup();
+
+            findings.extend(
+                paths.into_iter().filter_map(|path| {
+                    self.finding(path, format!("{} for {name}", self.description))
+                }),
+            );
+        }
+
+        findings
+    }
+}
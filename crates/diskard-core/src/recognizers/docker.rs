@@ -1,7 +1,9 @@
 use crate::error::Result;
-use crate::finding::{Category, Finding, RiskLevel};
-use crate::recognizer::Recognizer;
-use crate::size::dir_size;
+use crate::finding::{Category, Finding, NativeAction, RiskLevel};
+use crate::paths::{Locations, Platform};
+use crate::recognizer::{Recognizer, ScanContext};
+use crate::size::dir_usage;
+use std::path::PathBuf;
 
 /// Docker Desktop data (images, containers, volumes).
 pub struct DockerData;
@@ -19,27 +21,56 @@ impl Recognizer for DockerData {
         Category::Docker
     }
 
-    fn scan(&self) -> Result<Vec<Finding>> {
-        let Some(home) = dirs::home_dir() else {
-            return Ok(vec![]);
-        };
-        let path = home.join("Library/Containers/com.docker.docker/Data");
-        if !path.exists() {
-            return Ok(vec![]);
-        }
+    fn scan(&self, ctx: &ScanContext) -> Result<Vec<Finding>> {
+        let mut findings = Vec::new();
+
+        for (path, description) in docker_data_dirs(&ctx.locations) {
+            if !path.exists() {
+                continue;
+            }
+
+            let usage = dir_usage(&path);
+            if usage.size_bytes == 0 {
+                continue;
+            }
 
-        let size = dir_size(&path);
-        if size == 0 {
-            return Ok(vec![]);
+            findings.push(Finding {
+                path,
+                category: Category::Docker,
+                recognizer_id: self.id(),
+                risk: RiskLevel::Risky,
+                size_bytes: usage.size_bytes,
+                apparent_bytes: usage.apparent_bytes,
+                description: description.into(),
+                last_modified: usage.last_used,
+                native: Some(NativeAction::new("docker builder prune --force")),
+                extra_paths: Vec::new(),
+                projects: Vec::new(),
+            });
         }
 
-        Ok(vec![Finding {
-            path,
-            category: Category::Docker,
-            risk: RiskLevel::Risky,
-            size_bytes: size,
-            description: "Docker Desktop data — includes images, containers, and volumes".into(),
-            last_modified: None,
-        }])
+        Ok(findings)
+    }
+}
+
+/// Per-user Docker data locations with a description for each.
+///
+/// The system daemon's `/var/lib/docker` is root-owned and left alone.
+fn docker_data_dirs(loc: &Locations) -> Vec<(PathBuf, &'static str)> {
+    match loc.platform() {
+        Platform::MacOs => vec![(
+            loc.home().join("Library/Containers/com.docker.docker/Data"),
+            "Docker Desktop data — includes images, containers, and volumes",
+        )],
+        _ => vec![
+            (
+                loc.home().join(".docker/desktop/vms"),
+                "Docker Desktop VM disk — includes images, containers, and volumes",
+            ),
+            (
+                loc.xdg_data_home().join("docker"),
+                "Rootless Docker data — includes images, containers, and volumes",
+            ),
+        ],
     }
 }
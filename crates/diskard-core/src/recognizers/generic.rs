@@ -1,6 +1,9 @@
 use crate::error::Result;
 use crate::finding::{Category, Finding, RiskLevel};
-use crate::recognizer::Recognizer;
+use crate::project::ProjectDetector;
+use crate::recognizer::{Recognizer, ScanContext};
+use crate::size::DirUsage;
+use std::path::PathBuf;
 
 /// .DS_Store files scattered across the filesystem.
 pub struct DsStore;
@@ -18,51 +21,54 @@ impl Recognizer for DsStore {
         Category::Generic
     }
 
-    fn scan(&self) -> Result<Vec<Finding>> {
-        let Some(home) = dirs::home_dir() else {
-            return Ok(vec![]);
-        };
+    fn scan(&self, _ctx: &ScanContext) -> Result<Vec<Finding>> {
+        Ok(vec![])
+    }
 
-        let mut total_size: u64 = 0;
-        let mut count: u64 = 0;
+    fn project_detector(&self) -> Option<&dyn ProjectDetector> {
+        Some(self)
+    }
+}
 
-        // Walk common developer directories for .DS_Store files
-        let scan_roots = vec![
-            home.join("Developer"),
-            home.join("Projects"),
-            home.join("Documents"),
-            home.join("Desktop"),
-        ];
+impl ProjectDetector for DsStore {
+    /// The marker is the `.DS_Store` file itself, so `detect` receives every
+    /// directory that has one.
+    fn markers(&self) -> &[&'static str] {
+        &[".DS_Store"]
+    }
 
-        for root in scan_roots {
-            if !root.exists() {
-                continue;
-            }
+    /// Folder metadata says nothing about the folder's project.
+    fn guards_active_projects(&self) -> bool {
+        false
+    }
 
-            let walker = jwalk::WalkDir::new(&root)
-                .skip_hidden(false)
-                .max_depth(6)
-                .into_iter();
+    fn detect(&self, ctx: &ScanContext, projects: &[PathBuf]) -> Vec<Finding> {
+        let mut usage = DirUsage::default();
+        let mut count: u64 = 0;
 
-            for entry in walker.flatten() {
-                if entry.file_name().to_str() == Some(".DS_Store") && entry.file_type().is_file() {
-                    total_size += entry.metadata().map(|m| m.len()).unwrap_or(0);
-                    count += 1;
-                }
+        for dir in projects {
+            if let Ok(metadata) = dir.join(".DS_Store").metadata() {
+                usage = usage.combine(DirUsage::of_file(&metadata));
+                count += 1;
             }
         }
 
         if count == 0 {
-            return Ok(vec![]);
+            return vec![];
         }
 
-        Ok(vec![Finding {
-            path: home.join("**/.DS_Store"),
+        vec![Finding {
+            path: ctx.locations.home().join("**/.DS_Store"),
             category: Category::Generic,
+            recognizer_id: self.id(),
             risk: RiskLevel::Safe,
-            size_bytes: total_size,
+            size_bytes: usage.size_bytes,
+            apparent_bytes: usage.apparent_bytes,
             description: format!("{count} .DS_Store files — macOS folder metadata, safe to delete"),
-            last_modified: None,
-        }])
+            last_modified: usage.last_used,
+            native: None,
+            extra_paths: Vec::new(),
+            projects: Vec::new(),
+        }]
     }
 }
@@ -1,7 +1,9 @@
 use crate::error::Result;
 use crate::finding::{Category, Finding, RiskLevel};
-use crate::recognizer::Recognizer;
-use crate::size::dir_size;
+use crate::paths::Locations;
+use crate::recognizer::{Recognizer, ScanContext};
+use crate::size::dir_usage;
+use std::path::PathBuf;
 
 /// Gradle build cache and wrapper distributions.
 pub struct GradleCache;
@@ -19,57 +21,70 @@ impl Recognizer for GradleCache {
         Category::Gradle
     }
 
-    fn scan(&self) -> Result<Vec<Finding>> {
-        let Some(home) = dirs::home_dir() else {
-            return Ok(vec![]);
-        };
+    fn scan(&self, ctx: &ScanContext) -> Result<Vec<Finding>> {
+        let gradle_home = gradle_user_home(&ctx.locations);
 
         let mut findings = Vec::new();
 
         // Gradle caches (build outputs, dependency cache)
-        let caches = home.join(".gradle/caches");
+        let caches = gradle_home.join("caches");
         if caches.exists() {
-            let size = dir_size(&caches);
-            if size > 0 {
+            let usage = dir_usage(&caches);
+            if usage.size_bytes > 0 {
                 findings.push(Finding {
                     path: caches,
                     category: Category::Gradle,
+                    recognizer_id: self.id(),
                     risk: RiskLevel::Safe,
-                    size_bytes: size,
+                    size_bytes: usage.size_bytes,
+                    apparent_bytes: usage.apparent_bytes,
                     description: "Gradle build and dependency cache — rebuilt on next build".into(),
-                    last_modified: None,
+                    last_modified: usage.last_used,
+                    native: None,
+                    extra_paths: Vec::new(),
+                    projects: Vec::new(),
                 });
             }
         }
 
         // Gradle wrapper distributions
-        let wrapper = home.join(".gradle/wrapper/dists");
+        let wrapper = gradle_home.join("wrapper/dists");
         if wrapper.exists() {
-            let size = dir_size(&wrapper);
-            if size > 0 {
+            let usage = dir_usage(&wrapper);
+            if usage.size_bytes > 0 {
                 findings.push(Finding {
                     path: wrapper,
                     category: Category::Gradle,
+                    recognizer_id: self.id(),
                     risk: RiskLevel::Moderate,
-                    size_bytes: size,
+                    size_bytes: usage.size_bytes,
+                    apparent_bytes: usage.apparent_bytes,
                     description: "Gradle wrapper distributions — re-downloaded when needed".into(),
-                    last_modified: None,
+                    last_modified: usage.last_used,
+                    native: None,
+                    extra_paths: Vec::new(),
+                    projects: Vec::new(),
                 });
             }
         }
 
         // Maven local repository
-        let m2 = home.join(".m2/repository");
+        let m2 = ctx.locations.home().join(".m2/repository");
         if m2.exists() {
-            let size = dir_size(&m2);
-            if size > 0 {
+            let usage = dir_usage(&m2);
+            if usage.size_bytes > 0 {
                 findings.push(Finding {
                     path: m2,
                     category: Category::Gradle,
+                    recognizer_id: self.id(),
                     risk: RiskLevel::Moderate,
-                    size_bytes: size,
+                    size_bytes: usage.size_bytes,
+                    apparent_bytes: usage.apparent_bytes,
                     description: "Maven local repository — re-downloaded on next build".into(),
-                    last_modified: None,
+                    last_modified: usage.last_used,
+                    native: None,
+                    extra_paths: Vec::new(),
+                    projects: Vec::new(),
                 });
             }
         }
@@ -77,3 +92,9 @@ impl Recognizer for GradleCache {
         Ok(findings)
     }
 }
+
+/// `$GRADLE_USER_HOME`, else `~/.gradle`.
+fn gradle_user_home(loc: &Locations) -> PathBuf {
+    loc.var_path("GRADLE_USER_HOME")
+        .unwrap_or_else(|| loc.home().join(".gradle"))
+}
@@ -1,7 +1,9 @@
 use crate::error::Result;
-use crate::finding::{Category, Finding, RiskLevel};
-use crate::recognizer::Recognizer;
-use crate::size::dir_size;
+use crate::finding::{Category, Finding, NativeAction, RiskLevel};
+use crate::paths::{Locations, Platform};
+use crate::recognizer::{Recognizer, ScanContext};
+use crate::size::dir_usage;
+use std::path::PathBuf;
 
 /// Homebrew download cache.
 pub struct HomebrewCache;
@@ -19,27 +21,52 @@ impl Recognizer for HomebrewCache {
         Category::Homebrew
     }
 
-    fn scan(&self) -> Result<Vec<Finding>> {
-        let Some(home) = dirs::home_dir() else {
-            return Ok(vec![]);
-        };
-        let path = home.join("Library/Caches/Homebrew");
-        if !path.exists() {
-            return Ok(vec![]);
-        }
+    fn scan(&self, ctx: &ScanContext) -> Result<Vec<Finding>> {
+        let mut findings = Vec::new();
+
+        for path in homebrew_cache_dirs(&ctx.locations) {
+            if !path.exists() {
+                continue;
+            }
+
+            let usage = dir_usage(&path);
+            if usage.size_bytes == 0 {
+                continue;
+            }
 
-        let size = dir_size(&path);
-        if size == 0 {
-            return Ok(vec![]);
+            findings.push(Finding {
+                path,
+                category: Category::Homebrew,
+                recognizer_id: self.id(),
+                risk: RiskLevel::Safe,
+                size_bytes: usage.size_bytes,
+                apparent_bytes: usage.apparent_bytes,
+                description: "Homebrew download cache — re-downloaded when needed".into(),
+                last_modified: usage.last_used,
+                native: Some(NativeAction::new("brew cleanup -s --prune=all")),
+                extra_paths: Vec::new(),
+                projects: Vec::new(),
+            });
         }
 
-        Ok(vec![Finding {
-            path,
-            category: Category::Homebrew,
-            risk: RiskLevel::Safe,
-            size_bytes: size,
-            description: "Homebrew download cache — re-downloaded when needed".into(),
-            last_modified: None,
-        }])
+        Ok(findings)
+    }
+}
+
+/// `$HOMEBREW_CACHE`, else `Homebrew` under the platform cache dir.
+///
+/// On Linux the shared Linuxbrew prefix keeps its own cache, so it is checked too.
+fn homebrew_cache_dirs(loc: &Locations) -> Vec<PathBuf> {
+    if let Some(dir) = loc.var_path("HOMEBREW_CACHE") {
+        return vec![dir];
+    }
+
+    let mut dirs = vec![loc.cache_dir().join("Homebrew")];
+    if loc.platform() == Platform::Linux {
+        let linuxbrew = PathBuf::from("/home/linuxbrew/.cache/Homebrew");
+        if !dirs.contains(&linuxbrew) {
+            dirs.push(linuxbrew);
+        }
     }
+    dirs
 }
@@ -1,9 +1,13 @@
 use crate::error::Result;
 use crate::finding::{Category, Finding, RiskLevel};
-use crate::recognizer::Recognizer;
-use crate::size::dir_size;
+use crate::paths::Locations;
+use crate::recognizer::{Recognizer, ScanContext};
+use crate::size::{dir_usage, DirUsage};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 
-/// HuggingFace Hub cache — downloaded models and datasets.
+/// HuggingFace Hub cache — one finding per downloaded model, dataset or
+/// space, and one per revision of it no branch or tag points to any more.
 pub struct HuggingFaceCache;
 
 impl Recognizer for HuggingFaceCache {
@@ -19,27 +23,236 @@ impl Recognizer for HuggingFaceCache {
         Category::HuggingFace
     }
 
-    fn scan(&self) -> Result<Vec<Finding>> {
-        let Some(home) = dirs::home_dir() else {
-            return Ok(vec![]);
-        };
-        let path = home.join(".cache/huggingface");
-        if !path.exists() {
-            return Ok(vec![]);
+    fn scan(&self, ctx: &ScanContext) -> Result<Vec<Finding>> {
+        let home = huggingface_home(&ctx.locations);
+        let mut findings = Vec::new();
+
+        let hub = hub_cache(&ctx.locations, &home);
+        if let Ok(entries) = std::fs::read_dir(&hub) {
+            for entry in entries.filter_map(|e| e.ok()) {
+                let name = entry.file_name();
+                let Some((kind, id)) = name.to_str().and_then(repo_name) else {
+                    continue;
+                };
+                if entry.path().is_dir() {
+                    findings.extend(scan_repo(&entry.path(), kind, &id, self.id()));
+                }
+            }
         }
 
-        let size = dir_size(&path);
-        if size == 0 {
-            return Ok(vec![]);
+        // Arrow files the `datasets` library builds from downloads
+        let datasets = ctx
+            .locations
+            .var_path("HF_DATASETS_CACHE")
+            .unwrap_or_else(|| home.join("datasets"));
+        let usage = dir_usage(&datasets);
+        if usage.size_bytes > 0 {
+            findings.push(Finding {
+                path: datasets,
+                category: Category::HuggingFace,
+                recognizer_id: self.id(),
+                risk: RiskLevel::Moderate,
+                size_bytes: usage.size_bytes,
+                apparent_bytes: usage.apparent_bytes,
+                description: "HuggingFace datasets cache — rebuilt from downloads when needed"
+                    .into(),
+                last_modified: usage.last_used,
+                native: None,
+                extra_paths: Vec::new(),
+                projects: Vec::new(),
+            });
         }
 
-        Ok(vec![Finding {
-            path,
+        Ok(findings)
+    }
+}
+
+/// `$HF_HOME`, else `huggingface` under the XDG cache home.
+///
+/// The hub library uses the XDG layout on every platform, including macOS.
+fn huggingface_home(loc: &Locations) -> PathBuf {
+    loc.var_path("HF_HOME")
+        .unwrap_or_else(|| loc.xdg_cache_home().join("huggingface"))
+}
+
+/// `$HF_HUB_CACHE` (or its old name `$HUGGINGFACE_HUB_CACHE`), else `hub`
+/// under the HuggingFace home.
+fn hub_cache(loc: &Locations, home: &Path) -> PathBuf {
+    loc.var_path("HF_HUB_CACHE")
+        .or_else(|| loc.var_path("HUGGINGFACE_HUB_CACHE"))
+        .unwrap_or_else(|| home.join("hub"))
+}
+
+/// The kind and id of a repo directory such as `models--google--gemma-2b`.
+fn repo_name(dir_name: &str) -> Option<(&'static str, String)> {
+    let (prefix, id) = dir_name.split_once("--")?;
+    let kind = match prefix {
+        "models" => "model",
+        "datasets" => "dataset",
+        "spaces" => "space",
+        _ => return None,
+    };
+    Some((kind, id.replace("--", "/")))
+}
+
+/// A checked-out revision under `snapshots/`.
+struct Snapshot {
+    path: PathBuf,
+    revision: String,
+    /// Blobs its files link to.
+    blobs: HashSet<PathBuf>,
+    /// Files stored in the snapshot itself, where symlinks aren't available.
+    copies: DirUsage,
+}
+
+/// Findings for one repo: the whole of it, then each detached revision with
+/// the blobs no other revision shares.
+fn scan_repo(repo: &Path, kind: &str, id: &str, recognizer_id: &'static str) -> Vec<Finding> {
+    let usage = dir_usage(repo);
+    if usage.size_bytes == 0 {
+        return vec![];
+    }
+    let snapshots = snapshots(repo);
+    let refs = refs(repo);
+
+    let mut findings = vec![Finding {
+        path: repo.to_path_buf(),
+        category: Category::HuggingFace,
+        recognizer_id,
+        risk: RiskLevel::Moderate,
+        size_bytes: usage.size_bytes,
+        apparent_bytes: usage.apparent_bytes,
+        description: format!(
+            "HuggingFace {kind} {id} ({} revisions) — re-downloaded when needed",
+            snapshots.len()
+        ),
+        last_modified: usage.last_used,
+        native: None,
+        extra_paths: Vec::new(),
+        projects: Vec::new(),
+    }];
+
+    let mut users: HashMap<&Path, usize> = HashMap::new();
+    for blob in snapshots.iter().flat_map(|s| &s.blobs) {
+        *users.entry(blob).or_default() += 1;
+    }
+    let blob_usage: HashMap<&Path, DirUsage> =
+        users.keys().map(|blob| (*blob, dir_usage(blob))).collect();
+
+    for snapshot in snapshots.iter().filter(|s| !refs.contains(&s.revision)) {
+        let mut exclusive: Vec<PathBuf> = snapshot
+            .blobs
+            .iter()
+            .filter(|blob| users[blob.as_path()] == 1)
+            .cloned()
+            .collect();
+        exclusive.sort();
+        let freed = exclusive
+            .iter()
+            .map(|blob| blob_usage[blob.as_path()])
+            .fold(snapshot.copies, DirUsage::combine);
+        if freed.size_bytes == 0 {
+            continue;
+        }
+        // Reading a file through the snapshot touches the shared blob, so
+        // every blob says when the revision was last used
+        let last_used = snapshot
+            .blobs
+            .iter()
+            .filter_map(|blob| blob_usage[blob.as_path()].last_used)
+            .max()
+            .max(snapshot.copies.last_used);
+
+        let short: String = snapshot.revision.chars().take(12).collect();
+        findings.push(Finding {
+            path: snapshot.path.clone(),
             category: Category::HuggingFace,
-            risk: RiskLevel::Moderate,
-            size_bytes: size,
-            description: "HuggingFace model and dataset cache — re-downloaded when needed".into(),
-            last_modified: None,
-        }])
+            recognizer_id,
+            risk: RiskLevel::Safe,
+            size_bytes: freed.size_bytes,
+            apparent_bytes: freed.apparent_bytes,
+            description: format!(
+                "Old revision {short} of HuggingFace {kind} {id} — no branch or tag uses it"
+            ),
+            last_modified: last_used,
+            native: None,
+            extra_paths: exclusive,
+            projects: Vec::new(),
+        });
+    }
+    findings
+}
+
+/// Every revision under `snapshots/`, with the blobs its files link to.
+fn snapshots(repo: &Path) -> Vec<Snapshot> {
+    let blobs = repo.join("blobs");
+    let blobs_resolved = blobs.canonicalize().unwrap_or_else(|_| blobs.clone());
+    let Ok(entries) = std::fs::read_dir(repo.join("snapshots")) else {
+        return vec![];
+    };
+
+    let mut snapshots: Vec<Snapshot> = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+        .map(|entry| {
+            let mut snapshot = Snapshot {
+                path: entry.path(),
+                revision: entry.file_name().to_string_lossy().into_owned(),
+                blobs: HashSet::new(),
+                copies: DirUsage::default(),
+            };
+            add_files(&entry.path(), &blobs, &blobs_resolved, &mut snapshot);
+            snapshot
+        })
+        .collect();
+    snapshots.sort_by(|a, b| a.path.cmp(&b.path));
+    snapshots
+}
+
+/// Add the files under `dir` to `snapshot`: links into the blobs directory
+/// as blobs, anything else as copies.
+fn add_files(dir: &Path, blobs: &Path, blobs_resolved: &Path, snapshot: &mut Snapshot) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.filter_map(|e| e.ok()) {
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        let path = entry.path();
+        if file_type.is_dir() {
+            add_files(&path, blobs, blobs_resolved, snapshot);
+        } else if file_type.is_symlink() {
+            let Ok(target) = path.canonicalize() else {
+                continue;
+            };
+            if let (Some(parent), Some(name)) = (target.parent(), target.file_name()) {
+                if parent == blobs_resolved {
+                    snapshot.blobs.insert(blobs.join(name));
+                }
+            }
+        } else if let Ok(metadata) = entry.metadata() {
+            snapshot.copies = snapshot.copies.combine(DirUsage::of_file(&metadata));
+        }
+    }
+}
+
+/// Revisions a branch, tag or pull request under `refs/` points to.
+fn refs(repo: &Path) -> HashSet<String> {
+    let mut revisions = HashSet::new();
+    let mut dirs = vec![repo.join("refs")];
+    while let Some(dir) = dirs.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.filter_map(|e| e.ok()) {
+            let path = entry.path();
+            if path.is_dir() {
+                dirs.push(path);
+            } else if let Ok(revision) = std::fs::read_to_string(&path) {
+                revisions.insert(revision.trim().to_string());
+            }
+        }
     }
+    revisions
 }
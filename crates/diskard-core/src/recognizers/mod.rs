@@ -1,5 +1,6 @@
 mod claude;
 mod cocoapods;
+mod custom;
 mod docker;
 mod generic;
 mod gradle;
@@ -12,8 +13,12 @@ mod rust;
 mod vscode;
 mod xcode;
 
+use crate::config::Config;
+use crate::error::{Error, Result};
 use crate::recognizer::Recognizer;
 
+pub use custom::CustomRecognizer;
+
 /// Return all built-in recognizers.
 pub fn all_recognizers() -> Vec<Box<dyn Recognizer>> {
     vec![
@@ -30,8 +35,16 @@ pub fn all_recognizers() -> Vec<Box<dyn Recognizer>> {
         Box::new(homebrew::HomebrewCache),
         // Python
         Box::new(python::PipCache),
+        Box::new(python::PythonVenvs),
+        Box::new(python::PythonToolCaches),
+        Box::new(python::UvCache),
+        Box::new(python::PoetryCache),
+        Box::new(python::Pipx),
+        Box::new(python::PyenvVersions),
+        Box::new(python::CondaData),
         // Rust
         Box::new(rust::CargoTarget),
+        Box::new(rust::RustupToolchains),
         // Docker
         Box::new(docker::DockerData),
         // Ollama
@@ -50,3 +63,28 @@ pub fn all_recognizers() -> Vec<Box<dyn Recognizer>> {
         Box::new(generic::DsStore),
     ]
 }
+
+/// Built-in recognizers followed by the `[[custom]]` ones from `config`.
+pub fn for_config(config: &Config) -> Result<Vec<Box<dyn Recognizer>>> {
+    let mut recognizers = all_recognizers();
+    for custom in &config.custom {
+        if recognizers.iter().any(|r| r.id() == custom.id) {
+            return Err(Error::Config(format!(
+                "Custom recognizer '{}' reuses an existing id",
+                custom.id
+            )));
+        }
+        recognizers.push(Box::new(CustomRecognizer::new(custom)?));
+    }
+    let mut unknown: Vec<&String> = config
+        .recognizers
+        .settings
+        .keys()
+        .filter(|id| !recognizers.iter().any(|r| r.id() == id.as_str()))
+        .collect();
+    unknown.sort();
+    for id in unknown {
+        log::warn!("Ignoring [recognizers.{id}]: no recognizer has that id");
+    }
+    Ok(recognizers)
+}
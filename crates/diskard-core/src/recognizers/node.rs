@@ -1,9 +1,11 @@
 use std::path::PathBuf;
 
 use crate::error::Result;
-use crate::finding::{Category, Finding, RiskLevel};
-use crate::recognizer::Recognizer;
-use crate::size::dir_size;
+use crate::finding::{Category, Finding, NativeAction, RiskLevel};
+use crate::paths::Locations;
+use crate::project::{self, ProjectDetector};
+use crate::recognizer::{Recognizer, ScanContext};
+use crate::size::dir_usage;
 
 /// npm cache directory.
 pub struct NpmCache;
@@ -21,34 +23,46 @@ impl Recognizer for NpmCache {
         Category::Node
     }
 
-    fn scan(&self) -> Result<Vec<Finding>> {
-        let Some(home) = dirs::home_dir() else {
-            return Ok(vec![]);
-        };
-        let path = home.join(".npm");
+    fn scan(&self, ctx: &ScanContext) -> Result<Vec<Finding>> {
+        let path = npm_cache_dir(&ctx.locations);
         if !path.exists() {
             return Ok(vec![]);
         }
 
-        let size = dir_size(&path);
-        if size == 0 {
+        let usage = dir_usage(&path);
+        if usage.size_bytes == 0 {
             return Ok(vec![]);
         }
 
         Ok(vec![Finding {
             path,
             category: Category::Node,
+            recognizer_id: self.id(),
             risk: RiskLevel::Safe,
-            size_bytes: size,
+            size_bytes: usage.size_bytes,
+            apparent_bytes: usage.apparent_bytes,
             description: "npm package cache — repopulated on next install".into(),
-            last_modified: None,
+            last_modified: usage.last_used,
+            native: Some(NativeAction::new("npm cache clean --force")),
+            extra_paths: Vec::new(),
+            projects: Vec::new(),
         }])
     }
 }
 
+/// `$npm_config_cache`, else `~/.npm` on every platform.
+fn npm_cache_dir(loc: &Locations) -> PathBuf {
+    loc.var_path("npm_config_cache")
+        .unwrap_or_else(|| loc.home().join(".npm"))
+}
+
 /// node_modules directories in project trees — project-based scanner.
 pub struct NodeModules;
 
+/// node_modules directories smaller than this (1 MiB) aren't reported unless
+/// `[recognizers.node-modules] min_size` says otherwise.
+const DEFAULT_MIN_SIZE: u64 = 1_048_576;
+
 impl Recognizer for NodeModules {
     fn name(&self) -> &'static str {
         "node_modules"
@@ -62,58 +76,67 @@ impl Recognizer for NodeModules {
         Category::Node
     }
 
-    fn scan(&self) -> Result<Vec<Finding>> {
-        let Some(home) = dirs::home_dir() else {
-            return Ok(vec![]);
-        };
-
-        let mut findings = Vec::new();
-        let scan_roots: Vec<PathBuf> = vec![
-            home.join("Developer"),
-            home.join("Projects"),
-            home.join("src"),
-        ];
-
-        for root in scan_roots {
-            if !root.exists() {
-                continue;
-            }
-            self.scan_for_node_modules(&root, &mut findings);
-        }
+    fn scan(&self, _ctx: &ScanContext) -> Result<Vec<Finding>> {
+        Ok(vec![])
+    }
 
-        Ok(findings)
+    fn project_detector(&self) -> Option<&dyn ProjectDetector> {
+        Some(self)
     }
 }
 
-impl NodeModules {
-    fn scan_for_node_modules(&self, root: &PathBuf, findings: &mut Vec<Finding>) {
-        let walker = jwalk::WalkDir::new(root)
-            .max_depth(5)
-            .skip_hidden(true)
-            .into_iter();
-
-        for entry in walker.flatten() {
-            if entry.file_name().to_str() == Some("package.json") && entry.file_type().is_file() {
-                if let Some(parent) = entry.path().parent() {
-                    let nm = parent.join("node_modules");
-                    if nm.exists() && nm.is_dir() {
-                        let size = dir_size(&nm);
-                        if size > 1_048_576 {
-                            findings.push(Finding {
-                                path: nm,
-                                category: Category::Node,
-                                risk: RiskLevel::Safe,
-                                size_bytes: size,
-                                description: format!(
-                                    "node_modules for {}",
-                                    parent.file_name().unwrap_or_default().to_string_lossy()
-                                ),
-                                last_modified: None,
-                            });
-                        }
-                    }
-                }
+impl ProjectDetector for NodeModules {
+    fn markers(&self) -> &[&'static str] {
+        &["package.json"]
+    }
+
+    fn artifact_dirs(&self) -> &[&'static str] {
+        &["node_modules"]
+    }
+
+    fn detect(&self, ctx: &ScanContext, projects: &[PathBuf]) -> Vec<Finding> {
+        let min_size = ctx
+            .config
+            .recognizer_settings(self.id())
+            .min_size
+            .unwrap_or(DEFAULT_MIN_SIZE);
+        let mut findings = Vec::new();
+
+        for project in projects {
+            let nm = project.join("node_modules");
+            if !nm.is_dir() {
+                continue;
+            }
+            let usage = dir_usage(&nm);
+            if usage.size_bytes > 0 && usage.size_bytes >= min_size {
+                findings.push(Finding {
+                    path: nm,
+                    category: Category::Node,
+                    recognizer_id: self.id(),
+                    risk: RiskLevel::Safe,
+                    size_bytes: usage.size_bytes,
+                    apparent_bytes: usage.apparent_bytes,
+                    description: format!(
+                        "node_modules for {}",
+                        project.file_name().unwrap_or_default().to_string_lossy()
+                    ),
+                    last_modified: usage.last_used.max(project::last_activity(
+                        project,
+                        &[
+                            "package.json",
+                            "package-lock.json",
+                            "yarn.lock",
+                            "pnpm-lock.yaml",
+                            "src",
+                        ],
+                    )),
+                    native: None,
+                    extra_paths: Vec::new(),
+                    projects: Vec::new(),
+                });
             }
         }
+
+        findings
     }
 }
@@ -1,9 +1,15 @@
 use crate::error::Result;
-use crate::finding::{Category, Finding, RiskLevel};
-use crate::recognizer::Recognizer;
-use crate::size::dir_size;
+use crate::finding::{Category, Finding, NativeAction, RiskLevel};
+use crate::paths::{Locations, Platform};
+use crate::recognizer::{Recognizer, ScanContext};
+use crate::size::{dir_usage, format_bytes, DirUsage};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
 
-/// Ollama downloaded models.
+/// Ollama downloaded models — one finding per model tag, and one per blob no
+/// model uses.
 pub struct OllamaModels;
 
 impl Recognizer for OllamaModels {
@@ -19,27 +25,269 @@ impl Recognizer for OllamaModels {
         Category::Ollama
     }
 
-    fn scan(&self) -> Result<Vec<Finding>> {
-        let Some(home) = dirs::home_dir() else {
-            return Ok(vec![]);
-        };
-        let path = home.join(".ollama/models");
-        if !path.exists() {
-            return Ok(vec![]);
+    fn scan(&self, ctx: &ScanContext) -> Result<Vec<Finding>> {
+        let mut findings = Vec::new();
+
+        for path in ollama_model_dirs(&ctx.locations) {
+            if path.exists() {
+                findings.extend(scan_models(&path, self.id()));
+            }
         }
 
-        let size = dir_size(&path);
-        if size == 0 {
-            return Ok(vec![]);
+        Ok(findings)
+    }
+}
+
+/// `$OLLAMA_MODELS`, else `~/.ollama/models`.
+///
+/// The Linux install script runs the server as an `ollama` system user whose
+/// models live under `/usr/share/ollama`, so that location is checked too.
+fn ollama_model_dirs(loc: &Locations) -> Vec<PathBuf> {
+    if let Some(dir) = loc.var_path("OLLAMA_MODELS") {
+        return vec![dir];
+    }
+
+    let mut dirs = vec![loc.home().join(".ollama/models")];
+    if loc.platform() == Platform::Linux {
+        dirs.push(PathBuf::from("/usr/share/ollama/.ollama/models"));
+    }
+    dirs
+}
+
+/// The parts of an OCI image manifest naming its blobs.
+#[derive(Deserialize)]
+struct Manifest {
+    config: Option<Layer>,
+    #[serde(default)]
+    layers: Vec<Layer>,
+}
+
+#[derive(Deserialize)]
+struct Layer {
+    digest: String,
+}
+
+/// A model tag and the blobs its manifest lists.
+struct Model {
+    name: String,
+    manifest: PathBuf,
+    blobs: HashSet<PathBuf>,
+}
+
+/// Partial downloads younger than this may belong to a pull still running.
+const PARTIAL_MIN_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Findings for the models under `models`: each tag with the layers only it
+/// uses, then each blob no manifest lists.
+///
+/// Layers shared between tags are left out of every tag's finding, so
+/// deleting one model never breaks another; once no model uses a layer it
+/// shows up as an orphan. Every manifest protects the blobs it mentions, even
+/// one that doesn't parse or isn't named like a tag, and if any manifest
+/// can't be read no blob is called an orphan.
+fn scan_models(models: &Path, recognizer_id: &'static str) -> Vec<Finding> {
+    let blobs_dir = models.join("blobs");
+    let manifests_dir = models.join("manifests");
+    let mut manifests = Vec::new();
+    list_files(&manifests_dir, &mut manifests);
+    manifests.sort();
+
+    let mut parsed = Vec::new();
+    let mut users: HashMap<PathBuf, usize> = HashMap::new();
+    let mut unreadable = false;
+    for manifest in manifests {
+        let Ok(text) = std::fs::read_to_string(&manifest) else {
+            unreadable = true;
+            continue;
+        };
+        let layers = serde_json::from_str::<Manifest>(&text)
+            .ok()
+            .map(|parsed| parsed.config.into_iter().chain(parsed.layers));
+        let well_formed = layers.is_some();
+        let blobs: HashSet<PathBuf> = match layers {
+            Some(layers) => layers
+                .map(|layer| blob_path(&blobs_dir, &layer.digest))
+                .collect(),
+            None => digests(&text)
+                .map(|digest| blob_path(&blobs_dir, digest))
+                .collect(),
+        };
+        for blob in &blobs {
+            *users.entry(blob.clone()).or_default() += 1;
+        }
+        let name = manifest
+            .strip_prefix(&manifests_dir)
+            .ok()
+            .and_then(model_name);
+        if let Some(name) = name.filter(|_| well_formed) {
+            parsed.push(Model {
+                name,
+                manifest,
+                blobs,
+            });
         }
+    }
+    let blob_usage: HashMap<&Path, DirUsage> = users
+        .keys()
+        .map(|blob| (blob.as_path(), dir_usage(blob)))
+        .collect();
+
+    let mut findings = Vec::new();
+    for model in &parsed {
+        let mut exclusive: Vec<PathBuf> = model
+            .blobs
+            .iter()
+            .filter(|blob| users[blob.as_path()] == 1 && blob.exists())
+            .cloned()
+            .collect();
+        exclusive.sort();
+        let freed = exclusive
+            .iter()
+            .map(|blob| blob_usage[blob.as_path()])
+            .fold(dir_usage(&model.manifest), DirUsage::combine);
+        let shared: u64 = model
+            .blobs
+            .iter()
+            .filter(|blob| users[blob.as_path()] > 1)
+            .map(|blob| blob_usage[blob.as_path()].size_bytes)
+            .sum();
+        // Loading a model reads its blobs, so they say when it was last used
+        let last_used = model
+            .blobs
+            .iter()
+            .filter_map(|blob| blob_usage[blob.as_path()].last_used)
+            .max();
 
-        Ok(vec![Finding {
-            path,
+        let mut description = format!(
+            "Ollama model {} — re-downloaded with `ollama pull`",
+            model.name
+        );
+        if shared > 0 {
+            description.push_str(&format!(
+                " ({} shared with other models stays)",
+                format_bytes(shared)
+            ));
+        }
+        findings.push(Finding {
+            path: model.manifest.clone(),
             category: Category::Ollama,
+            recognizer_id,
             risk: RiskLevel::Moderate,
-            size_bytes: size,
-            description: "Ollama model files — re-downloaded with `ollama pull`".into(),
-            last_modified: None,
-        }])
+            size_bytes: freed.size_bytes,
+            apparent_bytes: freed.apparent_bytes,
+            description,
+            last_modified: last_used.max(freed.last_used),
+            native: Some(NativeAction::new(format!("ollama rm {}", model.name))),
+            extra_paths: exclusive,
+            projects: Vec::new(),
+        });
+    }
+
+    if unreadable {
+        return findings;
+    }
+    let Ok(entries) = std::fs::read_dir(&blobs_dir) else {
+        return findings;
+    };
+    let mut orphans: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with("sha256-"))
+                && path.is_file()
+                && !users.contains_key(path)
+        })
+        .collect();
+    orphans.sort();
+    for blob in orphans {
+        let partial = blob.to_string_lossy().contains("-partial");
+        // A running `ollama pull` writes its partial files as it goes
+        if partial && !modified_before(&blob, PARTIAL_MIN_AGE) {
+            continue;
+        }
+        let usage = dir_usage(&blob);
+        findings.push(Finding {
+            path: blob,
+            category: Category::Ollama,
+            recognizer_id,
+            risk: if partial {
+                RiskLevel::Moderate
+            } else {
+                RiskLevel::Safe
+            },
+            size_bytes: usage.size_bytes,
+            apparent_bytes: usage.apparent_bytes,
+            description: if partial {
+                "Ollama download left over from an interrupted `ollama pull`".into()
+            } else {
+                "Ollama layer no model uses any more".into()
+            },
+            last_modified: usage.last_used,
+            native: None,
+            extra_paths: Vec::new(),
+            projects: Vec::new(),
+        });
+    }
+    findings
+}
+
+/// Where the blob with `digest` (`sha256:…`) is stored.
+fn blob_path(blobs_dir: &Path, digest: &str) -> PathBuf {
+    blobs_dir.join(digest.replace(':', "-"))
+}
+
+/// Every `sha256:…` digest mentioned in `text`.
+fn digests(text: &str) -> impl Iterator<Item = &str> {
+    text.match_indices("sha256:").map(|(start, prefix)| {
+        let rest = &text[start + prefix.len()..];
+        let len = rest
+            .find(|c: char| !c.is_ascii_alphanumeric())
+            .unwrap_or(rest.len());
+        &text[start..start + prefix.len() + len]
+    })
+}
+
+/// Whether `path` was last written more than `age` ago.
+fn modified_before(path: &Path, age: Duration) -> bool {
+    std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .is_some_and(|elapsed| elapsed >= age)
+}
+
+/// The name `ollama` knows a model by, from its manifest path
+/// `host/namespace/model/tag`: `llama3:8b` for the official library,
+/// `namespace/model:tag` for others on ollama.com, and the full
+/// `host/namespace/model:tag` elsewhere.
+fn model_name(relative: &Path) -> Option<String> {
+    let parts: Vec<&str> = relative
+        .iter()
+        .map(|part| part.to_str())
+        .collect::<Option<_>>()?;
+    let [host, namespace, model, tag] = parts[..] else {
+        return None;
+    };
+    Some(match (host, namespace) {
+        ("registry.ollama.ai", "library") => format!("{model}:{tag}"),
+        ("registry.ollama.ai", _) => format!("{namespace}/{model}:{tag}"),
+        _ => format!("{host}/{namespace}/{model}:{tag}"),
+    })
+}
+
+/// Every file under `dir`.
+fn list_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        match entry.file_type() {
+            Ok(file_type) if file_type.is_dir() => list_files(&path, files),
+            Ok(file_type) if file_type.is_file() => files.push(path),
+            _ => {}
+        }
     }
 }
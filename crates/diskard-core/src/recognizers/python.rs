@@ -1,7 +1,13 @@
 use crate::error::Result;
-use crate::finding::{Category, Finding, RiskLevel};
-use crate::recognizer::Recognizer;
-use crate::size::dir_size;
+use crate::finding::{Category, Finding, NativeAction, RiskLevel};
+use crate::paths::{Locations, Platform};
+use crate::project::{self, ArtifactCheck, ProjectDetector};
+use crate::recognizer::{Recognizer, ScanContext};
+use crate::size::{dir_usage, DirUsage};
+use crate::walk::subdirs;
+use std::borrow::Cow;
+use std::collections::{BTreeSet, HashSet};
+use std::path::{Path, PathBuf};
 
 /// pip download cache.
 pub struct PipCache;
@@ -19,27 +25,605 @@ impl Recognizer for PipCache {
         Category::Python
     }
 
-    fn scan(&self) -> Result<Vec<Finding>> {
-        let Some(home) = dirs::home_dir() else {
-            return Ok(vec![]);
-        };
-        let path = home.join("Library/Caches/pip");
+    fn scan(&self, ctx: &ScanContext) -> Result<Vec<Finding>> {
+        let path = pip_cache_dir(&ctx.locations);
         if !path.exists() {
             return Ok(vec![]);
         }
 
-        let size = dir_size(&path);
-        if size == 0 {
+        let usage = dir_usage(&path);
+        if usage.size_bytes == 0 {
             return Ok(vec![]);
         }
 
         Ok(vec![Finding {
             path,
             category: Category::Python,
+            recognizer_id: self.id(),
             risk: RiskLevel::Safe,
-            size_bytes: size,
+            size_bytes: usage.size_bytes,
+            apparent_bytes: usage.apparent_bytes,
             description: "pip package cache — re-downloaded on next install".into(),
-            last_modified: None,
+            last_modified: usage.last_used,
+            native: Some(NativeAction::new("pip cache purge")),
+            extra_paths: Vec::new(),
+            projects: Vec::new(),
         }])
     }
 }
+
+/// `$PIP_CACHE_DIR`, else `pip` under the platform cache dir.
+fn pip_cache_dir(loc: &Locations) -> PathBuf {
+    loc.var_path("PIP_CACHE_DIR")
+        .unwrap_or_else(|| loc.cache_dir().join("pip"))
+}
+
+/// Files marking a Python project directory.
+const PROJECT_MARKERS: [&str; 7] = [
+    "pyproject.toml",
+    "setup.py",
+    "setup.cfg",
+    "requirements.txt",
+    "Pipfile",
+    "tox.ini",
+    "noxfile.py",
+];
+
+/// Project markers plus the `pyvenv.cfg` of virtualenvs by any name.
+const VENV_MARKERS: [&str; PROJECT_MARKERS.len() + 1] = {
+    let mut markers = ["pyvenv.cfg"; PROJECT_MARKERS.len() + 1];
+    let mut i = 0;
+    while i < PROJECT_MARKERS.len() {
+        markers[i] = PROJECT_MARKERS[i];
+        i += 1;
+    }
+    markers
+};
+
+/// Project files whose edits say a project is still being worked on.
+const PROJECT_FILES: [&str; 7] = [
+    "pyproject.toml",
+    "setup.py",
+    "setup.cfg",
+    "requirements.txt",
+    "Pipfile.lock",
+    "poetry.lock",
+    "uv.lock",
+];
+
+/// Directory names a project's virtualenv usually has.
+const VENV_NAMES: [&str; 3] = [".venv", "venv", "env"];
+
+/// A finding for `path` if it holds anything, sized and dated from disk.
+fn measured(
+    path: PathBuf,
+    recognizer_id: &'static str,
+    risk: RiskLevel,
+    description: String,
+    native: Option<NativeAction>,
+) -> Option<Finding> {
+    let usage = dir_usage(&path);
+    if usage.size_bytes == 0 {
+        return None;
+    }
+    Some(Finding {
+        path,
+        category: Category::Python,
+        recognizer_id,
+        risk,
+        size_bytes: usage.size_bytes,
+        apparent_bytes: usage.apparent_bytes,
+        description,
+        last_modified: usage.last_used,
+        native,
+        extra_paths: Vec::new(),
+        projects: Vec::new(),
+    })
+}
+
+fn project_name(project: &Path) -> Cow<'_, str> {
+    project.file_name().unwrap_or_default().to_string_lossy()
+}
+
+/// Whether `dir` is a virtualenv, which `venv`, virtualenv and uv all mark
+/// with a `pyvenv.cfg`.
+fn is_venv(dir: &Path) -> bool {
+    dir.join("pyvenv.cfg").is_file()
+}
+
+/// Virtualenvs in project trees — project-based scanner.
+pub struct PythonVenvs;
+
+impl Recognizer for PythonVenvs {
+    fn name(&self) -> &'static str {
+        "Python virtualenvs"
+    }
+
+    fn id(&self) -> &'static str {
+        "python-venvs"
+    }
+
+    fn category(&self) -> Category {
+        Category::Python
+    }
+
+    fn scan(&self, _ctx: &ScanContext) -> Result<Vec<Finding>> {
+        Ok(vec![])
+    }
+
+    fn project_detector(&self) -> Option<&dyn ProjectDetector> {
+        Some(self)
+    }
+}
+
+impl ProjectDetector for PythonVenvs {
+    /// Project files find `.venv`, which is hidden from the walk; `pyvenv.cfg`
+    /// finds virtualenvs by any other name.
+    fn markers(&self) -> &[&'static str] {
+        &VENV_MARKERS
+    }
+
+    fn artifact_dirs(&self) -> &[&'static str] {
+        &["venv"]
+    }
+
+    fn artifact_check(&self) -> ArtifactCheck {
+        is_venv
+    }
+
+    fn detect(&self, _ctx: &ScanContext, projects: &[PathBuf]) -> Vec<Finding> {
+        let mut venvs: BTreeSet<(PathBuf, &Path)> = BTreeSet::new();
+        for dir in projects {
+            if is_venv(dir) {
+                if let Some(project) = dir.parent() {
+                    venvs.insert((dir.clone(), project));
+                }
+                continue;
+            }
+            for name in VENV_NAMES {
+                let venv = dir.join(name);
+                if is_venv(&venv) {
+                    venvs.insert((venv, dir));
+                }
+            }
+        }
+
+        venvs
+            .into_iter()
+            .filter_map(|(venv, project)| {
+                let mut finding = measured(
+                    venv,
+                    self.id(),
+                    RiskLevel::Moderate,
+                    format!(
+                        "Python virtualenv for {} — recreate it and reinstall its packages",
+                        project_name(project)
+                    ),
+                    None,
+                )?;
+                finding.last_modified = finding
+                    .last_modified
+                    .max(project::last_activity(project, &PROJECT_FILES));
+                Some(finding)
+            })
+            .collect()
+    }
+}
+
+/// Bytecode and tool caches in Python projects — project-based scanner.
+pub struct PythonToolCaches;
+
+/// Per-project tool caches, with why each is safe to delete.
+const TOOL_CACHES: [(&str, &str); 5] = [
+    (
+        ".pytest_cache",
+        "pytest cache — only remembers the last failures",
+    ),
+    (".mypy_cache", "mypy cache — rebuilt on the next type check"),
+    (".ruff_cache", "Ruff cache — rebuilt on the next lint"),
+    (".tox", "tox environments — recreated on the next tox run"),
+    (".nox", "nox sessions — recreated on the next nox run"),
+];
+
+impl Recognizer for PythonToolCaches {
+    fn name(&self) -> &'static str {
+        "Python tool caches"
+    }
+
+    fn id(&self) -> &'static str {
+        "python-caches"
+    }
+
+    fn category(&self) -> Category {
+        Category::Python
+    }
+
+    fn scan(&self, _ctx: &ScanContext) -> Result<Vec<Finding>> {
+        Ok(vec![])
+    }
+
+    fn project_detector(&self) -> Option<&dyn ProjectDetector> {
+        Some(self)
+    }
+}
+
+impl ProjectDetector for PythonToolCaches {
+    fn markers(&self) -> &[&'static str] {
+        &PROJECT_MARKERS
+    }
+
+    fn artifact_dirs(&self) -> &[&'static str] {
+        &["__pycache__"]
+    }
+
+    fn detect(&self, ctx: &ScanContext, projects: &[PathBuf]) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        for project in projects {
+            for (name, reason) in TOOL_CACHES {
+                findings.extend(measured(
+                    project.join(name),
+                    self.id(),
+                    RiskLevel::Safe,
+                    format!("{} {reason}", project_name(project)),
+                    None,
+                ));
+            }
+            findings.extend(self.pycache(ctx, project, projects));
+        }
+        findings
+    }
+}
+
+impl PythonToolCaches {
+    /// The project's `__pycache__` directories as one finding, leaving out
+    /// hidden directories, virtualenvs and projects nested inside it.
+    fn pycache(&self, ctx: &ScanContext, project: &Path, projects: &[PathBuf]) -> Option<Finding> {
+        let nested: HashSet<PathBuf> = projects
+            .iter()
+            .filter(|p| p.as_path() != project && p.starts_with(project))
+            .cloned()
+            .collect();
+        let walker = ctx.project_walker(project, move |entry| {
+            let name = entry.file_name().to_string_lossy();
+            name.starts_with('.')
+                || name == "__pycache__"
+                || name == "node_modules"
+                || nested.contains(&entry.path())
+                || is_venv(&entry.path())
+        });
+        let mut dirs: Vec<PathBuf> = walker
+            .into_iter()
+            .flatten()
+            .filter(|e| e.file_type().is_dir() && e.file_name() == "__pycache__")
+            .map(|e| e.path())
+            .collect();
+        dirs.sort();
+
+        let usage = dirs
+            .iter()
+            .map(|dir| dir_usage(dir))
+            .fold(DirUsage::default(), DirUsage::combine);
+        if usage.size_bytes == 0 {
+            return None;
+        }
+        let mut dirs = dirs.into_iter();
+        Some(Finding {
+            path: dirs.next()?,
+            category: Category::Python,
+            recognizer_id: self.id(),
+            risk: RiskLevel::Safe,
+            size_bytes: usage.size_bytes,
+            apparent_bytes: usage.apparent_bytes,
+            description: format!(
+                "{} __pycache__ directories in {} — bytecode, recompiled on import",
+                dirs.len() + 1,
+                project_name(project)
+            ),
+            last_modified: usage.last_used,
+            native: None,
+            extra_paths: dirs.collect(),
+            projects: Vec::new(),
+        })
+    }
+}
+
+/// uv's package and build cache.
+pub struct UvCache;
+
+impl Recognizer for UvCache {
+    fn name(&self) -> &'static str {
+        "uv cache"
+    }
+
+    fn id(&self) -> &'static str {
+        "uv-cache"
+    }
+
+    fn category(&self) -> Category {
+        Category::Python
+    }
+
+    /// uv keeps the XDG layout on macOS too.
+    fn scan(&self, ctx: &ScanContext) -> Result<Vec<Finding>> {
+        let path = ctx
+            .locations
+            .var_path("UV_CACHE_DIR")
+            .unwrap_or_else(|| ctx.locations.xdg_cache_home().join("uv"));
+        Ok(measured(
+            path,
+            self.id(),
+            RiskLevel::Safe,
+            "uv package cache — re-downloaded on next install".into(),
+            Some(NativeAction::new("uv cache clean")),
+        )
+        .into_iter()
+        .collect())
+    }
+}
+
+/// Poetry's package cache and the virtualenvs it manages.
+pub struct PoetryCache;
+
+impl Recognizer for PoetryCache {
+    fn name(&self) -> &'static str {
+        "Poetry cache"
+    }
+
+    fn id(&self) -> &'static str {
+        "poetry-cache"
+    }
+
+    fn category(&self) -> Category {
+        Category::Python
+    }
+
+    fn scan(&self, ctx: &ScanContext) -> Result<Vec<Finding>> {
+        let root = ctx
+            .locations
+            .var_path("POETRY_CACHE_DIR")
+            .unwrap_or_else(|| ctx.locations.cache_dir().join("pypoetry"));
+        let mut findings: Vec<Finding> = [
+            ("cache", "Poetry package index cache"),
+            ("artifacts", "Poetry downloaded distributions"),
+        ]
+        .into_iter()
+        .filter_map(|(dir, what)| {
+            measured(
+                root.join(dir),
+                self.id(),
+                RiskLevel::Safe,
+                format!("{what} — re-downloaded on next install"),
+                None,
+            )
+        })
+        .collect();
+
+        for venv in subdirs(&root.join("virtualenvs")) {
+            let name = project_name(&venv).into_owned();
+            findings.extend(measured(
+                venv,
+                self.id(),
+                RiskLevel::Moderate,
+                format!("Poetry virtualenv {name} — recreated by `poetry install`"),
+                None,
+            ));
+        }
+        Ok(findings)
+    }
+}
+
+/// pipx's cache and the apps it installed.
+pub struct Pipx;
+
+impl Recognizer for Pipx {
+    fn name(&self) -> &'static str {
+        "pipx"
+    }
+
+    fn id(&self) -> &'static str {
+        "pipx"
+    }
+
+    fn category(&self) -> Category {
+        Category::Python
+    }
+
+    fn scan(&self, ctx: &ScanContext) -> Result<Vec<Finding>> {
+        let mut findings = Vec::new();
+        for home in pipx_homes(&ctx.locations) {
+            findings.extend(measured(
+                home.join(".cache"),
+                self.id(),
+                RiskLevel::Safe,
+                "pipx run cache — re-downloaded by `pipx run`".into(),
+                None,
+            ));
+            for venv in subdirs(&home.join("venvs")) {
+                let app = project_name(&venv).into_owned();
+                findings.extend(measured(
+                    venv,
+                    self.id(),
+                    RiskLevel::Risky,
+                    format!(
+                        "pipx app {app} — its commands stop working until `pipx install {app}`"
+                    ),
+                    None,
+                ));
+            }
+        }
+        Ok(findings)
+    }
+}
+
+/// `$PIPX_HOME`, else the platform data directory newer pipx uses and the
+/// `~/.local/pipx` older versions did.
+fn pipx_homes(loc: &Locations) -> Vec<PathBuf> {
+    if let Some(home) = loc.var_path("PIPX_HOME") {
+        return vec![home];
+    }
+    let data = match loc.platform() {
+        Platform::MacOs => loc.home().join("Library/Application Support/pipx"),
+        _ => loc.xdg_data_home().join("pipx"),
+    };
+    vec![data, loc.home().join(".local/pipx")]
+}
+
+/// Python versions pyenv installed, other than the global ones and those
+/// pinned by a project's `.python-version` — project-based scanner.
+pub struct PyenvVersions;
+
+impl Recognizer for PyenvVersions {
+    fn name(&self) -> &'static str {
+        "pyenv versions"
+    }
+
+    fn id(&self) -> &'static str {
+        "pyenv-versions"
+    }
+
+    fn category(&self) -> Category {
+        Category::Python
+    }
+
+    fn scan(&self, _ctx: &ScanContext) -> Result<Vec<Finding>> {
+        Ok(vec![])
+    }
+
+    fn project_detector(&self) -> Option<&dyn ProjectDetector> {
+        Some(self)
+    }
+}
+
+impl ProjectDetector for PyenvVersions {
+    fn markers(&self) -> &[&'static str] {
+        &[".python-version"]
+    }
+
+    fn artifact_dirs(&self) -> &[&'static str] {
+        &[]
+    }
+
+    fn guards_active_projects(&self) -> bool {
+        false
+    }
+
+    fn detect(&self, ctx: &ScanContext, projects: &[PathBuf]) -> Vec<Finding> {
+        let root = ctx
+            .locations
+            .var_path("PYENV_ROOT")
+            .unwrap_or_else(|| ctx.locations.home().join(".pyenv"));
+        let versions = root.join("versions");
+        let pinned: Vec<PathBuf> = std::iter::once(root.join("version"))
+            .chain(projects.iter().map(|p| p.join(".python-version")))
+            .flat_map(|file| pinned_versions(&file))
+            .flat_map(|pin| resolve_version(&versions, &pin))
+            .collect();
+
+        subdirs(&versions)
+            .into_iter()
+            .filter(|dir| {
+                let real = dir.canonicalize().unwrap_or_else(|_| dir.clone());
+                !pinned.iter().any(|kept| kept.starts_with(&real))
+            })
+            .filter_map(|dir| {
+                let version = project_name(&dir).into_owned();
+                measured(
+                    dir,
+                    self.id(),
+                    RiskLevel::Risky,
+                    format!(
+                        "Python {version} from pyenv — not the global version or pinned by a \
+                         scanned project; virtualenvs built on it stop working"
+                    ),
+                    None,
+                )
+            })
+            .collect()
+    }
+}
+
+/// The versions a pyenv `version` or `.python-version` file names, one or
+/// more per line, skipping comments.
+fn pinned_versions(file: &Path) -> Vec<String> {
+    let text = std::fs::read_to_string(file).unwrap_or_default();
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with('#'))
+        .flat_map(str::split_whitespace)
+        .map(String::from)
+        .collect()
+}
+
+/// Where the installed versions `pin` may select really are: `versions/<pin>`,
+/// following the links pyenv-virtualenv makes to environments inside a
+/// version, or for a prefix such as `3.12` every version it starts.
+fn resolve_version(versions: &Path, pin: &str) -> Vec<PathBuf> {
+    let exact = versions.join(pin);
+    let selected = if exact.exists() {
+        vec![exact]
+    } else {
+        let prefix = format!("{pin}.");
+        subdirs(versions)
+            .into_iter()
+            .filter(|dir| project_name(dir).starts_with(&prefix))
+            .collect()
+    };
+    selected
+        .into_iter()
+        .map(|dir| dir.canonicalize().unwrap_or(dir))
+        .collect()
+}
+
+/// Conda's package cache and environments.
+pub struct CondaData;
+
+impl Recognizer for CondaData {
+    fn name(&self) -> &'static str {
+        "conda packages and environments"
+    }
+
+    fn id(&self) -> &'static str {
+        "conda"
+    }
+
+    fn category(&self) -> Category {
+        Category::Python
+    }
+
+    fn scan(&self, ctx: &ScanContext) -> Result<Vec<Finding>> {
+        let mut findings = Vec::new();
+        for root in conda_roots(&ctx.locations) {
+            findings.extend(measured(
+                root.join("pkgs"),
+                self.id(),
+                RiskLevel::Safe,
+                "conda package cache — re-downloaded when an environment needs it".into(),
+                Some(NativeAction::new("conda clean --all --yes")),
+            ));
+            for env in subdirs(&root.join("envs")) {
+                let name = project_name(&env).into_owned();
+                findings.extend(measured(
+                    env,
+                    self.id(),
+                    RiskLevel::Risky,
+                    format!("conda environment {name} — recreate it from its environment file"),
+                    None,
+                ));
+            }
+        }
+        Ok(findings)
+    }
+}
+
+/// Where conda distributions install by default, plus `~/.conda`, which
+/// holds environments and packages created without write access to them.
+fn conda_roots(loc: &Locations) -> Vec<PathBuf> {
+    let mut roots = vec![loc.home().join(".conda")];
+    for name in ["miniconda3", "anaconda3", "miniforge3", "mambaforge"] {
+        roots.push(loc.home().join(name));
+        if loc.platform() == Platform::MacOs {
+            roots.push(loc.home().join("opt").join(name));
+        }
+    }
+    roots
+}
@@ -1,12 +1,22 @@
 use crate::error::Result;
-use crate::finding::{Category, Finding, RiskLevel};
-use crate::recognizer::Recognizer;
-use crate::size::dir_size;
-use std::path::PathBuf;
+use crate::finding::{Category, Finding, NativeAction, RiskLevel};
+use crate::paths::Locations;
+use crate::project::{self, ArtifactCheck, ProjectDetector};
+use crate::recognizer::{Recognizer, ScanContext};
+use crate::size::{dir_usage, DirUsage};
+use crate::walk::subdirs;
+use serde::Deserialize;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
 
 /// Cargo target directories — build artifacts from Rust projects.
 pub struct CargoTarget;
 
+/// Target directories smaller than this (1 MiB) aren't reported unless
+/// `[recognizers.cargo-target] min_size` says otherwise.
+const DEFAULT_MIN_SIZE: u64 = 1_048_576;
+
 impl Recognizer for CargoTarget {
     fn name(&self) -> &'static str {
         "Cargo target dirs"
@@ -20,78 +30,617 @@ impl Recognizer for CargoTarget {
         Category::Rust
     }
 
-    fn scan(&self) -> Result<Vec<Finding>> {
-        let Some(home) = dirs::home_dir() else {
-            return Ok(vec![]);
-        };
-
-        // Also check the cargo registry cache
+    fn scan(&self, ctx: &ScanContext) -> Result<Vec<Finding>> {
+        // Also check the caches under Cargo's home
+        let home = cargo_home(&ctx.locations);
         let mut findings = Vec::new();
 
-        let registry_cache = home.join(".cargo/registry/cache");
-        if registry_cache.exists() {
-            let size = dir_size(&registry_cache);
-            if size > 0 {
+        for (dir, risk, description) in [
+            (
+                "registry/cache",
+                RiskLevel::Safe,
+                "Cargo registry cache — re-downloaded when needed",
+            ),
+            (
+                "registry/src",
+                RiskLevel::Safe,
+                "Cargo registry sources — re-extracted from the registry cache",
+            ),
+            (
+                "git/checkouts",
+                RiskLevel::Safe,
+                "Cargo git dependency checkouts — recreated from the git database",
+            ),
+            (
+                "git/db",
+                RiskLevel::Moderate,
+                "Cargo git dependency clones — re-fetched when needed",
+            ),
+        ] {
+            let path = home.join(dir);
+            if !path.exists() {
+                continue;
+            }
+            let usage = dir_usage(&path);
+            if usage.size_bytes > 0 {
                 findings.push(Finding {
-                    path: registry_cache,
+                    path,
                     category: Category::Rust,
-                    risk: RiskLevel::Safe,
-                    size_bytes: size,
-                    description: "Cargo registry cache — re-downloaded when needed".into(),
-                    last_modified: None,
+                    recognizer_id: self.id(),
+                    risk,
+                    size_bytes: usage.size_bytes,
+                    apparent_bytes: usage.apparent_bytes,
+                    description: description.into(),
+                    last_modified: usage.last_used,
+                    native: None,
+                    extra_paths: Vec::new(),
+                    projects: Vec::new(),
                 });
             }
         }
 
-        // Scan common developer directories for Cargo projects
-        let scan_roots: Vec<PathBuf> = vec![
-            home.join("Developer"),
-            home.join("Projects"),
-            home.join("src"),
-        ];
+        Ok(findings)
+    }
 
-        for root in scan_roots {
-            if !root.exists() {
+    fn project_detector(&self) -> Option<&dyn ProjectDetector> {
+        Some(self)
+    }
+}
+
+impl ProjectDetector for CargoTarget {
+    fn markers(&self) -> &[&'static str] {
+        &["Cargo.toml"]
+    }
+
+    fn artifact_dirs(&self) -> &[&'static str] {
+        &["target"]
+    }
+
+    fn artifact_check(&self) -> ArtifactCheck {
+        is_cargo_target
+    }
+
+    fn detect(&self, ctx: &ScanContext, projects: &[PathBuf]) -> Vec<Finding> {
+        let min_size = ctx
+            .config
+            .recognizer_settings(self.id())
+            .min_size
+            .unwrap_or(DEFAULT_MIN_SIZE);
+        let mut findings = Vec::new();
+
+        for (target, shared) in shared_targets(ctx, projects) {
+            if !target.is_dir() {
                 continue;
             }
-            self.scan_for_targets(&root, &mut findings);
+            let owners: Vec<&Path> = shared.owners.into_iter().collect();
+            let mut members: Vec<PathBuf> =
+                shared.members.iter().map(|p| p.to_path_buf()).collect();
+            members.sort();
+            let name = match owners[..] {
+                [owner] => owner.file_name().unwrap_or_default().to_string_lossy(),
+                _ => format!("{} projects", owners.len()).into(),
+            };
+            let activity = members
+                .iter()
+                .filter_map(|member| project::last_activity(member, &PROJECT_FILES))
+                .max();
+
+            let mut parts = target_parts(&target, &name);
+            if parts.is_empty() {
+                // Not laid out the way cargo builds; report it whole
+                parts.push(Part {
+                    path: target.clone(),
+                    risk: RiskLevel::Moderate,
+                    description: format!("Rust build artifacts for {name}"),
+                    native: Some(NativeAction::new(
+                        "cargo clean --manifest-path {project}/Cargo.toml",
+                    )),
+                    extra_paths: Vec::new(),
+                });
+            }
+            // `cargo clean` only reaches the manifest from the target dir it
+            // builds into by default
+            let cleanable =
+                shared.current && owners.len() == 1 && target == owners[0].join("target");
+            for part in parts {
+                let usage = part
+                    .extra_paths
+                    .iter()
+                    .map(|p| dir_usage(p))
+                    .fold(dir_usage(&part.path), DirUsage::combine);
+                if usage.size_bytes == 0 || usage.size_bytes < min_size {
+                    continue;
+                }
+                findings.push(Finding {
+                    path: part.path,
+                    category: Category::Rust,
+                    recognizer_id: self.id(),
+                    risk: part.risk,
+                    size_bytes: usage.size_bytes,
+                    apparent_bytes: usage.apparent_bytes,
+                    description: part.description,
+                    last_modified: usage.last_used.max(activity),
+                    native: part.native.filter(|_| cleanable),
+                    extra_paths: part.extra_paths,
+                    projects: members.clone(),
+                });
+            }
         }
 
-        Ok(findings)
+        findings.extend(unlocked_crates(ctx, projects, self.id()));
+        findings
+    }
+}
+
+/// Project files whose edits say a crate is still being worked on.
+const PROJECT_FILES: [&str; 7] = [
+    "Cargo.toml",
+    "Cargo.lock",
+    "build.rs",
+    "src",
+    "tests",
+    "benches",
+    "examples",
+];
+
+/// A target directory and the projects building into it.
+#[derive(Default)]
+struct SharedTarget<'a> {
+    /// Workspaces or standalone crates using it.
+    owners: BTreeSet<&'a Path>,
+    /// Every project among them, members included.
+    members: Vec<&'a Path>,
+    /// Whether cargo still builds into it, rather than it being left over.
+    current: bool,
+}
+
+/// Each target directory the projects build into or have left behind.
+///
+/// Workspace members build into their workspace's target directory, and
+/// `$CARGO_TARGET_DIR` or a `build.target-dir` in `.cargo/config.toml`
+/// moves it elsewhere, so one directory may serve many projects.
+fn shared_targets<'a>(
+    ctx: &ScanContext,
+    projects: &'a [PathBuf],
+) -> BTreeMap<PathBuf, SharedTarget<'a>> {
+    let workspaces: Vec<&Path> = projects
+        .iter()
+        .filter(|project| is_workspace(project))
+        .map(PathBuf::as_path)
+        .collect();
+    let env_target = ctx.locations.var_path("CARGO_TARGET_DIR");
+
+    let mut targets: BTreeMap<PathBuf, SharedTarget> = BTreeMap::new();
+    for project in projects {
+        let owner = workspaces
+            .iter()
+            .filter(|root| project.starts_with(root))
+            .max_by_key(|root| root.components().count())
+            .copied()
+            .unwrap_or(project);
+        let target = env_target
+            .clone()
+            .or_else(|| configured_target(owner))
+            .unwrap_or_else(|| owner.join("target"));
+        // A target dir from before the project joined a workspace or moved
+        // its builds is still there
+        let own = project.join("target");
+        if own != target && own.is_dir() {
+            let shared = targets.entry(own).or_default();
+            shared.owners.insert(project);
+            shared.members.push(project);
+        }
+        let shared = targets.entry(target).or_default();
+        shared.owners.insert(owner);
+        shared.members.push(project);
+        shared.current = true;
+    }
+    targets
+}
+
+/// Whether `dir` is Cargo's build output: next to a manifest, or holding the
+/// files Cargo leaves in any target directory, shared ones included.
+fn is_cargo_target(dir: &Path) -> bool {
+    dir.parent()
+        .is_some_and(|parent| parent.join("Cargo.toml").is_file())
+        || dir.join(".rustc_info.json").is_file()
+        || dir.join("CACHEDIR.TAG").is_file()
+}
+
+/// Whether the manifest in `project` declares a `[workspace]`.
+fn is_workspace(project: &Path) -> bool {
+    std::fs::read_to_string(project.join("Cargo.toml"))
+        .ok()
+        .and_then(|text| text.parse::<toml::Table>().ok())
+        .is_some_and(|manifest| manifest.contains_key("workspace"))
+}
+
+/// `build.target-dir` from `.cargo/config.toml` in `project`, relative to
+/// the project.
+fn configured_target(project: &Path) -> Option<PathBuf> {
+    let text = std::fs::read_to_string(project.join(".cargo/config.toml")).ok()?;
+    let config = text.parse::<toml::Table>().ok()?;
+    let dir = project.join(config.get("build")?.get("target-dir")?.as_str()?);
+    Some(dir.canonicalize().unwrap_or(dir))
+}
+
+/// Whether `dir` is a profile's output, such as `target/debug`.
+fn is_profile(dir: &Path) -> bool {
+    ["deps", ".fingerprint", "build", "incremental"]
+        .iter()
+        .any(|name| dir.join(name).is_dir())
+}
+
+/// A piece of a target directory reported on its own.
+struct Part {
+    path: PathBuf,
+    risk: RiskLevel,
+    description: String,
+    /// The `cargo clean` clearing just this piece, if there is one. `{project}`
+    /// is the target directory, beside the manifest.
+    native: Option<NativeAction>,
+    extra_paths: Vec<PathBuf>,
+}
+
+/// The parts of a target directory worth clearing separately: each profile
+/// and its incremental cache, docs, packaged crates, and the builds for each
+/// cross-compile target. Whatever else tools put there, such as `criterion`
+/// reports or `flycheck` builds, is one more part.
+fn target_parts(target: &Path, name: &str) -> Vec<Part> {
+    let mut parts = Vec::new();
+    let mut others = Vec::new();
+    for dir in subdirs(target) {
+        let Some(dir_name) = dir.file_name().and_then(OsStr::to_str) else {
+            continue;
+        };
+        match dir_name {
+            "doc" => parts.push(Part {
+                description: format!("Rust docs for {name} — rebuilt by `cargo doc`"),
+                path: dir,
+                risk: RiskLevel::Safe,
+                native: Some(NativeAction::new(
+                    "cargo clean --manifest-path {project}/../Cargo.toml --doc",
+                )),
+                extra_paths: Vec::new(),
+            }),
+            "package" => parts.push(Part {
+                description: format!("Crates packaged from {name} by `cargo package`"),
+                path: dir,
+                risk: RiskLevel::Safe,
+                native: None,
+                extra_paths: Vec::new(),
+            }),
+            _ if is_profile(&dir) => {
+                parts.extend(incremental(&dir, name));
+                // The `dev` and `test` profiles build into `debug`
+                let profile = if dir_name == "debug" { "dev" } else { dir_name };
+                parts.push(Part {
+                    description: format!("Rust {dir_name} build of {name}"),
+                    native: Some(NativeAction::new(format!(
+                        "cargo clean --manifest-path {{project}}/../Cargo.toml --profile {profile}"
+                    ))),
+                    path: dir,
+                    risk: RiskLevel::Moderate,
+                    extra_paths: Vec::new(),
+                });
+            }
+            _ => {
+                let profiles: Vec<PathBuf> = subdirs(&dir)
+                    .into_iter()
+                    .filter(|p| is_profile(p))
+                    .collect();
+                if profiles.is_empty() {
+                    others.push(dir);
+                    continue;
+                }
+                for profile in &profiles {
+                    parts.extend(incremental(profile, name));
+                }
+                // `cargo clean --target` without a package clears everything,
+                // so cross builds have no narrower command
+                parts.push(Part {
+                    description: format!("Rust {dir_name} builds of {name}"),
+                    path: dir,
+                    risk: RiskLevel::Moderate,
+                    native: None,
+                    extra_paths: Vec::new(),
+                });
+            }
+        }
+    }
+
+    if parts.is_empty() {
+        return parts;
+    }
+    // Files such as `.rustc_info.json` after the directories
+    let mut files: Vec<PathBuf> = std::fs::read_dir(target)
+        .into_iter()
+        .flatten()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_ok_and(|t| !t.is_dir()))
+        .map(|e| e.path())
+        .collect();
+    files.sort();
+    others.extend(files);
+    if !others.is_empty() {
+        let names: Vec<String> = others
+            .iter()
+            .map(|p| {
+                p.file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .collect();
+        let path = others.remove(0);
+        parts.push(Part {
+            path,
+            risk: RiskLevel::Moderate,
+            description: format!("Other build output for {name}: {}", names.join(", ")),
+            native: None,
+            extra_paths: others,
+        });
     }
+    parts
+}
+
+/// The incremental compilation cache of `profile`, if it has one.
+fn incremental(profile: &Path, name: &str) -> Option<Part> {
+    let path = profile.join("incremental");
+    path.is_dir().then(|| Part {
+        path,
+        risk: RiskLevel::Safe,
+        description: format!("Incremental compilation cache for {name} — rebuilt on next build"),
+        native: None,
+        extra_paths: Vec::new(),
+    })
+}
+
+/// `$CARGO_HOME`, else `~/.cargo`.
+fn cargo_home(loc: &Locations) -> PathBuf {
+    loc.var_path("CARGO_HOME")
+        .unwrap_or_else(|| loc.home().join(".cargo"))
+}
+
+/// The parts of a `Cargo.lock` naming the crates it uses.
+#[derive(Deserialize)]
+struct Lockfile {
+    #[serde(default)]
+    package: Vec<LockedPackage>,
 }
 
-impl CargoTarget {
-    fn scan_for_targets(&self, root: &PathBuf, findings: &mut Vec<Finding>) {
-        // Walk top 3 levels looking for Cargo.toml + target/
-        let walker = jwalk::WalkDir::new(root)
-            .max_depth(4)
-            .skip_hidden(true)
-            .into_iter();
-
-        for entry in walker.flatten() {
-            if entry.file_name().to_str() == Some("Cargo.toml") && entry.file_type().is_file() {
-                if let Some(parent) = entry.path().parent() {
-                    let target = parent.join("target");
-                    if target.exists() && target.is_dir() {
-                        let size = dir_size(&target);
-                        if size > 1_048_576 {
-                            // Only report if > 1MB
-                            findings.push(Finding {
-                                path: target,
-                                category: Category::Rust,
-                                risk: RiskLevel::Moderate,
-                                size_bytes: size,
-                                description: format!(
-                                    "Rust build artifacts for {}",
-                                    parent.file_name().unwrap_or_default().to_string_lossy()
-                                ),
-                                last_modified: None,
-                            });
-                        }
-                    }
+#[derive(Deserialize)]
+struct LockedPackage {
+    name: String,
+    version: String,
+}
+
+/// Crate versions in the registry that no `Cargo.lock` in `projects` uses:
+/// the downloaded `.crate` file with its extracted sources.
+///
+/// Without a lockfile there is nothing to compare against, so nothing is
+/// reported.
+fn unlocked_crates(
+    ctx: &ScanContext,
+    projects: &[PathBuf],
+    recognizer_id: &'static str,
+) -> Vec<Finding> {
+    let locked: HashSet<String> = projects
+        .iter()
+        .filter_map(|project| std::fs::read_to_string(project.join("Cargo.lock")).ok())
+        .filter_map(|text| toml::from_str::<Lockfile>(&text).ok())
+        .flat_map(|lockfile| lockfile.package)
+        .map(|package| format!("{}-{}", package.name, package.version))
+        .collect();
+    if locked.is_empty() {
+        return vec![];
+    }
+
+    // Both layouts are `<registry>/<name>-<version>`, with `.crate` on downloads
+    let registry = cargo_home(&ctx.locations).join("registry");
+    let mut crates: BTreeMap<(OsString, String), (Option<PathBuf>, Option<PathBuf>)> =
+        BTreeMap::new();
+    for (kind, is_src) in [("cache", false), ("src", true)] {
+        for index in subdirs(&registry.join(kind)) {
+            let Some(index_name) = index.file_name().map(OsStr::to_os_string) else {
+                continue;
+            };
+            let Ok(entries) = std::fs::read_dir(&index) else {
+                continue;
+            };
+            for entry in entries.filter_map(|e| e.ok()) {
+                let path = entry.path();
+                let name = entry.file_name().to_string_lossy().into_owned();
+                let (stem, matches) = match name.strip_suffix(".crate") {
+                    Some(stem) => (stem.to_string(), !is_src && path.is_file()),
+                    None => (name, is_src && path.is_dir()),
+                };
+                if !matches || locked.contains(&stem) {
+                    continue;
+                }
+                let slot = crates.entry((index_name.clone(), stem)).or_default();
+                if is_src {
+                    slot.1 = Some(path);
+                } else {
+                    slot.0 = Some(path);
                 }
             }
         }
     }
+
+    crates
+        .into_iter()
+        .filter_map(|((_, stem), (download, sources))| {
+            let (path, extra_paths) = match (download, sources) {
+                (Some(download), sources) => (download, sources.into_iter().collect()),
+                (None, Some(sources)) => (sources, Vec::new()),
+                (None, None) => return None,
+            };
+            let usage = extra_paths
+                .iter()
+                .map(|p| dir_usage(p))
+                .fold(dir_usage(&path), DirUsage::combine);
+            if usage.size_bytes == 0 {
+                return None;
+            }
+            Some(Finding {
+                path,
+                category: Category::Rust,
+                recognizer_id,
+                risk: RiskLevel::Safe,
+                size_bytes: usage.size_bytes,
+                apparent_bytes: usage.apparent_bytes,
+                description: format!("Crate {stem} — no Cargo.lock under the scan roots uses it"),
+                last_modified: usage.last_used,
+                native: None,
+                extra_paths,
+                projects: Vec::new(),
+            })
+        })
+        .collect()
+}
+
+/// Rustup toolchains that aren't the default, a directory override, or
+/// pinned by a `rust-toolchain.toml` in a scanned project.
+pub struct RustupToolchains;
+
+impl Recognizer for RustupToolchains {
+    fn name(&self) -> &'static str {
+        "Rustup toolchains"
+    }
+
+    fn id(&self) -> &'static str {
+        "rustup-toolchains"
+    }
+
+    fn category(&self) -> Category {
+        Category::Rust
+    }
+
+    fn scan(&self, _ctx: &ScanContext) -> Result<Vec<Finding>> {
+        Ok(vec![])
+    }
+
+    fn project_detector(&self) -> Option<&dyn ProjectDetector> {
+        Some(self)
+    }
+}
+
+impl ProjectDetector for RustupToolchains {
+    fn markers(&self) -> &[&'static str] {
+        &["rust-toolchain.toml", "rust-toolchain"]
+    }
+
+    fn artifact_dirs(&self) -> &[&'static str] {
+        &["target"]
+    }
+
+    fn artifact_check(&self) -> ArtifactCheck {
+        is_cargo_target
+    }
+
+    fn guards_active_projects(&self) -> bool {
+        false
+    }
+
+    fn detect(&self, ctx: &ScanContext, projects: &[PathBuf]) -> Vec<Finding> {
+        let rustup = rustup_home(&ctx.locations);
+        let mut kept = settings_toolchains(&rustup);
+        kept.extend(
+            projects
+                .iter()
+                .filter_map(|project| pinned_toolchain(project)),
+        );
+
+        let Ok(entries) = std::fs::read_dir(rustup.join("toolchains")) else {
+            return vec![];
+        };
+        let mut toolchains: Vec<(PathBuf, String)> = entries
+            .filter_map(|e| e.ok())
+            // Toolchains added with `rustup toolchain link` are symlinks
+            .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+            .filter_map(|e| Some((e.path(), e.file_name().into_string().ok()?)))
+            .filter(|(_, name)| !kept.iter().any(|channel| selects(channel, name)))
+            .collect();
+        toolchains.sort();
+
+        toolchains
+            .into_iter()
+            .filter_map(|(path, name)| {
+                let usage = dir_usage(&path);
+                if usage.size_bytes == 0 {
+                    return None;
+                }
+                Some(Finding {
+                    path,
+                    category: Category::Rust,
+                    recognizer_id: self.id(),
+                    risk: RiskLevel::Moderate,
+                    size_bytes: usage.size_bytes,
+                    apparent_bytes: usage.apparent_bytes,
+                    description: format!(
+                        "Rust toolchain {name} — not the default or pinned by a scanned project"
+                    ),
+                    last_modified: usage.last_used,
+                    native: Some(NativeAction::new(format!(
+                        "rustup toolchain uninstall {name}"
+                    ))),
+                    extra_paths: Vec::new(),
+                    projects: Vec::new(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// `$RUSTUP_HOME`, else `~/.rustup`.
+fn rustup_home(loc: &Locations) -> PathBuf {
+    loc.var_path("RUSTUP_HOME")
+        .unwrap_or_else(|| loc.home().join(".rustup"))
+}
+
+/// The default toolchain and directory overrides from rustup's
+/// `settings.toml`.
+fn settings_toolchains(rustup: &Path) -> Vec<String> {
+    let Some(settings) = std::fs::read_to_string(rustup.join("settings.toml"))
+        .ok()
+        .and_then(|text| text.parse::<toml::Table>().ok())
+    else {
+        return vec![];
+    };
+    let default = settings
+        .get("default_toolchain")
+        .and_then(|v| v.as_str())
+        .map(String::from);
+    let overrides = settings
+        .get("overrides")
+        .and_then(|v| v.as_table())
+        .into_iter()
+        .flat_map(|table| table.values())
+        .filter_map(|v| v.as_str().map(String::from));
+    default.into_iter().chain(overrides).collect()
+}
+
+/// The channel `project` pins in `rust-toolchain.toml`, or in the older
+/// `rust-toolchain`, which may also hold just the channel name.
+fn pinned_toolchain(project: &Path) -> Option<String> {
+    ["rust-toolchain.toml", "rust-toolchain"]
+        .iter()
+        .find_map(|name| std::fs::read_to_string(project.join(name)).ok())
+        .and_then(|text| match text.parse::<toml::Table>() {
+            Ok(table) => table
+                .get("toolchain")?
+                .get("channel")?
+                .as_str()
+                .map(String::from),
+            Err(_) => text.lines().next().map(|line| line.trim().to_string()),
+        })
+        .filter(|channel| !channel.is_empty())
+}
+
+/// Whether `channel` (e.g. `stable` or `1.75`) resolves to the installed
+/// toolchain `name` (e.g. `stable-x86_64-unknown-linux-gnu`).
+fn selects(channel: &str, name: &str) -> bool {
+    name.strip_prefix(channel)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('-'))
 }
@@ -1,8 +1,10 @@
 use crate::error::Result;
 use crate::finding::{Category, Finding, RiskLevel};
-use crate::recognizer::Recognizer;
-use crate::size::dir_size;
+use crate::paths::{Locations, Platform};
+use crate::recognizer::{Recognizer, ScanContext};
+use crate::size::dir_usage;
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 /// VS Code extensions — detects duplicate/old versions.
 pub struct VSCodeExtensions;
@@ -20,67 +22,99 @@ impl Recognizer for VSCodeExtensions {
         Category::VSCode
     }
 
-    fn scan(&self) -> Result<Vec<Finding>> {
-        let Some(home) = dirs::home_dir() else {
-            return Ok(vec![]);
-        };
-        let extensions_dir = home.join(".vscode/extensions");
-        if !extensions_dir.exists() {
-            return Ok(vec![]);
+    fn scan(&self, ctx: &ScanContext) -> Result<Vec<Finding>> {
+        let keep = ctx
+            .config
+            .recognizer_settings(self.id())
+            .keep_latest
+            .unwrap_or(1);
+        let mut findings = Vec::new();
+        for extensions_dir in extension_dirs(&ctx.locations) {
+            if extensions_dir.exists() {
+                scan_extensions_dir(self.id(), &extensions_dir, keep, &mut findings)?;
+            }
         }
+        Ok(findings)
+    }
+}
 
-        // Group extensions by name (without version) to detect duplicates
-        let mut extensions: HashMap<String, Vec<std::path::PathBuf>> = HashMap::new();
+/// `$VSCODE_EXTENSIONS`, else `~/.vscode/extensions`.
+///
+/// On Linux, extensions installed by VS Code Remote live under `~/.vscode-server`.
+fn extension_dirs(loc: &Locations) -> Vec<PathBuf> {
+    if let Some(dir) = loc.var_path("VSCODE_EXTENSIONS") {
+        return vec![dir];
+    }
 
-        let entries = std::fs::read_dir(&extensions_dir)
-            .map_err(|e| crate::error::Error::io(&extensions_dir, e))?;
+    let mut dirs = vec![loc.home().join(".vscode/extensions")];
+    if loc.platform() == Platform::Linux {
+        dirs.push(loc.home().join(".vscode-server/extensions"));
+    }
+    dirs
+}
 
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if !path.is_dir() {
-                continue;
-            }
-            let name = path
-                .file_name()
-                .unwrap_or_default()
-                .to_string_lossy()
-                .to_string();
-
-            // Extension dirs are like "publisher.name-1.2.3"
-            // Strip the version suffix to group by extension identity
-            if let Some(base) = strip_version_suffix(&name) {
-                extensions.entry(base).or_default().push(path);
-            }
+/// Report all but the `keep` newest versions of each extension in
+/// `extensions_dir`.
+fn scan_extensions_dir(
+    recognizer_id: &'static str,
+    extensions_dir: &Path,
+    keep: usize,
+    findings: &mut Vec<Finding>,
+) -> Result<()> {
+    // Group extensions by name (without version) to detect duplicates
+    let mut extensions: HashMap<String, Vec<PathBuf>> = HashMap::new();
+
+    let entries = std::fs::read_dir(extensions_dir)
+        .map_err(|e| crate::error::Error::io(extensions_dir, e))?;
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !path.is_dir() {
+            continue;
         }
+        let name = path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
 
-        let mut findings = Vec::new();
+        // Extension dirs are like "publisher.name-1.2.3"
+        // Strip the version suffix to group by extension identity
+        if let Some(base) = strip_version_suffix(&name) {
+            extensions.entry(base).or_default().push(path);
+        }
+    }
 
-        for (ext_name, mut versions) in extensions {
-            if versions.len() <= 1 {
-                continue;
-            }
+    for (ext_name, mut versions) in extensions {
+        if versions.len() <= keep {
+            continue;
+        }
+
+        // Sort by name (version order) and mark all but the last `keep` as old
+        versions.sort();
+        let old_versions = &versions[..versions.len() - keep];
 
-            // Sort by name (version order) and mark all but the last as old
-            versions.sort();
-            let old_versions = &versions[..versions.len() - 1];
-
-            for old in old_versions {
-                let size = dir_size(old);
-                if size > 0 {
-                    findings.push(Finding {
-                        path: old.clone(),
-                        category: Category::VSCode,
-                        risk: RiskLevel::Moderate,
-                        size_bytes: size,
-                        description: format!("Old version of VS Code extension {ext_name}"),
-                        last_modified: None,
-                    });
-                }
+        for old in old_versions {
+            let usage = dir_usage(old);
+            if usage.size_bytes > 0 {
+                findings.push(Finding {
+                    path: old.clone(),
+                    category: Category::VSCode,
+                    recognizer_id,
+                    risk: RiskLevel::Moderate,
+                    size_bytes: usage.size_bytes,
+                    apparent_bytes: usage.apparent_bytes,
+                    description: format!("Old version of VS Code extension {ext_name}"),
+                    last_modified: usage.last_used,
+                    native: None,
+                    extra_paths: Vec::new(),
+                    projects: Vec::new(),
+                });
             }
         }
-
-        Ok(findings)
     }
+
+    Ok(())
 }
 
 /// Strip the version suffix from a VS Code extension directory name.
@@ -1,11 +1,13 @@
 use crate::error::Result;
 use crate::finding::{Category, Finding, RiskLevel};
-use crate::recognizer::Recognizer;
-use crate::size::dir_size;
+use crate::paths::{Locations, Platform};
+use crate::recognizer::{Recognizer, ScanContext};
+use crate::size::dir_usage;
 use std::path::PathBuf;
 
-fn home() -> Option<PathBuf> {
-    dirs::home_dir()
+/// `~/Library/Developer` — Xcode only exists on macOS.
+fn developer_dir(loc: &Locations) -> Option<PathBuf> {
+    (loc.platform() == Platform::MacOs).then(|| loc.home().join("Library/Developer"))
 }
 
 /// Xcode DerivedData — build artifacts that regenerate on next build.
@@ -24,27 +26,32 @@ impl Recognizer for DerivedData {
         Category::Xcode
     }
 
-    fn scan(&self) -> Result<Vec<Finding>> {
-        let Some(home) = home() else {
+    fn scan(&self, ctx: &ScanContext) -> Result<Vec<Finding>> {
+        let Some(developer) = developer_dir(&ctx.locations) else {
             return Ok(vec![]);
         };
-        let path = home.join("Library/Developer/Xcode/DerivedData");
+        let path = developer.join("Xcode/DerivedData");
         if !path.exists() {
             return Ok(vec![]);
         }
 
-        let size = dir_size(&path);
-        if size == 0 {
+        let usage = dir_usage(&path);
+        if usage.size_bytes == 0 {
             return Ok(vec![]);
         }
 
         Ok(vec![Finding {
             path,
             category: Category::Xcode,
+            recognizer_id: self.id(),
             risk: RiskLevel::Safe,
-            size_bytes: size,
+            size_bytes: usage.size_bytes,
+            apparent_bytes: usage.apparent_bytes,
             description: "Xcode build artifacts — regenerated on next build".into(),
-            last_modified: None,
+            last_modified: usage.last_used,
+            native: None,
+            extra_paths: Vec::new(),
+            projects: Vec::new(),
         }])
     }
 }
@@ -65,28 +72,33 @@ impl Recognizer for DeviceSupport {
         Category::Xcode
     }
 
-    fn scan(&self) -> Result<Vec<Finding>> {
-        let Some(home) = home() else {
+    fn scan(&self, ctx: &ScanContext) -> Result<Vec<Finding>> {
+        let Some(developer) = developer_dir(&ctx.locations) else {
             return Ok(vec![]);
         };
-        let path = home.join("Library/Developer/Xcode/iOS DeviceSupport");
+        let path = developer.join("Xcode/iOS DeviceSupport");
         if !path.exists() {
             return Ok(vec![]);
         }
 
-        let size = dir_size(&path);
-        if size == 0 {
+        let usage = dir_usage(&path);
+        if usage.size_bytes == 0 {
             return Ok(vec![]);
         }
 
         Ok(vec![Finding {
             path,
             category: Category::Xcode,
+            recognizer_id: self.id(),
             risk: RiskLevel::Moderate,
-            size_bytes: size,
+            size_bytes: usage.size_bytes,
+            apparent_bytes: usage.apparent_bytes,
             description: "Debug symbols for connected iOS devices — re-downloaded when needed"
                 .into(),
-            last_modified: None,
+            last_modified: usage.last_used,
+            native: None,
+            extra_paths: Vec::new(),
+            projects: Vec::new(),
         }])
     }
 }
@@ -107,28 +119,33 @@ impl Recognizer for Simulators {
         Category::Xcode
     }
 
-    fn scan(&self) -> Result<Vec<Finding>> {
-        let Some(home) = home() else {
+    fn scan(&self, ctx: &ScanContext) -> Result<Vec<Finding>> {
+        let Some(developer) = developer_dir(&ctx.locations) else {
             return Ok(vec![]);
         };
-        let path = home.join("Library/Developer/CoreSimulator/Devices");
+        let path = developer.join("CoreSimulator/Devices");
         if !path.exists() {
             return Ok(vec![]);
         }
 
-        let size = dir_size(&path);
-        if size == 0 {
+        let usage = dir_usage(&path);
+        if usage.size_bytes == 0 {
             return Ok(vec![]);
         }
 
         Ok(vec![Finding {
             path,
             category: Category::Xcode,
+            recognizer_id: self.id(),
             risk: RiskLevel::Risky,
-            size_bytes: size,
+            size_bytes: usage.size_bytes,
+            apparent_bytes: usage.apparent_bytes,
             description: "iOS Simulator device data — deleting removes all simulator content"
                 .into(),
-            last_modified: None,
+            last_modified: usage.last_used,
+            native: None,
+            extra_paths: Vec::new(),
+            projects: Vec::new(),
         }])
     }
 }
@@ -149,27 +166,32 @@ impl Recognizer for Archives {
         Category::Xcode
     }
 
-    fn scan(&self) -> Result<Vec<Finding>> {
-        let Some(home) = home() else {
+    fn scan(&self, ctx: &ScanContext) -> Result<Vec<Finding>> {
+        let Some(developer) = developer_dir(&ctx.locations) else {
             return Ok(vec![]);
         };
-        let path = home.join("Library/Developer/Xcode/Archives");
+        let path = developer.join("Xcode/Archives");
         if !path.exists() {
             return Ok(vec![]);
         }
 
-        let size = dir_size(&path);
-        if size == 0 {
+        let usage = dir_usage(&path);
+        if usage.size_bytes == 0 {
             return Ok(vec![]);
         }
 
         Ok(vec![Finding {
             path,
             category: Category::Xcode,
+            recognizer_id: self.id(),
             risk: RiskLevel::Moderate,
-            size_bytes: size,
+            size_bytes: usage.size_bytes,
+            apparent_bytes: usage.apparent_bytes,
             description: "Xcode build archives — old app exports that can be re-archived".into(),
-            last_modified: None,
+            last_modified: usage.last_used,
+            native: None,
+            extra_paths: Vec::new(),
+            projects: Vec::new(),
         }])
     }
 }
@@ -190,27 +212,32 @@ impl Recognizer for Previews {
         Category::Xcode
     }
 
-    fn scan(&self) -> Result<Vec<Finding>> {
-        let Some(home) = home() else {
+    fn scan(&self, ctx: &ScanContext) -> Result<Vec<Finding>> {
+        let Some(developer) = developer_dir(&ctx.locations) else {
             return Ok(vec![]);
         };
-        let path = home.join("Library/Developer/Xcode/UserData/Previews");
+        let path = developer.join("Xcode/UserData/Previews");
         if !path.exists() {
             return Ok(vec![]);
         }
 
-        let size = dir_size(&path);
-        if size == 0 {
+        let usage = dir_usage(&path);
+        if usage.size_bytes == 0 {
             return Ok(vec![]);
         }
 
         Ok(vec![Finding {
             path,
             category: Category::Xcode,
+            recognizer_id: self.id(),
             risk: RiskLevel::Safe,
-            size_bytes: size,
+            size_bytes: usage.size_bytes,
+            apparent_bytes: usage.apparent_bytes,
             description: "SwiftUI preview cache — regenerated automatically".into(),
-            last_modified: None,
+            last_modified: usage.last_used,
+            native: None,
+            extra_paths: Vec::new(),
+            projects: Vec::new(),
         }])
     }
 }
@@ -1,16 +1,40 @@
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
 use std::time::{Duration, Instant, SystemTime};
 
-use crate::config::Config;
-use crate::error::Result;
-use crate::finding::{Category, Finding, RiskLevel};
-use crate::recognizer::Recognizer;
+use crate::activity::ActiveProjects;
+use crate::cache::ScanCache;
+use crate::config::{ActiveProtection, Config};
+use crate::error::{Error, ErrorKind, ErrorRecord, Result};
+use crate::finding::{self, Category, Finding, NativeAction, RiskLevel};
+use crate::mounts::{self, MountUsage};
+use crate::project::{self, ProjectDetector};
+use crate::recognizer::{Recognizer, ScanContext};
+use crate::size::SharedLinks;
+use crate::walk::{self, IoBudget};
 
 /// Results from a scan operation.
 pub struct ScanResult {
     pub findings: Vec<Finding>,
     pub total_reclaimable: u64,
+    /// The file systems the findings live on, largest reclaimable first.
+    pub mounts: Vec<MountUsage>,
     pub scan_duration: Duration,
-    pub errors: Vec<String>,
+    pub errors: Vec<ErrorRecord>,
+    /// Findings left out because their project is active (with
+    /// `protect_active = "exclude"`).
+    pub excluded: Vec<Excluded>,
+}
+
+/// A finding a scan left out, and why.
+#[derive(Debug, Clone, Serialize)]
+pub struct Excluded {
+    pub finding: Finding,
+    pub reason: String,
 }
 
 /// How to sort findings.
@@ -22,13 +46,57 @@ pub enum SortOrder {
     Category,
 }
 
+/// Progress reported while a scan runs.
+#[derive(Debug, Clone)]
+pub enum ScanEvent {
+    /// The scan is about to run this many recognizers.
+    Started { recognizers: usize },
+    /// A recognizer began scanning.
+    RecognizerStarted {
+        id: &'static str,
+        name: &'static str,
+    },
+    /// A finding passed the scan filters. `total_bytes` counts every finding so far.
+    FindingDiscovered { finding: Finding, total_bytes: u64 },
+    /// A recognizer finished. `findings` and `bytes` cover what passed the filters.
+    RecognizerFinished {
+        id: &'static str,
+        name: &'static str,
+        findings: usize,
+        bytes: u64,
+    },
+    /// A recognizer failed. The error is also reported in [`ScanResult::errors`].
+    RecognizerFailed {
+        id: &'static str,
+        name: &'static str,
+        error: String,
+    },
+}
+
+/// Receives [`ScanEvent`]s, possibly from several scanning threads at once.
+pub type EventHandler = Arc<dyn Fn(ScanEvent) + Send + Sync>;
+
 /// Options for controlling scan behavior.
 pub struct ScanOptions {
     pub max_risk: RiskLevel,
     pub min_size: u64,
     pub category: Option<Category>,
     pub older_than: Option<Duration>,
+    /// Only keep findings whose path or description contains one of these as
+    /// a whole name, ignoring case. Empty keeps everything.
+    pub matching: Vec<String>,
     pub sort: SortOrder,
+    /// Run recognizers one at a time, each walk using the global rayon pool.
+    pub sequential: bool,
+    /// Threads shared by all filesystem walks when recognizers run concurrently.
+    /// Bounds how many directories are open at once.
+    pub io_threads: usize,
+    /// Called as recognizers start and finish, e.g. to drive a progress display.
+    pub on_event: Option<EventHandler>,
+    /// Reuse sizes recorded by earlier scans for directories that haven't
+    /// changed, and record the sizes measured by this one. With `older_than`
+    /// set every directory is re-read, since recorded last uses can be stale.
+    pub cache: Option<ScanCache>,
 }
 
 impl Default for ScanOptions {
@@ -38,18 +106,46 @@ impl Default for ScanOptions {
             min_size: 0,
             category: None,
             older_than: None,
+            matching: Vec::new(),
             sort: SortOrder::Size,
+            sequential: false,
+            io_threads: walk::default_io_threads(),
+            on_event: None,
+            cache: None,
         }
     }
 }
 
-/// Run all enabled recognizers in parallel and collect findings.
+/// Run all enabled recognizers against the current user's environment.
 pub fn scan(
     recognizers: &[Box<dyn Recognizer>],
     config: &Config,
     options: &ScanOptions,
+) -> ScanResult {
+    match ScanContext::detect(config.clone()) {
+        Some(ctx) => scan_with_context(recognizers, &ctx, options),
+        None => ScanResult {
+            findings: Vec::new(),
+            total_reclaimable: 0,
+            mounts: Vec::new(),
+            scan_duration: Duration::ZERO,
+            errors: vec![ErrorRecord::new(
+                ErrorKind::NotFound,
+                "Cannot determine home directory",
+            )],
+            excluded: Vec::new(),
+        },
+    }
+}
+
+/// Run all enabled recognizers against `ctx` and collect findings.
+pub fn scan_with_context(
+    recognizers: &[Box<dyn Recognizer>],
+    ctx: &ScanContext,
+    options: &ScanOptions,
 ) -> ScanResult {
     let start = Instant::now();
+    let config = &ctx.config;
 
     // Filter to enabled recognizers, optionally by category
     let enabled: Vec<&Box<dyn Recognizer>> = recognizers
@@ -58,64 +154,327 @@ pub fn scan(
         .filter(|r| options.category.is_none() || Some(r.category()) == options.category)
         .collect();
 
-    // Run recognizers sequentially to avoid file descriptor exhaustion.
-    // Each recognizer uses jwalk (rayon-based) internally for dir_size,
-    // and running them all in parallel can exceed the OS open-file limit.
-    let results: Vec<Result<Vec<Finding>>> = enabled
+    // Walk the project roots once on behalf of every project-based recognizer.
+    // The result is aligned with the order detectors appear in `enabled`.
+    let searches: Vec<(&dyn ProjectDetector, Vec<PathBuf>)> = enabled
+        .iter()
+        .filter_map(|r| Some((r.project_detector()?, ctx.project_roots_for(r.id()))))
+        .collect();
+
+    // Concurrent walks each spread over the rayon pool and can exceed the OS
+    // open-file limit, so when recognizers run in parallel every walk shares
+    // one bounded pool of I/O threads instead.
+    let budget = if options.sequential {
+        None
+    } else {
+        IoBudget::new(options.io_threads)
+            .inspect_err(|e| log::warn!("{e}; scanning sequentially"))
+            .ok()
+    };
+
+    let same_fs = config.scan.same_file_system;
+    let find_projects =
+        || walk::same_file_system(same_fs, || project::find_projects_in(ctx, &searches));
+    let mut projects = match &budget {
+        Some(budget) => budget.enter(find_projects),
+        None => find_projects(),
+    }
+    .into_iter();
+
+    // Pair each recognizer with its share of the project walk up front.
+    let jobs: Vec<(&dyn Recognizer, Vec<PathBuf>)> = enabled
         .iter()
         .map(|recognizer| {
-            log::debug!("Running recognizer: {}", recognizer.name());
-            recognizer.scan()
+            let dirs = match recognizer.project_detector() {
+                Some(_) => projects.next().unwrap_or_default(),
+                None => Vec::new(),
+            };
+            (recognizer.as_ref(), dirs)
         })
         .collect();
 
+    let progress = Progress {
+        on_event: options.on_event.as_ref(),
+        total_bytes: AtomicU64::new(0),
+    };
+    progress.emit(ScanEvent::Started {
+        recognizers: jobs.len(),
+    });
+
+    // Judge project activity once per project, across every recognizer
+    let protection = config.active_protection().map(|protection| {
+        let artifact_dirs = searches.iter().flat_map(|(d, _)| d.artifact_dirs());
+        let active = ActiveProjects::new(protection.within, artifact_dirs.copied());
+        (protection, active)
+    });
+    let excluded = Mutex::new(Vec::new());
+    let links = SharedLinks::default();
+
+    let cache = options
+        .cache
+        .as_ref()
+        .map(|cache| match options.older_than {
+            Some(_) => cache.refreshing(),
+            None => cache.clone(),
+        });
+
+    let now = SystemTime::now();
+    let run = |recognizer: &dyn Recognizer, dirs: &[PathBuf]| {
+        progress.emit(ScanEvent::RecognizerStarted {
+            id: recognizer.id(),
+            name: recognizer.name(),
+        });
+        // A bug in one recognizer shouldn't take the whole scan down with it
+        let attempt = || {
+            links.enter(|| {
+                walk::same_file_system(same_fs, || {
+                    panic::catch_unwind(AssertUnwindSafe(|| run_recognizer(recognizer, ctx, dirs)))
+                })
+            })
+        };
+        let result = match &cache {
+            Some(cache) => cache.enter(attempt),
+            None => attempt(),
+        }
+        .unwrap_or_else(|_| {
+            Err(Error::Scanner(format!(
+                "Recognizer '{}' panicked",
+                recognizer.id()
+            )))
+        })
+        .map(|mut findings| {
+            let settings = config.recognizer_settings(recognizer.id());
+            if let Some(risk) = settings.risk {
+                findings.iter_mut().for_each(|f| f.risk = risk);
+            }
+            if let Some(command) = &settings.native {
+                let native = NativeAction::new(command.as_str());
+                findings
+                    .iter_mut()
+                    .for_each(|f| f.native = Some(native.clone()));
+            }
+            let min_size = options.min_size.max(settings.min_size.unwrap_or(0));
+            let passes = |f: &Finding| passes_filters(f, options, min_size, ctx, now);
+            if let Some((protection, active)) = &protection {
+                let guarded = recognizer
+                    .project_detector()
+                    .is_some_and(|d| d.guards_active_projects());
+                if guarded {
+                    findings =
+                        protect_active(findings, dirs, *protection, active, |excluded_one| {
+                            if passes(&excluded_one.finding) {
+                                let mut excluded =
+                                    excluded.lock().unwrap_or_else(PoisonError::into_inner);
+                                excluded.push(excluded_one);
+                            }
+                        });
+                }
+            }
+            findings.retain(passes);
+            findings
+        });
+        progress.finished(recognizer, &result);
+        result
+    };
+
+    let results: Vec<Result<Vec<Finding>>> = match &budget {
+        None => jobs
+            .iter()
+            .map(|(recognizer, dirs)| run(*recognizer, dirs))
+            .collect(),
+        Some(budget) => std::thread::scope(|scope| {
+            let handles: Vec<_> = jobs
+                .iter()
+                .map(|(recognizer, dirs)| {
+                    let run = &run;
+                    scope.spawn(move || budget.enter(|| run(*recognizer, dirs)))
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| {
+                    handle
+                        .join()
+                        .unwrap_or_else(|_| Err(Error::Scanner("Scan thread panicked".into())))
+                })
+                .collect()
+        }),
+    };
+
     let mut findings = Vec::new();
     let mut errors = Vec::new();
 
-    for result in results {
+    // Results come back in job order
+    for ((recognizer, _), result) in jobs.iter().zip(results) {
         match result {
             Ok(mut f) => findings.append(&mut f),
-            Err(e) => errors.push(e.to_string()),
+            Err(e) => errors.push(ErrorRecord::from_error(&e).recognizer(recognizer.id())),
         }
     }
 
-    // Filter by config
-    let now = SystemTime::now();
-    findings.retain(|f| {
-        if f.risk > options.max_risk || f.size_bytes < options.min_size {
-            return false;
+    // Sort
+    match options.sort {
+        SortOrder::Size => findings.sort_by_key(|f| Reverse(f.size_bytes)),
+        SortOrder::Risk => findings.sort_by_key(|f| Reverse(f.risk)),
+        SortOrder::Category => findings.sort_by_key(|f| f.category.to_string()),
+    }
+
+    // A finding inside another frees nothing more once the outer one goes,
+    // and files hard-linked from several findings go once all of them do
+    let outermost = finding::outermost(&findings);
+    let total_reclaimable = outermost.iter().map(|f| f.size_bytes).sum::<u64>()
+        + links.freed_together(outermost.iter().flat_map(|f| f.paths()));
+    let mounts = mounts::group_by_mount(outermost);
+
+    ScanResult {
+        findings,
+        total_reclaimable,
+        mounts,
+        scan_duration: start.elapsed(),
+        errors,
+        excluded: excluded
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner),
+    }
+}
+
+/// Raise the risk of findings inside active projects, or hand them to
+/// `exclude`. Each finding belongs to the innermost of `projects` holding it.
+fn protect_active(
+    findings: Vec<Finding>,
+    projects: &[PathBuf],
+    protection: ActiveProtection,
+    active: &ActiveProjects,
+    mut exclude: impl FnMut(Excluded),
+) -> Vec<Finding> {
+    let mut kept = Vec::with_capacity(findings.len());
+    for mut finding in findings {
+        let reason = projects
+            .iter()
+            .filter(|project| finding.path.starts_with(project))
+            .max_by_key(|project| project.components().count())
+            .into_iter()
+            .chain(&finding.projects)
+            .find_map(|project| active.reason(project));
+        match reason {
+            Some(reason) if protection.exclude => exclude(Excluded { finding, reason }),
+            Some(reason) => {
+                finding.risk = finding.risk.raised();
+                finding.description = format!("{} (active: {reason})", finding.description);
+                kept.push(finding);
+            }
+            None => kept.push(finding),
         }
-        if config.is_path_ignored(&f.path) {
+    }
+    kept
+}
+
+/// Whether a finding passes the risk, size, ignore, text, and age filters.
+/// `min_size` is the larger of the scan's and the recognizer's own.
+fn passes_filters(
+    f: &Finding,
+    options: &ScanOptions,
+    min_size: u64,
+    ctx: &ScanContext,
+    now: SystemTime,
+) -> bool {
+    if f.risk > options.max_risk || f.size_bytes < min_size {
+        return false;
+    }
+    if ctx.is_ignored(&f.path) {
+        return false;
+    }
+    if !options.matching.is_empty() {
+        let path = f.path.to_string_lossy().to_lowercase();
+        let description = f.description.to_lowercase();
+        let matches = options.matching.iter().any(|text| {
+            let text = text.to_lowercase();
+            contains_name(&path, &text) || contains_name(&description, &text)
+        });
+        if !matches {
             return false;
         }
-        if let Some(max_age) = options.older_than {
-            if let Some(modified) = f.last_modified {
-                if let Ok(age) = now.duration_since(modified) {
-                    if age < max_age {
-                        return false;
-                    }
+    }
+    if let Some(max_age) = options.older_than {
+        if let Some(modified) = f.last_modified {
+            if let Ok(age) = now.duration_since(modified) {
+                if age < max_age {
+                    return false;
                 }
             }
-            // If no last_modified, include it (we can't determine age)
         }
-        true
-    });
+        // If no last_modified, include it (we can't determine age)
+    }
+    true
+}
 
-    // Sort
-    match options.sort {
-        SortOrder::Size => findings.sort_by(|a, b| b.size_bytes.cmp(&a.size_bytes)),
-        SortOrder::Risk => findings.sort_by(|a, b| b.risk.cmp(&a.risk)),
-        SortOrder::Category => {
-            findings.sort_by(|a, b| a.category.to_string().cmp(&b.category.to_string()))
+/// Whether `name` appears in `text` as a whole, not as part of a longer name:
+/// `llama3:8b` is in `model llama3:8b —` but not in `llama3:8b-instruct`.
+fn contains_name(text: &str, name: &str) -> bool {
+    let is_name_char = |c: char| c.is_alphanumeric() || "-_.:+@".contains(c);
+    text.match_indices(name).any(|(start, _)| {
+        let before = text[..start].chars().next_back();
+        let after = text[start + name.len()..].chars().next();
+        !before.is_some_and(is_name_char) && !after.is_some_and(is_name_char)
+    })
+}
+
+/// Reports scan events to the caller's handler, if any.
+struct Progress<'a> {
+    on_event: Option<&'a EventHandler>,
+    total_bytes: AtomicU64,
+}
+
+impl Progress<'_> {
+    fn emit(&self, event: ScanEvent) {
+        if let Some(on_event) = self.on_event {
+            on_event(event);
         }
     }
 
-    let total_reclaimable = findings.iter().map(|f| f.size_bytes).sum();
+    /// Report a recognizer's kept findings, then its completion or failure.
+    fn finished(&self, recognizer: &dyn Recognizer, result: &Result<Vec<Finding>>) {
+        if self.on_event.is_none() {
+            return;
+        }
+        match result {
+            Ok(findings) => {
+                for finding in findings {
+                    let total_bytes = self
+                        .total_bytes
+                        .fetch_add(finding.size_bytes, Ordering::Relaxed)
+                        + finding.size_bytes;
+                    self.emit(ScanEvent::FindingDiscovered {
+                        finding: finding.clone(),
+                        total_bytes,
+                    });
+                }
+                self.emit(ScanEvent::RecognizerFinished {
+                    id: recognizer.id(),
+                    name: recognizer.name(),
+                    findings: findings.len(),
+                    bytes: findings.iter().map(|f| f.size_bytes).sum(),
+                });
+            }
+            Err(e) => self.emit(ScanEvent::RecognizerFailed {
+                id: recognizer.id(),
+                name: recognizer.name(),
+                error: e.to_string(),
+            }),
+        }
+    }
+}
 
-    ScanResult {
-        findings,
-        total_reclaimable,
-        scan_duration: start.elapsed(),
-        errors,
+/// Run one recognizer's fixed-path scan and its project detector, if any.
+fn run_recognizer(
+    recognizer: &dyn Recognizer,
+    ctx: &ScanContext,
+    projects: &[PathBuf],
+) -> Result<Vec<Finding>> {
+    log::debug!("Running recognizer: {}", recognizer.name());
+    let mut findings = recognizer.scan(ctx)?;
+    if let Some(detector) = recognizer.project_detector() {
+        findings.extend(detector.detect(ctx, projects));
     }
+    Ok(findings)
 }
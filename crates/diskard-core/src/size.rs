@@ -1,28 +1,318 @@
-use std::path::Path;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::fs::Metadata;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::SystemTime;
+
+use crate::cache;
+use crate::walk::walk_dir;
+
+thread_local! {
+    static SHARED: RefCell<Option<SharedLinks>> = const { RefCell::new(None) };
+}
 
 /// Format bytes into a human-readable string using binary units (e.g., "1.0 GiB").
 pub fn format_bytes(bytes: u64) -> String {
     bytesize::ByteSize(bytes).to_string_as(true)
 }
 
-/// Calculate the total size of a directory by walking all files.
+/// Parse a size such as "50MB", "1.5GB", or a plain number of bytes. Units
+/// are binary (1 MB = 1024 KB) and case-insensitive.
+pub fn parse_size(s: &str) -> Result<u64, String> {
+    let s = s.trim().to_uppercase();
+
+    if let Ok(n) = s.parse::<u64>() {
+        return Ok(n);
+    }
+
+    let (num_str, multiplier) = if let Some(n) = s.strip_suffix("GB") {
+        (n.trim(), 1_073_741_824u64)
+    } else if let Some(n) = s.strip_suffix("MB") {
+        (n.trim(), 1_048_576u64)
+    } else if let Some(n) = s.strip_suffix("KB") {
+        (n.trim(), 1_024u64)
+    } else if let Some(n) = s.strip_suffix('B') {
+        (n.trim(), 1u64)
+    } else {
+        return Err(format!("Invalid size format: {s}. Use e.g. 10MB, 1GB"));
+    };
+
+    let num: f64 = num_str
+        .parse()
+        .map_err(|_| format!("Invalid size number: {num_str}"))?;
+
+    Ok((num * multiplier as f64) as u64)
+}
+
+/// Total size and most recent use of everything under a path.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DirUsage {
+    /// Bytes the files occupy on disk, counting each hard-linked file once
+    /// and leaving out files also linked from outside the tree. This is what
+    /// deleting the tree frees.
+    pub size_bytes: u64,
+    /// Sum of file lengths, counting hard-linked files the same way. Exceeds
+    /// `size_bytes` for sparse files such as VM disk images.
+    pub apparent_bytes: u64,
+    /// Newest file modification or access time, or directory modification
+    /// time, anywhere in the tree. `None` if no timestamps could be read.
+    pub last_used: Option<SystemTime>,
+}
+
+impl DirUsage {
+    /// Usage of a single file.
+    pub fn of_file(metadata: &Metadata) -> Self {
+        Self {
+            size_bytes: allocated_bytes(metadata),
+            apparent_bytes: metadata.len(),
+            last_used: last_used(metadata),
+        }
+    }
+
+    /// Usage of two trees with no files in common.
+    pub fn combine(self, other: Self) -> Self {
+        Self {
+            size_bytes: self.size_bytes + other.size_bytes,
+            apparent_bytes: self.apparent_bytes + other.apparent_bytes,
+            last_used: self.last_used.max(other.last_used),
+        }
+    }
+}
+
+/// Adds up file usage, counting a file reachable through several hard links
+/// in the tree only once, and only if every link to it is in the tree.
+#[derive(Debug, Default)]
+pub(crate) struct Tally {
+    usage: DirUsage,
+    /// Files with more than one link, by (device, inode).
+    linked: HashMap<(u64, u64), Linked>,
+}
+
+/// A file with more than one hard link, as seen from inside a tree.
+#[derive(Debug, Clone, Copy)]
+struct Linked {
+    usage: DirUsage,
+    /// Links the file has in total.
+    links: u64,
+    /// Links to it found in the tree.
+    seen: u64,
+}
+
+impl Tally {
+    pub(crate) fn add_file(&mut self, metadata: &Metadata) {
+        let file = DirUsage::of_file(metadata);
+        match hard_link(metadata) {
+            Some((id, links)) => self.add_linked(id, links, file),
+            None => self.usage = self.usage.combine(file),
+        }
+    }
+
+    /// Count one link to a file with `links` links in total.
+    pub(crate) fn add_linked(&mut self, id: (u64, u64), links: u64, file: DirUsage) {
+        self.linked
+            .entry(id)
+            .or_insert(Linked {
+                usage: file,
+                links,
+                seen: 0,
+            })
+            .seen += 1;
+    }
+
+    /// Count a directory, whose modification time is its only sign of use.
+    pub(crate) fn add_dir(&mut self, metadata: &Metadata) {
+        self.usage.last_used = self.usage.last_used.max(metadata.modified().ok());
+    }
+
+    pub(crate) fn add_usage(&mut self, usage: DirUsage) {
+        self.usage = self.usage.combine(usage);
+    }
+
+    pub(crate) fn merge(mut self, other: Self) -> Self {
+        self.usage = self.usage.combine(other.usage);
+        for (id, file) in other.linked {
+            match self.linked.entry(id) {
+                Entry::Occupied(mut entry) => entry.get_mut().seen += file.seen,
+                Entry::Vacant(entry) => {
+                    entry.insert(file);
+                }
+            }
+        }
+        self
+    }
+
+    /// Usage of the tree at `root`. Files also linked from outside it add
+    /// only their last use, since deleting the tree leaves them on disk;
+    /// they are handed to the scan's [`SharedLinks`], if any.
+    pub(crate) fn total(self, root: &Path) -> DirUsage {
+        let mut usage = self.usage;
+        let mut partial = Vec::new();
+        for (id, file) in self.linked {
+            if file.seen >= file.links {
+                usage = usage.combine(file.usage);
+            } else {
+                usage.last_used = usage.last_used.max(file.usage.last_used);
+                partial.push((id, file));
+            }
+        }
+        if let Some(shared) = SHARED.with(|shared| shared.borrow().clone()) {
+            shared.record(root, partial);
+        }
+        usage
+    }
+}
+
+/// Hard-linked files found only partly inside the trees measured during a
+/// scan, by the tree measured.
+///
+/// Deleting one such tree leaves the file on disk, so the tree's size leaves
+/// it out, but deleting every tree linking it frees it.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SharedLinks {
+    trees: Arc<Mutex<HashMap<PathBuf, PartlyLinked>>>,
+}
+
+/// Files of one tree with links outside it, by (device, inode).
+type PartlyLinked = Vec<((u64, u64), Linked)>;
+
+impl SharedLinks {
+    /// Run `f` with every [`dir_usage`] call it makes on this thread
+    /// recording its partly linked files here.
+    pub(crate) fn enter<R>(&self, f: impl FnOnce() -> R) -> R {
+        let previous = SHARED.with(|shared| shared.replace(Some(self.clone())));
+        let result = f();
+        SHARED.with(|shared| *shared.borrow_mut() = previous);
+        result
+    }
+
+    fn record(&self, root: &Path, files: PartlyLinked) {
+        let mut trees = self.trees.lock().unwrap_or_else(PoisonError::into_inner);
+        if files.is_empty() {
+            trees.remove(root);
+        } else {
+            trees.insert(root.to_path_buf(), files);
+        }
+    }
+
+    /// Bytes that deleting all of `paths` together frees beyond their own
+    /// sizes: each file counted once, if every link to it lies under them.
+    pub(crate) fn freed_together<'a>(&self, paths: impl IntoIterator<Item = &'a Path>) -> u64 {
+        let paths: Vec<PathBuf> = paths
+            .into_iter()
+            .map(|p| p.canonicalize().unwrap_or_else(|_| p.to_path_buf()))
+            .collect();
+        let trees = self.trees.lock().unwrap_or_else(PoisonError::into_inner);
+        let inside: Vec<&PathBuf> = trees
+            .keys()
+            .filter(|root| paths.iter().any(|p| root.starts_with(p)))
+            .collect();
+
+        let mut files: HashMap<(u64, u64), Linked> = HashMap::new();
+        // A tree inside another saw the same links again
+        for root in inside.iter().filter(|root| {
+            !root
+                .ancestors()
+                .skip(1)
+                .any(|a| inside.iter().any(|r| *r == a))
+        }) {
+            for (id, file) in &trees[*root] {
+                match files.entry(*id) {
+                    Entry::Occupied(mut entry) => entry.get_mut().seen += file.seen,
+                    Entry::Vacant(entry) => {
+                        entry.insert(*file);
+                    }
+                }
+            }
+        }
+        files
+            .values()
+            .filter(|file| file.seen >= file.links)
+            .map(|file| file.usage.size_bytes)
+            .sum()
+    }
+}
+
+/// Calculate the on-disk size of a directory by walking all files.
 pub fn dir_size(path: &Path) -> u64 {
+    dir_usage(path).size_bytes
+}
+
+/// Calculate the on-disk and apparent size and last use of a directory in a
+/// single walk.
+///
+/// Inside [`ScanCache::enter`](crate::cache::ScanCache::enter) unchanged
+/// subdirectories reuse the sizes recorded by earlier scans.
+pub fn dir_usage(path: &Path) -> DirUsage {
     if !path.exists() {
-        return 0;
+        return DirUsage::default();
     }
 
+    let resolved = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let mut tally = Tally::default();
     if path.is_file() {
-        return path.metadata().map(|m| m.len()).unwrap_or(0);
+        if let Ok(metadata) = path.metadata() {
+            tally.add_file(&metadata);
+        }
+        return tally.total(&resolved);
     }
 
-    let resolved = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
-    jwalk::WalkDir::new(resolved)
+    if let Some(cache) = cache::current() {
+        return cache.dir_usage(&resolved);
+    }
+
+    for entry in walk_dir(&resolved)
         .skip_hidden(false)
         .into_iter()
         .filter_map(|entry| entry.ok())
-        .filter(|entry| entry.file_type().is_file())
-        .map(|entry| entry.metadata().map(|m| m.len()).unwrap_or(0))
-        .sum()
+    {
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        if entry.file_type().is_file() {
+            tally.add_file(&metadata);
+        } else {
+            // Listing a directory bumps its access time, so only its
+            // modification time says anything about use.
+            tally.add_dir(&metadata);
+        }
+    }
+    tally.total(&resolved)
+}
+
+/// Newest of a file's modification and access times.
+pub fn last_used(metadata: &Metadata) -> Option<SystemTime> {
+    metadata.modified().ok().max(metadata.accessed().ok())
+}
+
+/// Bytes allocated to a file on disk: its 512-byte block count on Unix,
+/// its length elsewhere.
+pub fn allocated_bytes(metadata: &Metadata) -> u64 {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        metadata.blocks() * 512
+    }
+    #[cfg(not(unix))]
+    {
+        metadata.len()
+    }
+}
+
+/// (device, inode) and link count of a file with more than one hard link.
+pub(crate) fn hard_link(metadata: &Metadata) -> Option<((u64, u64), u64)> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        (metadata.nlink() > 1).then(|| ((metadata.dev(), metadata.ino()), metadata.nlink()))
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = metadata;
+        None
+    }
 }
 
 /// Return (total_bytes, free_bytes) for the filesystem containing `path`.
@@ -43,8 +333,25 @@ mod tests {
         assert_eq!(format_bytes(1_073_741_824), "1.0 GiB");
     }
 
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("4096"), Ok(4096));
+        assert_eq!(parse_size("50MB"), Ok(50 * 1_048_576));
+        assert_eq!(parse_size("1.5 gb"), Ok(1_610_612_736));
+        assert!(parse_size("ten MB").is_err());
+        assert!(parse_size("5 TB").is_err());
+    }
+
     #[test]
     fn test_dir_size_nonexistent() {
         assert_eq!(dir_size(Path::new("/nonexistent/path")), 0);
     }
+
+    #[test]
+    fn test_dir_usage_nonexistent() {
+        assert_eq!(
+            dir_usage(Path::new("/nonexistent/path")),
+            DirUsage::default()
+        );
+    }
 }
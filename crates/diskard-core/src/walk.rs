@@ -0,0 +1,249 @@
+use std::cell::{Cell, RefCell};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::error::{Error, Result};
+
+thread_local! {
+    static CURRENT: RefCell<Option<IoBudget>> = const { RefCell::new(None) };
+    static SAME_FILE_SYSTEM: Cell<bool> = const { Cell::new(false) };
+}
+
+/// A fixed pool of I/O threads shared by every directory walk started inside
+/// [`IoBudget::enter`].
+///
+/// Each jwalk walk normally spreads its `read_dir` calls over the global rayon
+/// pool, so running many walks at once multiplies the number of directories
+/// held open. Routing them all through one pool bounds the open directories to
+/// the pool size no matter how many recognizers run concurrently.
+#[derive(Clone)]
+pub struct IoBudget {
+    pool: Arc<rayon::ThreadPool>,
+}
+
+impl IoBudget {
+    /// A budget of `threads` I/O threads (at least one).
+    pub fn new(threads: usize) -> Result<Self> {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads.max(1))
+            .thread_name(|i| format!("diskard-io-{i}"))
+            .build()
+            .map_err(|e| Error::Scanner(format!("Cannot start I/O threads: {e}")))?;
+        Ok(Self {
+            pool: Arc::new(pool),
+        })
+    }
+
+    /// Run `f` with every walk it starts on this thread drawing from this budget.
+    ///
+    /// Must not be called from one of the budget's own threads: walks block
+    /// while the pool reads directories.
+    pub fn enter<R>(&self, f: impl FnOnce() -> R) -> R {
+        let previous = CURRENT.with(|current| current.replace(Some(self.clone())));
+        let result = f();
+        CURRENT.with(|current| *current.borrow_mut() = previous);
+        result
+    }
+}
+
+/// Default I/O budget: the number of CPUs, clamped to `4..=16`.
+pub fn default_io_threads() -> usize {
+    std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(4)
+        .clamp(4, 16)
+}
+
+/// Run `f` with every walk it starts on this thread, when `enabled`, staying
+/// on the file system it starts on. Mount points are reported but not
+/// descended into.
+pub fn same_file_system<R>(enabled: bool, f: impl FnOnce() -> R) -> R {
+    let previous = SAME_FILE_SYSTEM.with(|current| current.replace(enabled));
+    let result = f();
+    SAME_FILE_SYSTEM.with(|current| current.set(previous));
+    result
+}
+
+/// The device walks of `root` started on this thread must stay on, if
+/// [`same_file_system`] is in effect.
+pub(crate) fn boundary(root: &Path) -> Option<u64> {
+    if !SAME_FILE_SYSTEM.with(Cell::get) {
+        return None;
+    }
+    std::fs::metadata(root).ok().map(|m| device(&m))
+}
+
+/// Device holding the file `metadata` describes (always 0 off Unix, where
+/// walks don't detect mount points).
+pub(crate) fn device(metadata: &std::fs::Metadata) -> u64 {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        metadata.dev()
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = metadata;
+        0
+    }
+}
+
+/// Start a directory walk of `root`.
+///
+/// Inside [`IoBudget::enter`] the walk runs on that budget's threads;
+/// elsewhere it uses jwalk's default (the global rayon pool).
+pub fn walk_dir(root: impl AsRef<Path>) -> jwalk::WalkDir {
+    walk_dir_pruning(root, |_| false)
+}
+
+/// Start a directory walk of `root` that reports, but doesn't descend into,
+/// subdirectories for which `prune` returns `true`.
+///
+/// Use this rather than setting `process_read_dir` on the walker, which would
+/// replace the [`same_file_system`] check.
+pub fn walk_dir_pruning(
+    root: impl AsRef<Path>,
+    prune: impl Fn(&jwalk::DirEntry<((), ())>) -> bool + Send + Sync + 'static,
+) -> jwalk::WalkDir {
+    walk_dir_carrying(root, (), |_, _, _| {}, move |_, entry| prune(entry))
+}
+
+/// Start a directory walk of `root` that carries `state` down as it descends.
+///
+/// Each directory read gets a copy of its parent's state, which `enter`
+/// updates from the directory's path and entries (it may also drop the
+/// entries). Subdirectories for which `prune` returns `true`, given that
+/// state, are reported but not descended into.
+pub fn walk_dir_carrying<S>(
+    root: impl AsRef<Path>,
+    state: S,
+    enter: impl Fn(&Path, &mut S, &mut Vec<jwalk::Result<jwalk::DirEntry<(S, ())>>>)
+        + Send
+        + Sync
+        + 'static,
+    prune: impl Fn(&S, &jwalk::DirEntry<(S, ())>) -> bool + Send + Sync + 'static,
+) -> jwalk::WalkDirGeneric<(S, ())>
+where
+    S: Clone + Send + Default + std::fmt::Debug + 'static,
+{
+    let boundary = boundary(root.as_ref());
+    let walker = jwalk::WalkDirGeneric::<(S, ())>::new(root)
+        .root_read_dir_state(state)
+        .process_read_dir(move |_, dir, state, children| {
+            enter(dir, state, children);
+            for entry in children.iter_mut().flatten() {
+                if !entry.file_type().is_dir() {
+                    continue;
+                }
+                let crosses = boundary.is_some_and(|dev| {
+                    entry
+                        .metadata()
+                        .is_ok_and(|metadata| device(&metadata) != dev)
+                });
+                if crosses || prune(state, entry) {
+                    entry.read_children_path = None;
+                }
+            }
+        });
+    match CURRENT.with(|current| current.borrow().clone()) {
+        // The walking thread is never one of the pool's, so a free pool
+        // thread is always eventually available and the busy check can go.
+        Some(budget) => walker.parallelism(jwalk::Parallelism::RayonExistingPool {
+            pool: budget.pool,
+            busy_timeout: None,
+        }),
+        None => walker,
+    }
+}
+
+/// The directories directly inside `dir`, sorted. Symbolic links to
+/// directories are left out.
+pub fn subdirs(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return vec![];
+    };
+    let mut dirs: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+        .map(|e| e.path())
+        .collect();
+    dirs.sort();
+    dirs
+}
+
+/// Run `f` on the current budget's threads, so rayon work it spawns stays
+/// within the budget. Outside a budget `f` runs here and uses the global pool.
+pub(crate) fn install<R: Send>(f: impl FnOnce() -> R + Send) -> R {
+    match CURRENT.with(|current| current.borrow().clone()) {
+        Some(budget) => budget.pool.install(f),
+        None => f(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn in_budget() -> bool {
+        CURRENT.with(|current| current.borrow().is_some())
+    }
+
+    #[test]
+    fn test_enter_restores_previous_budget() {
+        let budget = IoBudget::new(2).unwrap();
+        assert!(!in_budget());
+        budget.enter(|| {
+            assert!(in_budget());
+            IoBudget::new(1).unwrap().enter(|| assert!(in_budget()));
+            assert!(in_budget());
+        });
+        assert!(!in_budget());
+    }
+
+    #[test]
+    fn test_walk_inside_budget_sees_every_entry() {
+        let root = tempfile::TempDir::new().unwrap();
+        for dir in ["a/b/c", "d/e", "f"] {
+            std::fs::create_dir_all(root.path().join(dir)).unwrap();
+        }
+        let count = || walk_dir(root.path()).into_iter().flatten().count();
+
+        let outside = count();
+        let inside = IoBudget::new(1).unwrap().enter(count);
+
+        assert_eq!(outside, 7);
+        assert_eq!(inside, outside);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_same_file_system_stops_at_mount_points() {
+        // /dev/pts and /dev/shm are usually mounted separately from /dev
+        let dev = std::fs::metadata("/dev").map(|m| device(&m)).ok();
+        let mount = std::fs::read_dir("/dev")
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|entry| entry.path())
+            .find(|path| {
+                std::fs::symlink_metadata(path).is_ok_and(|m| m.is_dir() && Some(device(&m)) != dev)
+                    && std::fs::read_dir(path).is_ok_and(|mut d| d.next().is_some())
+            });
+        let Some(mount) = mount else {
+            return;
+        };
+
+        let inside = |enabled| {
+            same_file_system(enabled, || {
+                walk_dir("/dev")
+                    .max_depth(2)
+                    .into_iter()
+                    .flatten()
+                    .filter(|entry| entry.path().starts_with(&mount) && entry.path() != mount)
+                    .count()
+            })
+        };
+        assert!(inside(false) > 0);
+        assert_eq!(inside(true), 0);
+    }
+}
@@ -1,7 +1,13 @@
 use std::path::{Path, PathBuf};
+use std::time::Instant;
 
+use diskard_core::cleaner::CleanEvent;
+use diskard_core::error::ErrorRecord;
 use diskard_core::finding::Finding;
-use diskard_core::size::{dir_size, disk_usage};
+use diskard_core::mounts::{self, MountUsage};
+use diskard_core::processes;
+use diskard_core::scanner::ScanEvent;
+use diskard_core::size::{dir_usage, format_bytes};
 
 /// Application state for the TUI.
 pub struct App {
@@ -12,8 +18,52 @@ pub struct App {
     pub mode: AppMode,
     pub status_message: Option<String>,
     pub drill_down: Option<DrillDownState>,
-    pub disk_total: u64,
-    pub disk_free: u64,
+    /// File systems holding findings, largest reclaimable first; the root
+    /// file system alone while there are none.
+    pub mounts: Vec<MountUsage>,
+    /// Progress of the scan still feeding in findings, if one is running.
+    pub scan: Option<ScanProgress>,
+    /// Items about to be deleted that running processes are using, with
+    /// the processes, for the confirmation dialog.
+    pub in_use: Vec<String>,
+    /// Progress of the clean running in the background, if one is.
+    pub cleaning: Option<CleanProgress>,
+}
+
+/// Progress of a clean running in the background.
+#[derive(Default)]
+pub struct CleanProgress {
+    pub total: usize,
+    pub finished: usize,
+    pub total_bytes: u64,
+    pub files_removed: u64,
+    pub bytes_freed: u64,
+}
+
+impl CleanProgress {
+    /// One-line summary for the status bar.
+    pub fn status(&self) -> String {
+        let mut status = format!(
+            " Cleaning {}/{} — {} of {} freed",
+            self.finished,
+            self.total,
+            format_bytes(self.bytes_freed),
+            format_bytes(self.total_bytes),
+        );
+        if self.files_removed > 0 {
+            status.push_str(&format!(", {} files removed", self.files_removed));
+        }
+        status
+    }
+}
+
+/// Progress of a scan running in the background.
+pub struct ScanProgress {
+    pub started: Instant,
+    pub total: usize,
+    pub finished: usize,
+    /// Names of the recognizers currently running.
+    pub running: Vec<&'static str>,
 }
 
 pub struct FindingItem {
@@ -34,6 +84,7 @@ pub struct DrillDownEntry {
     pub name: String,
     pub path: PathBuf,
     pub size_bytes: u64,
+    pub apparent_bytes: u64,
     pub is_dir: bool,
     pub checked: bool,
 }
@@ -56,22 +107,19 @@ fn scan_directory(path: &Path) -> Option<Vec<DrillDownEntry>> {
         .map(|entry| {
             let path = entry.path();
             let is_dir = path.is_dir();
-            let size_bytes = if is_dir {
-                dir_size(&path)
-            } else {
-                path.metadata().map(|m| m.len()).unwrap_or(0)
-            };
+            let usage = dir_usage(&path);
             let name = entry.file_name().to_string_lossy().into_owned();
             DrillDownEntry {
                 name,
                 path,
-                size_bytes,
+                size_bytes: usage.size_bytes,
+                apparent_bytes: usage.apparent_bytes,
                 is_dir,
                 checked: false,
             }
         })
         .collect();
-    entries.sort_by(|a, b| b.size_bytes.cmp(&a.size_bytes));
+    entries.sort_by_key(|e| std::cmp::Reverse(e.size_bytes));
     Some(entries)
 }
 
@@ -166,12 +214,8 @@ impl DrillDownState {
             .sum()
     }
 
-    pub fn checked_paths(&self) -> Vec<(PathBuf, u64)> {
-        self.entries
-            .iter()
-            .filter(|e| e.checked)
-            .map(|e| (e.path.clone(), e.size_bytes))
-            .collect()
+    pub fn checked_entries(&self) -> Vec<&DrillDownEntry> {
+        self.entries.iter().filter(|e| e.checked).collect()
     }
 
     /// Remove checked entries and rescan the current directory.
@@ -196,9 +240,7 @@ impl App {
             })
             .collect();
 
-        let (disk_total, disk_free) = disk_usage(Path::new("/")).unwrap_or((0, 0));
-
-        Self {
+        let mut app = Self {
             findings: items,
             selected: 0,
             should_quit: false,
@@ -206,11 +248,132 @@ impl App {
             mode: AppMode::Browse,
             status_message: None,
             drill_down: None,
-            disk_total,
-            disk_free,
+            mounts: Vec::new(),
+            scan: None,
+            in_use: Vec::new(),
+            cleaning: None,
+        };
+        app.refresh_mounts();
+        app
+    }
+
+    /// Recompute per-mount space after findings were added or removed.
+    pub fn refresh_mounts(&mut self) {
+        self.mounts = mounts::group_by_mount(self.findings.iter().map(|f| &f.finding));
+        if self.mounts.is_empty() {
+            self.mounts.extend(MountUsage::of(Path::new("/")));
+        }
+    }
+
+    /// An empty app that fills in as scan events arrive.
+    pub fn scanning() -> Self {
+        Self {
+            scan: Some(ScanProgress {
+                started: Instant::now(),
+                total: 0,
+                finished: 0,
+                running: Vec::new(),
+            }),
+            ..Self::new(Vec::new())
         }
     }
 
+    /// Apply a progress event from the background scan.
+    pub fn handle_scan_event(&mut self, event: ScanEvent) {
+        match event {
+            ScanEvent::FindingDiscovered { finding, .. } => self.insert_finding(finding),
+            ScanEvent::RecognizerFailed { name, error, .. } => {
+                self.status_message = Some(format!(" {name} failed: {error}"));
+                self.recognizer_done(name);
+            }
+            ScanEvent::RecognizerFinished { name, .. } => self.recognizer_done(name),
+            ScanEvent::Started { recognizers } => {
+                if let Some(scan) = &mut self.scan {
+                    scan.total = recognizers;
+                }
+            }
+            ScanEvent::RecognizerStarted { name, .. } => {
+                if let Some(scan) = &mut self.scan {
+                    scan.running.push(name);
+                }
+            }
+        }
+    }
+
+    /// Apply a progress event from the background clean.
+    pub fn handle_clean_event(&mut self, event: CleanEvent) {
+        let Some(cleaning) = &mut self.cleaning else {
+            return;
+        };
+        match event {
+            CleanEvent::Started {
+                findings,
+                total_bytes,
+            } => {
+                cleaning.total = findings;
+                cleaning.total_bytes = total_bytes;
+            }
+            CleanEvent::Progress {
+                files_removed,
+                bytes_freed,
+            } => {
+                // Events from several threads may arrive out of order
+                cleaning.files_removed = cleaning.files_removed.max(files_removed);
+                cleaning.bytes_freed = cleaning.bytes_freed.max(bytes_freed);
+            }
+            CleanEvent::FindingFinished { .. } => cleaning.finished += 1,
+        }
+    }
+
+    fn recognizer_done(&mut self, name: &str) {
+        if let Some(scan) = &mut self.scan {
+            scan.running.retain(|running| *running != name);
+            scan.finished += 1;
+        }
+    }
+
+    /// Insert a finding in size order, keeping the highlighted item in place.
+    fn insert_finding(&mut self, finding: Finding) {
+        let index = self
+            .findings
+            .partition_point(|item| item.finding.size_bytes >= finding.size_bytes);
+        if index <= self.selected && !self.findings.is_empty() {
+            self.selected += 1;
+        }
+        self.count_in_mount(&finding);
+        self.findings.insert(
+            index,
+            FindingItem {
+                finding,
+                checked: false,
+            },
+        );
+    }
+
+    /// Add one new finding to the space of the file system it lives on,
+    /// resolving only its own mount.
+    fn count_in_mount(&mut self, finding: &Finding) {
+        let Some(mount) = mounts::mount_point(&finding.path) else {
+            return;
+        };
+        let index = match self.mounts.iter().position(|m| m.mount_point == mount) {
+            Some(index) => index,
+            None => match MountUsage::of(&mount) {
+                Some(usage) => {
+                    self.mounts.push(usage);
+                    self.mounts.len() - 1
+                }
+                None => return,
+            },
+        };
+        self.mounts[index].reclaimable_bytes += finding.size_bytes;
+        self.mounts[index].findings += 1;
+        // The placeholder shown before any findings gives way to real ones
+        self.mounts.retain(|m| m.findings > 0);
+        self.mounts
+            .sort_by_key(|m| std::cmp::Reverse(m.reclaimable_bytes));
+    }
+
     pub fn total_reclaimable(&self) -> u64 {
         self.findings.iter().map(|f| f.finding.size_bytes).sum()
     }
@@ -265,6 +428,65 @@ impl App {
         if self.selected >= self.findings.len() && !self.findings.is_empty() {
             self.selected = self.findings.len() - 1;
         }
+        self.refresh_mounts();
+    }
+
+    /// Ask to confirm deleting the checked findings (`Confirm`) or drill-down
+    /// entries (`ConfirmDrillDown`), noting which are in use.
+    pub fn confirm(&mut self, mode: AppMode) {
+        let findings = if mode == AppMode::ConfirmDrillDown {
+            self.checked_drill_down_findings()
+        } else {
+            self.checked_findings()
+        };
+        let paths: Vec<&Path> = findings.iter().map(|f| f.path.as_path()).collect();
+        self.in_use = findings
+            .iter()
+            .zip(processes::processes_using(&paths))
+            .filter(|(_, users)| !users.is_empty())
+            .map(|(finding, users)| {
+                let name = finding.path.file_name().unwrap_or_default();
+                format!(
+                    "{}: {}",
+                    name.to_string_lossy(),
+                    processes::describe(&users)
+                )
+            })
+            .collect();
+        self.mode = mode;
+    }
+
+    /// Uncheck findings that failed to delete, so they stay listed.
+    pub fn keep_failed(&mut self, errors: &[ErrorRecord]) {
+        for item in &mut self.findings {
+            if errors
+                .iter()
+                .any(|e| e.path.as_ref() == Some(&item.finding.path))
+            {
+                item.checked = false;
+            }
+        }
+    }
+
+    /// Checked drill-down entries as findings attributed to the inspected finding.
+    pub fn checked_drill_down_findings(&self) -> Vec<Finding> {
+        let (Some(state), Some(item)) = (&self.drill_down, self.findings.get(self.selected)) else {
+            return vec![];
+        };
+        state
+            .checked_entries()
+            .into_iter()
+            .map(|entry| Finding {
+                path: entry.path.clone(),
+                size_bytes: entry.size_bytes,
+                apparent_bytes: entry.apparent_bytes,
+                description: format!("Part of {}", item.finding.description),
+                // Commands and extra paths belong to the whole finding
+                native: None,
+                extra_paths: Vec::new(),
+                ..item.finding.clone()
+            })
+            .collect()
     }
 
     /// Enter drill-down mode for the currently selected finding.
@@ -15,12 +15,19 @@ pub fn render(frame: &mut Frame, area: Rect, app: &App) {
         (app.checked_count(), app.checked_size())
     };
 
-    render_dialog(frame, area, count, size);
+    render_dialog(frame, area, count, size, &app.in_use);
 }
 
-fn render_dialog(frame: &mut Frame, area: Rect, count: usize, size: u64) {
-    let popup_width = 50.min(area.width.saturating_sub(4));
-    let popup_height = 7.min(area.height.saturating_sub(2));
+fn render_dialog(frame: &mut Frame, area: Rect, count: usize, size: u64, in_use: &[String]) {
+    // Room for a heading and up to three items in use
+    let shown = in_use.len().min(3);
+    let extra = if in_use.is_empty() {
+        0
+    } else {
+        shown as u16 + 2
+    };
+    let popup_width = if in_use.is_empty() { 50 } else { 70 }.min(area.width.saturating_sub(4));
+    let popup_height = (7 + extra).min(area.height.saturating_sub(2));
     let popup_area = Rect::new(
         (area.width - popup_width) / 2,
         (area.height - popup_height) / 2,
@@ -28,12 +35,27 @@ fn render_dialog(frame: &mut Frame, area: Rect, count: usize, size: u64) {
         popup_height,
     );
 
-    let text = vec![
+    let mut text = vec![
         Line::from(""),
         Line::from(Span::styled(
             format!("Delete {} items ({})?", count, format_bytes(size)),
             Style::default().fg(Color::Yellow),
         )),
+    ];
+    if !in_use.is_empty() {
+        text.push(Line::from(""));
+        text.push(Line::from(Span::styled(
+            format!("⚠ {} in use and will be skipped:", in_use.len()),
+            Style::default().fg(Color::Red),
+        )));
+        text.extend(
+            in_use
+                .iter()
+                .take(shown)
+                .map(|item| Line::from(Span::raw(item.clone()))),
+        );
+    }
+    text.extend([
         Line::from(""),
         Line::from(vec![
             Span::styled(" [y] ", Style::default().fg(Color::Green)),
@@ -41,7 +63,7 @@ fn render_dialog(frame: &mut Frame, area: Rect, count: usize, size: u64) {
             Span::styled(" [n] ", Style::default().fg(Color::Red)),
             Span::raw("Cancel"),
         ]),
-    ];
+    ]);
 
     frame.render_widget(Clear, popup_area);
     let paragraph = Paragraph::new(text).alignment(Alignment::Center).block(
@@ -7,10 +7,21 @@ use ratatui::Frame;
 
 use crate::app::App;
 
-/// Render the disk summary header.
+/// At most this many further file systems get a line of their own.
+const MAX_EXTRA_MOUNTS: usize = 3;
+
+/// Height of the header: borders, three summary lines, and one line per
+/// further file system holding findings.
+pub fn height(app: &App) -> u16 {
+    5 + app.mounts.len().saturating_sub(1).min(MAX_EXTRA_MOUNTS) as u16
+}
+
+/// Render the disk summary header for the file system with the most
+/// reclaimable space, followed by a line for each other one.
 pub fn render(frame: &mut Frame, area: Rect, app: &App) {
-    let total = app.disk_total;
-    let free = app.disk_free;
+    let primary = app.mounts.first();
+    let total = primary.map_or(0, |m| m.total_bytes);
+    let free = primary.map_or(0, |m| m.free_bytes);
     let used = total.saturating_sub(free);
     let used_ratio = if total > 0 {
         used as f64 / total as f64
@@ -28,8 +39,12 @@ pub fn render(frame: &mut Frame, area: Rect, app: &App) {
     };
 
     // Line 1: disk stats
+    let label = primary.map_or_else(
+        || " Disk: ".to_string(),
+        |m| format!(" {}: ", m.mount_point.display()),
+    );
     let line1 = Line::from(vec![
-        Span::styled(" Disk: ", Style::default().fg(Color::White)),
+        Span::styled(label, Style::default().fg(Color::White)),
         Span::styled(format_bytes(total), Style::default().fg(Color::Cyan)),
         Span::raw(" total | "),
         Span::styled(format_bytes(used), Style::default().fg(gauge_color)),
@@ -56,19 +71,46 @@ pub fn render(frame: &mut Frame, area: Rect, app: &App) {
     // Line 3: reclaimable / selected
     let reclaimable = app.total_reclaimable();
     let selected = app.checked_size();
-    let line3 = Line::from(vec![
+    let mut line3 = Line::from(vec![
         Span::styled(" Reclaimable: ", Style::default().fg(Color::White)),
         Span::styled(format_bytes(reclaimable), Style::default().fg(Color::Cyan)),
         Span::raw(" | "),
         Span::styled("Selected: ", Style::default().fg(Color::White)),
         Span::styled(format_bytes(selected), Style::default().fg(Color::Yellow)),
     ]);
+    if let Some(scan) = &app.scan {
+        line3.push_span(Span::raw(" | "));
+        line3.push_span(Span::styled(
+            format!("Scanning {}/{}…", scan.finished, scan.total),
+            Style::default().fg(Color::Cyan),
+        ));
+    }
 
     let block = Block::default()
         .title(" Disk Summary ")
         .borders(Borders::ALL)
         .border_style(Style::default().fg(Color::Green));
 
-    let paragraph = Paragraph::new(vec![line1, line2, line3]).block(block);
+    let mut lines = vec![line1, line2, line3];
+    for mount in app.mounts.iter().skip(1).take(MAX_EXTRA_MOUNTS) {
+        lines.push(Line::from(vec![
+            Span::styled(
+                format!(" {}: ", mount.mount_point.display()),
+                Style::default().fg(Color::White),
+            ),
+            Span::styled(
+                format_bytes(mount.reclaimable_bytes),
+                Style::default().fg(Color::Cyan),
+            ),
+            Span::raw(" reclaimable | "),
+            Span::styled(
+                format_bytes(mount.free_bytes),
+                Style::default().fg(Color::Green),
+            ),
+            Span::raw(format!(" free of {}", format_bytes(mount.total_bytes))),
+        ]));
+    }
+
+    let paragraph = Paragraph::new(lines).block(block);
     frame.render_widget(paragraph, area);
 }
@@ -4,8 +4,11 @@ use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, Paragraph};
 use ratatui::Frame;
 
+use crate::app::ScanProgress;
+
 /// Render a scanning progress indicator.
-pub fn render(frame: &mut Frame, area: Rect, elapsed_secs: f64) {
+pub fn render(frame: &mut Frame, area: Rect, scan: &ScanProgress) {
+    let elapsed_secs = scan.started.elapsed().as_secs_f64();
     let dots = ".".repeat(((elapsed_secs * 2.0) as usize % 4) + 1);
     let text = vec![
         Line::from(""),
@@ -15,7 +18,14 @@ pub fn render(frame: &mut Frame, area: Rect, elapsed_secs: f64) {
         )),
         Line::from(""),
         Line::from(Span::styled(
-            format!("  Elapsed: {elapsed_secs:.1}s"),
+            format!(
+                "  {}/{} recognizers done · elapsed: {elapsed_secs:.1}s",
+                scan.finished, scan.total
+            ),
+            Style::default().fg(Color::DarkGray),
+        )),
+        Line::from(Span::styled(
+            format!("  {}", scan.running.join(", ")),
             Style::default().fg(Color::DarkGray),
         )),
     ];
@@ -3,13 +3,17 @@ pub mod components;
 pub mod event;
 pub mod tui;
 
-use app::{App, AppMode};
+use app::{App, AppMode, CleanProgress};
 use crossterm::event::{KeyCode, KeyEventKind, KeyModifiers};
-use diskard_core::cleaner::{self, DeleteMode};
+use diskard_core::cleaner::{self, CleanEvent, CleanOptions, CleanResult, DeleteMode};
 use diskard_core::finding::Finding;
+use diskard_core::journal::Journal;
+use diskard_core::scanner::ScanEvent;
 use diskard_core::size::format_bytes;
 use ratatui::layout::{Constraint, Layout};
 use std::io;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Arc;
 use std::time::Duration;
 
 /// Run the interactive TUI with the given findings.
@@ -19,17 +23,64 @@ pub fn run(findings: Vec<Finding>) -> io::Result<()> {
         return Ok(());
     }
 
+    run_app(App::new(findings), None)
+}
+
+/// Run the interactive TUI while a scan is still in progress, adding findings
+/// as they arrive on `events`. The scan is done when the sender hangs up.
+pub fn run_streaming(events: Receiver<ScanEvent>) -> io::Result<()> {
+    run_app(App::scanning(), Some(events))
+}
+
+fn run_app(mut app: App, mut events: Option<Receiver<ScanEvent>>) -> io::Result<()> {
     let mut terminal = tui::init()?;
-    let mut app = App::new(findings);
+    let mut found_nothing = false;
+    let mut clean_job: Option<CleanJob> = None;
 
     loop {
+        // Take in whatever the background scan has found since the last frame
+        if let Some(receiver) = &events {
+            loop {
+                match receiver.try_recv() {
+                    Ok(event) => app.handle_scan_event(event),
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => {
+                        app.scan = None;
+                        events = None;
+                        if app.findings.is_empty() {
+                            found_nothing = true;
+                            app.should_quit = true;
+                        }
+                        break;
+                    }
+                }
+            }
+        }
+
+        // Take in the background clean's progress, and its outcome once done
+        if let Some(job) = &clean_job {
+            let mut outcome = None;
+            while let Ok(message) = job.messages.try_recv() {
+                match message {
+                    CleanMessage::Event(event) => app.handle_clean_event(event),
+                    CleanMessage::Done(result) => outcome = Some(result),
+                }
+            }
+            if let Some(result) = outcome {
+                let drill_down = job.drill_down;
+                let count = job.count;
+                clean_job = None;
+                finish_clean(&mut app, result, count, drill_down);
+            }
+        }
+
         // Draw
         terminal.draw(|frame| {
             let area = frame.area();
 
             // Main layout: header + results + status bar
             let chunks = Layout::vertical([
-                Constraint::Length(5),
+                Constraint::Length(components::header::height(&app)),
                 Constraint::Min(5),
                 Constraint::Length(1),
             ])
@@ -43,12 +94,16 @@ pub fn run(findings: Vec<Finding>) -> io::Result<()> {
                 if let Some(ref state) = app.drill_down {
                     components::drilldown::render(frame, chunks[1], state);
                 }
+            } else if let (Some(scan), true) = (&app.scan, app.findings.is_empty()) {
+                components::scan_view::render(frame, chunks[1], scan);
             } else {
                 components::results::render(frame, chunks[1], &app);
             }
 
             // Status bar
-            let status = if let Some(ref msg) = app.status_message {
+            let status = if let Some(ref cleaning) = app.cleaning {
+                cleaning.status()
+            } else if let Some(ref msg) = app.status_message {
                 msg.clone()
             } else if app.mode == AppMode::DrillDown || app.mode == AppMode::ConfirmDrillDown {
                 " ↑↓/jk: navigate | Space: toggle | a: all | d: delete | l/→/Enter: open | h/←: back | Esc/q: exit".to_string()
@@ -87,6 +142,11 @@ pub fn run(findings: Vec<Finding>) -> io::Result<()> {
                         continue;
                     }
 
+                    // The list can't change under a clean in progress
+                    if app.cleaning.is_some() {
+                        continue;
+                    }
+
                     match app.mode {
                         AppMode::Browse => match key.code {
                             KeyCode::Char('q') | KeyCode::Esc => app.should_quit = true,
@@ -98,7 +158,7 @@ pub fn run(findings: Vec<Finding>) -> io::Result<()> {
                             KeyCode::Char('l') | KeyCode::Right => app.enter_drill_down(),
                             KeyCode::Enter => {
                                 if app.checked_count() > 0 {
-                                    app.mode = AppMode::Confirm;
+                                    app.confirm(AppMode::Confirm);
                                 } else {
                                     app.status_message =
                                         Some(" No items selected. Use Space to select.".into());
@@ -130,7 +190,7 @@ pub fn run(findings: Vec<Finding>) -> io::Result<()> {
                             KeyCode::Char('d') => {
                                 if let Some(ref state) = app.drill_down {
                                     if state.checked_count() > 0 {
-                                        app.mode = AppMode::ConfirmDrillDown;
+                                        app.confirm(AppMode::ConfirmDrillDown);
                                     } else {
                                         app.status_message =
                                             Some(" No items selected. Use Space to select.".into());
@@ -166,34 +226,9 @@ pub fn run(findings: Vec<Finding>) -> io::Result<()> {
                         },
                         AppMode::ConfirmDrillDown => match key.code {
                             KeyCode::Char('y') | KeyCode::Enter => {
-                                if let Some(ref mut state) = app.drill_down {
-                                    let to_delete = state.checked_paths();
-                                    let mut deleted = 0u64;
-                                    let mut count = 0usize;
-                                    let mut err_count = 0usize;
-                                    for (path, size) in &to_delete {
-                                        match cleaner::delete_path(path, DeleteMode::Trash) {
-                                            Ok(()) => {
-                                                deleted += size;
-                                                count += 1;
-                                            }
-                                            Err(_) => err_count += 1,
-                                        }
-                                    }
-                                    state.remove_checked();
-                                    let msg = if err_count > 0 {
-                                        format!(
-                                            " Trashed {count} items ({}), {err_count} failed",
-                                            format_bytes(deleted),
-                                        )
-                                    } else {
-                                        format!(
-                                            " Moved {count} items to Trash, freed {}",
-                                            format_bytes(deleted),
-                                        )
-                                    };
-                                    app.status_message = Some(msg);
-                                }
+                                let to_delete = app.checked_drill_down_findings();
+                                app.cleaning = Some(CleanProgress::default());
+                                clean_job = Some(CleanJob::start(to_delete, true));
                                 app.mode = AppMode::DrillDown;
                             }
                             KeyCode::Char('n') | KeyCode::Esc => {
@@ -204,25 +239,9 @@ pub fn run(findings: Vec<Finding>) -> io::Result<()> {
                         AppMode::Confirm => match key.code {
                             KeyCode::Char('y') | KeyCode::Enter => {
                                 let to_delete = app.checked_findings();
-                                let count = to_delete.len();
-                                match cleaner::clean(&to_delete, DeleteMode::Trash) {
-                                    Ok(result) => {
-                                        app.remove_checked();
-                                        app.status_message = Some(format!(
-                                            " Moved {} items to Trash, freed {}",
-                                            result.deleted_count,
-                                            format_bytes(result.freed_bytes),
-                                        ));
-                                    }
-                                    Err(e) => {
-                                        app.status_message =
-                                            Some(format!(" Error cleaning {count} items: {e}"));
-                                    }
-                                }
+                                app.cleaning = Some(CleanProgress::default());
+                                clean_job = Some(CleanJob::start(to_delete, false));
                                 app.mode = AppMode::Browse;
-                                if app.findings.is_empty() {
-                                    app.should_quit = true;
-                                }
                             }
                             KeyCode::Char('n') | KeyCode::Esc => {
                                 app.mode = AppMode::Browse;
@@ -237,5 +256,86 @@ pub fn run(findings: Vec<Finding>) -> io::Result<()> {
     }
 
     tui::restore()?;
+    if found_nothing {
+        println!("No reclaimable space found.");
+    }
     Ok(())
 }
+
+/// Messages from a clean running in the background.
+enum CleanMessage {
+    Event(CleanEvent),
+    Done(diskard_core::error::Result<CleanResult>),
+}
+
+/// A clean moving items to the Trash in the background.
+struct CleanJob {
+    messages: Receiver<CleanMessage>,
+    count: usize,
+    /// Whether drill-down entries are being deleted rather than findings.
+    drill_down: bool,
+}
+
+impl CleanJob {
+    fn start(findings: Vec<Finding>, drill_down: bool) -> Self {
+        let (sender, messages) = mpsc::channel();
+        let count = findings.len();
+        let events = sender.clone();
+        std::thread::spawn(move || {
+            let options = CleanOptions {
+                mode: DeleteMode::Trash,
+                journal: Journal::open_default().ok(),
+                on_event: Some(Arc::new(move |event| {
+                    let _ = events.send(CleanMessage::Event(event));
+                })),
+                ..Default::default()
+            };
+            let result = cleaner::clean_with(&findings, &options);
+            let _ = sender.send(CleanMessage::Done(result));
+        });
+        Self {
+            messages,
+            count,
+            drill_down,
+        }
+    }
+}
+
+/// Drop what a finished clean removed and report how it went.
+fn finish_clean(
+    app: &mut App,
+    result: diskard_core::error::Result<CleanResult>,
+    count: usize,
+    drill_down: bool,
+) {
+    app.cleaning = None;
+    let message = match &result {
+        Ok(result) if result.errors.is_empty() => format!(
+            " Moved {} items to Trash, freed {}",
+            result.deleted_count,
+            format_bytes(result.freed_bytes),
+        ),
+        Ok(result) => format!(
+            " Trashed {} items ({}), {} failed: {}",
+            result.deleted_count,
+            format_bytes(result.freed_bytes),
+            result.errors.len(),
+            result.errors[0].message,
+        ),
+        Err(e) => format!(" Error cleaning {count} items: {e}"),
+    };
+    app.status_message = Some(message);
+
+    if drill_down {
+        if let (Ok(_), Some(state)) = (&result, &mut app.drill_down) {
+            state.remove_checked();
+        }
+        app.refresh_mounts();
+    } else if let Ok(result) = &result {
+        app.keep_failed(&result.errors);
+        app.remove_checked();
+        if app.findings.is_empty() && app.scan.is_none() {
+            app.should_quit = true;
+        }
+    }
+}
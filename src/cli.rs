@@ -1,4 +1,6 @@
-use clap::{Parser, Subcommand, ValueEnum};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use diskard_core::error::ErrorKind;
+use std::path::PathBuf;
 
 #[derive(Parser)]
 #[command(
@@ -49,21 +51,15 @@ pub enum Command {
         /// Only show items older than duration (e.g., "7d", "30d", "1h")
         #[arg(long)]
         older_than: Option<String>,
+
+        #[command(flatten)]
+        scan: ScanArgs,
     },
 
     /// Delete selected findings
     Clean {
-        /// Only show what would be deleted
-        #[arg(long)]
-        dry_run: bool,
-
-        /// Move to trash instead of permanent delete (default)
-        #[arg(long, conflicts_with = "permanent")]
-        trash: bool,
-
-        /// Permanently delete files (irreversible)
-        #[arg(long, conflicts_with = "trash")]
-        permanent: bool,
+        #[command(flatten)]
+        delete: DeleteArgs,
 
         /// Maximum risk level to clean
         #[arg(long, short, default_value = "safe")]
@@ -77,9 +73,29 @@ pub enum Command {
         #[arg(long)]
         older_than: Option<String>,
 
-        /// Skip confirmation prompt
-        #[arg(short = 'y', long)]
-        yes: bool,
+        #[command(flatten)]
+        scan: ScanArgs,
+    },
+
+    /// Show items removed by previous clean operations
+    History,
+
+    /// Restore a trashed or quarantined item to its original path
+    Restore {
+        /// Journal ID (from `diskard history`) or original path of the item
+        target: String,
+    },
+
+    /// Inspect, restore, or purge quarantined items
+    Quarantine {
+        #[command(subcommand)]
+        action: QuarantineAction,
+    },
+
+    /// Manage the cache of directory sizes kept between scans
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
     },
 
     /// List available targets or configuration
@@ -104,6 +120,10 @@ pub enum Command {
         /// Filter by category
         #[arg(long, short)]
         category: Option<CategoryFilter>,
+
+        /// Measure every directory afresh instead of reusing sizes from earlier scans
+        #[arg(long)]
+        no_cache: bool,
     },
 
     /// Generate shell completions
@@ -113,12 +133,104 @@ pub enum Command {
     },
 }
 
+/// Where and how `scan` and `clean` look for findings, and when they fail.
+#[derive(Args)]
+pub struct ScanArgs {
+    /// Search this directory for projects instead of the configured roots (repeatable)
+    #[arg(long = "root", value_name = "PATH")]
+    pub roots: Vec<PathBuf>,
+
+    /// Measure every directory afresh instead of reusing sizes from earlier scans
+    #[arg(long)]
+    pub no_cache: bool,
+
+    /// Only include items whose path or description contains TEXT as a whole
+    /// name, such as a model tag: llama3:8b but not llama3:8b-instruct (repeatable)
+    #[arg(long, value_name = "TEXT")]
+    pub matching: Vec<String>,
+
+    /// Exit with an error status if anything failed, or only failures of these
+    /// kinds (e.g. "--fail-on-error=permission_denied,in_use")
+    #[arg(
+        long,
+        value_name = "KINDS",
+        num_args = 0..=1,
+        require_equals = true,
+        value_delimiter = ','
+    )]
+    pub fail_on_error: Option<Vec<ErrorKind>>,
+}
+
+/// How `clean` deletes what it finds.
+#[derive(Args)]
+pub struct DeleteArgs {
+    /// Only show what would be deleted
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Move to trash instead of permanent delete (default)
+    #[arg(long, conflicts_with_all = ["permanent", "quarantine"])]
+    pub trash: bool,
+
+    /// Permanently delete files (irreversible)
+    #[arg(long, conflicts_with_all = ["trash", "quarantine"])]
+    pub permanent: bool,
+
+    /// Move into diskard's quarantine until purged
+    #[arg(long, conflicts_with_all = ["trash", "permanent"])]
+    pub quarantine: bool,
+
+    /// Run each item's own clean command, like `cargo clean`, where its tool
+    /// is installed; the rest are deleted as the other flags or config say
+    #[arg(long, conflicts_with = "dry_run")]
+    pub native: bool,
+
+    /// Permanently delete items the Trash refuses instead of leaving them
+    #[arg(long, conflicts_with_all = ["permanent", "quarantine", "dry_run"])]
+    pub permanent_fallback: bool,
+
+    /// Delete items even while running processes are using them
+    #[arg(long)]
+    pub allow_in_use: bool,
+
+    /// Skip confirmation prompt
+    #[arg(short = 'y', long)]
+    pub yes: bool,
+}
+
 #[derive(Subcommand)]
 pub enum ListCommand {
     /// List all available recognizers/targets
     Targets,
 }
 
+#[derive(Subcommand)]
+pub enum QuarantineAction {
+    /// List quarantined items
+    List,
+    /// Move a quarantined item back to its original path
+    Restore {
+        /// Quarantine ID (from `diskard quarantine list`) or original path of the item
+        target: String,
+    },
+    /// Permanently delete quarantined items
+    Purge {
+        /// Only purge items quarantined longer than duration (e.g., "7d", "2w")
+        #[arg(long)]
+        older_than: Option<String>,
+
+        /// Skip confirmation prompt
+        #[arg(short = 'y', long)]
+        yes: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum CacheAction {
+    /// Delete the scan cache so the next scan measures everything afresh
+    Clear,
+}
+
 #[derive(Subcommand)]
 pub enum ConfigAction {
     /// Show current configuration
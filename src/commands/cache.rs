@@ -0,0 +1,37 @@
+use anyhow::Result;
+use console::style;
+use diskard_core::cache::ScanCache;
+
+/// The scan cache to use, unless disabled with `--no-cache`.
+pub fn open(no_cache: bool) -> Option<ScanCache> {
+    if no_cache {
+        return None;
+    }
+    ScanCache::open_default()
+        .inspect_err(|e| log::warn!("{e}; scanning without cache"))
+        .ok()
+}
+
+/// Persist what a scan measured. A failure only costs the next scan time.
+pub fn save(cache: Option<&ScanCache>) {
+    if let Some(cache) = cache {
+        if let Err(e) = cache.save() {
+            log::warn!("Cannot save scan cache: {e}");
+        }
+    }
+}
+
+pub fn clear() -> Result<()> {
+    let cache = ScanCache::open_default()?;
+    let records = cache.len();
+    if cache.clear()? {
+        println!(
+            "{}  Cleared scan cache ({} directories)",
+            style("✓").green().bold(),
+            records,
+        );
+    } else {
+        println!("{}", style("Scan cache is already empty.").dim());
+    }
+    Ok(())
+}
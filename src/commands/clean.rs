@@ -1,48 +1,138 @@
 use anyhow::Result;
 use console::style;
-use diskard_core::cleaner::{self, DeleteMode};
+use diskard_core::cleaner::{self, CleanOptions, CleanResult, DeleteMode, InUse};
 use diskard_core::config::Config;
-use diskard_core::recognizers::all_recognizers;
+use diskard_core::error::ErrorKind;
+use diskard_core::finding::Finding;
+use diskard_core::journal::Journal;
+use diskard_core::processes;
+use diskard_core::recognizers;
 use diskard_core::scanner::{self, ScanOptions};
 use diskard_core::size::format_bytes;
 
-use crate::cli::{CategoryFilter, RiskFilter};
-use crate::commands::scan::parse_duration;
+use crate::cli::{CategoryFilter, DeleteArgs, OutputFormat, RiskFilter, ScanArgs};
+use crate::commands;
+use crate::commands::scan::{check_errors, override_roots, parse_duration};
+use crate::output;
+use crate::progress::{CleanSpinner, ScanSpinner};
+
+/// Pick the delete mode from the command-line flags, falling back to the
+/// configured `delete_mode` when none is given.
+fn delete_mode(args: &DeleteArgs) -> Option<DeleteMode> {
+    if args.dry_run {
+        Some(DeleteMode::DryRun)
+    } else if args.native {
+        Some(DeleteMode::Native)
+    } else {
+        filesystem_mode(args)
+    }
+}
+
+/// The way to delete files the flags ask for, which is also how `--native`
+/// deletes items it has no command for.
+fn filesystem_mode(args: &DeleteArgs) -> Option<DeleteMode> {
+    if args.permanent {
+        Some(DeleteMode::Permanent)
+    } else if args.quarantine {
+        Some(DeleteMode::Quarantine)
+    } else if args.trash {
+        Some(DeleteMode::Trash)
+    } else {
+        None
+    }
+}
 
 pub fn run(
-    dry_run: bool,
-    permanent: bool,
+    delete: DeleteArgs,
     risk: RiskFilter,
     category: Option<CategoryFilter>,
     older_than: Option<String>,
-    yes: bool,
+    scan: ScanArgs,
+    format: OutputFormat,
 ) -> Result<()> {
-    let config = Config::load()?;
-    let recognizers = all_recognizers();
+    let mut config = Config::load()?;
+    override_roots(&mut config, scan.roots)?;
+    let recognizers = recognizers::for_config(&config)?;
 
     let older_duration = match older_than {
         Some(s) => Some(parse_duration(&s)?),
         None => None,
     };
 
+    let mode = delete_mode(&delete).unwrap_or_else(|| config.delete_mode());
+    let native_fallback = filesystem_mode(&delete).unwrap_or_else(|| config.delete_mode());
+    let in_use = if delete.allow_in_use {
+        InUse::Warn
+    } else {
+        InUse::Refuse
+    };
+    if matches!(format, OutputFormat::Json) && mode != DeleteMode::DryRun && !delete.yes {
+        anyhow::bail!("JSON output can't prompt for confirmation; pass --yes or --dry-run");
+    }
+
+    let (spinner, on_event) = match format {
+        OutputFormat::Table => ScanSpinner::start().unzip(),
+        OutputFormat::Json => (None, None),
+    };
     let options = ScanOptions {
         max_risk: risk.to_risk_level(),
         min_size: config.defaults.min_size,
         category: category.map(|c| c.to_category()),
         older_than: older_duration,
+        matching: scan.matching,
+        on_event,
+        cache: commands::cache::open(scan.no_cache),
         ..Default::default()
     };
 
     let result = scanner::scan(&recognizers, &config, &options);
+    commands::cache::save(options.cache.as_ref());
+    if let Some(spinner) = spinner {
+        spinner.finish();
+    }
+
+    let fail_on = scan.fail_on_error.as_deref();
+    if matches!(format, OutputFormat::Json) {
+        let options = CleanOptions {
+            mode,
+            in_use,
+            trash_fallback: delete.permanent_fallback,
+            journal: Some(Journal::open_default()?),
+            native_fallback,
+            native_timeout: config.native_timeout(),
+            ..Default::default()
+        };
+        let clean_result = cleaner::clean_with(&result.findings, &options)?;
+        let output = serde_json::json!({
+            "mode": mode,
+            "deleted_count": clean_result.deleted_count,
+            "freed_bytes": clean_result.freed_bytes,
+            "freed_human": format_bytes(clean_result.freed_bytes),
+            "errors": clean_result.errors,
+            "warnings": clean_result.warnings,
+            "remaining": clean_result.remaining,
+            "scan_errors": result.errors,
+        });
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        let errors: Vec<_> = result
+            .errors
+            .into_iter()
+            .chain(clean_result.errors)
+            .collect();
+        return check_errors(fail_on, &errors);
+    }
 
     if result.findings.is_empty() {
         println!("{}", style("Nothing to clean.").dim());
-        return Ok(());
+        output::print_errors("errors during scan", &result.errors);
+        return check_errors(fail_on, &result.errors);
     }
 
-    // Show what will be cleaned
+    // Show what will be cleaned, and what running processes are using
+    let paths: Vec<_> = result.findings.iter().map(|f| f.path.as_path()).collect();
+    let users = processes::processes_using(&paths);
     println!("\n{}", style("Items to clean:").bold());
-    for (i, finding) in result.findings.iter().enumerate() {
+    for (i, (finding, users)) in result.findings.iter().zip(&users).enumerate() {
         println!(
             "  {}. {} {} — {}",
             i + 1,
@@ -51,6 +141,13 @@ pub fn run(
             finding.description,
         );
         println!("     {}", style(finding.path.display()).dim());
+        if !users.is_empty() {
+            println!(
+                "     {} in use by {}",
+                style("⚠").yellow(),
+                processes::describe(users),
+            );
+        }
     }
 
     println!(
@@ -59,47 +156,61 @@ pub fn run(
         style(format_bytes(result.total_reclaimable)).cyan().bold(),
     );
 
-    let mode = if dry_run {
-        DeleteMode::DryRun
-    } else if permanent {
-        DeleteMode::Permanent
-    } else {
-        DeleteMode::Trash
-    };
+    let busy = users.iter().filter(|u| !u.is_empty()).count();
+    if busy > 0 {
+        match in_use {
+            InUse::Refuse => println!(
+                "{}  {} items in use will be skipped (use {} to delete them anyway)",
+                style("⚠").yellow(),
+                busy,
+                style("--allow-in-use").bold(),
+            ),
+            InUse::Warn => println!(
+                "{}  {} items in use will be deleted anyway",
+                style("⚠").yellow(),
+                busy,
+            ),
+        }
+    }
+
+    output::print_errors("errors during scan", &result.errors);
 
     if mode == DeleteMode::DryRun {
         println!(
             "\n{}",
             style("Dry run — no files were deleted.").yellow().bold()
         );
-        return Ok(());
+        return check_errors(fail_on, &result.errors);
     }
 
     // Confirmation
-    if !yes {
-        let mode_label = if permanent {
-            "PERMANENTLY DELETE"
-        } else {
-            "move to Trash"
+    if !delete.yes {
+        let mode_label = match mode {
+            DeleteMode::Permanent => "PERMANENTLY DELETE",
+            DeleteMode::Quarantine => "quarantine",
+            DeleteMode::Native => "Run native clean commands for",
+            _ => "move to Trash",
         };
-        print!(
-            "\n{} {} these items? [y/N] ",
-            style("?").yellow().bold(),
-            mode_label,
-        );
-        use std::io::{self, Write};
-        io::stdout().flush()?;
-
-        let mut input = String::new();
-        io::stdin().read_line(&mut input)?;
-        if !input.trim().eq_ignore_ascii_case("y") {
+        if !confirm(&format!("{mode_label} these items?"))? {
             println!("{}", style("Cancelled.").dim());
             return Ok(());
         }
     }
 
     // Execute
-    let clean_result = cleaner::clean(&result.findings, mode)?;
+    let options = CleanOptions {
+        mode,
+        in_use,
+        trash_fallback: delete.permanent_fallback,
+        journal: Some(Journal::open_default()?),
+        native_fallback,
+        native_timeout: config.native_timeout(),
+        ..Default::default()
+    };
+    let mut clean_result = clean_with_progress(&result.findings, options.clone())?;
+    if mode == DeleteMode::Trash && !delete.permanent_fallback && !delete.yes {
+        offer_permanent_delete(&result.findings, &mut clean_result, &options)?;
+    }
 
     println!(
         "\n{}  Cleaned {} items, freed {}",
@@ -107,17 +218,127 @@ pub fn run(
         clean_result.deleted_count,
         style(format_bytes(clean_result.freed_bytes)).cyan().bold(),
     );
+    match mode {
+        DeleteMode::Trash => println!(
+            "    Undo with {} or {}",
+            style("diskard restore <id|path>").bold(),
+            style("diskard history").dim(),
+        ),
+        DeleteMode::Quarantine => println!(
+            "    Undo with {}, free the space with {}",
+            style("diskard restore <id|path>").bold(),
+            style("diskard quarantine purge --older-than 7d").bold(),
+        ),
+        _ => {}
+    }
 
-    if !clean_result.errors.is_empty() {
+    if !clean_result.warnings.is_empty() {
+        println!(
+            "\n{}  {} warnings:",
+            style("⚠").yellow(),
+            clean_result.warnings.len(),
+        );
+        for warning in &clean_result.warnings {
+            println!("    {}", style(warning).yellow());
+        }
+    }
+    if !clean_result.remaining.is_empty() {
         println!(
-            "\n{}  {} errors:",
+            "\n{}  {} items only partly removed:",
             style("⚠").yellow(),
-            clean_result.errors.len(),
+            clean_result.remaining.len(),
         );
-        for (path, err) in &clean_result.errors {
-            println!("    {} — {}", style(path).red(), err);
+        for left in &clean_result.remaining {
+            println!(
+                "    {} — freed {}, {} left in {} entries that could not be removed",
+                style(left.path.display()).yellow(),
+                format_bytes(left.freed_bytes),
+                style(format_bytes(left.remaining_bytes)).cyan(),
+                left.failed_entries,
+            );
         }
     }
+    output::print_errors("errors", &clean_result.errors);
+
+    let errors: Vec<_> = result
+        .errors
+        .into_iter()
+        .chain(clean_result.errors)
+        .collect();
+    check_errors(fail_on, &errors)
+}
+
+/// Clean with a progress line on the terminal.
+fn clean_with_progress(findings: &[Finding], mut options: CleanOptions) -> Result<CleanResult> {
+    let (spinner, on_event) = CleanSpinner::start().unzip();
+    options.on_event = on_event;
+    let result = cleaner::clean_with(findings, &options);
+    if let Some(spinner) = spinner {
+        spinner.finish();
+    }
+    Ok(result?)
+}
+
+/// Ask a yes/no question on the terminal; anything but "y" is no.
+fn confirm(question: &str) -> Result<bool> {
+    use std::io::{self, Write};
+
+    print!("\n{} {question} [y/N] ", style("?").yellow().bold());
+    io::stdout().flush()?;
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    Ok(input.trim().eq_ignore_ascii_case("y"))
+}
 
+/// Offer to permanently delete the findings the trash refused, with the
+/// rest of `options` unchanged, folding the outcome into `result`.
+fn offer_permanent_delete(
+    findings: &[Finding],
+    result: &mut CleanResult,
+    options: &CleanOptions,
+) -> Result<()> {
+    let refused: Vec<Finding> = findings
+        .iter()
+        .filter(|f| {
+            result
+                .errors
+                .iter()
+                .any(|e| e.kind == ErrorKind::TrashUnavailable && e.path.as_ref() == Some(&f.path))
+        })
+        .cloned()
+        .collect();
+    if refused.is_empty() {
+        return Ok(());
+    }
+
+    println!(
+        "\n{}  {} items could not be moved to the Trash:",
+        style("⚠").yellow(),
+        refused.len(),
+    );
+    for finding in &refused {
+        println!(
+            "    {} {}",
+            style(finding.size_human()).cyan(),
+            style(finding.path.display()).dim(),
+        );
+    }
+    if !confirm("PERMANENTLY DELETE them instead?")? {
+        return Ok(());
+    }
+
+    let options = CleanOptions {
+        mode: DeleteMode::Permanent,
+        ..options.clone()
+    };
+    let fallback = clean_with_progress(&refused, options)?;
+    result
+        .errors
+        .retain(|e| !refused.iter().any(|f| e.path.as_ref() == Some(&f.path)));
+    result.deleted_count += fallback.deleted_count;
+    result.freed_bytes += fallback.freed_bytes;
+    result.errors.extend(fallback.errors);
+    result.warnings.extend(fallback.warnings);
+    result.remaining.extend(fallback.remaining);
     Ok(())
 }
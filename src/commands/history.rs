@@ -0,0 +1,77 @@
+use anyhow::Result;
+use console::style;
+use diskard_core::journal::{self, Journal};
+use diskard_core::quarantine::Quarantine;
+use diskard_core::size::format_bytes;
+
+use crate::cli::OutputFormat;
+
+pub fn history(format: OutputFormat) -> Result<()> {
+    let journal = Journal::open_default()?;
+    let entries = journal.entries()?;
+
+    if let OutputFormat::Json = format {
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+        return Ok(());
+    }
+
+    if entries.is_empty() {
+        println!("{}", style("Nothing has been cleaned yet.").dim());
+        return Ok(());
+    }
+
+    println!(
+        "\n{:>5}  {:<16}  {:<10}  {:>10}  {}",
+        style("ID").bold().underlined(),
+        style("WHEN").bold().underlined(),
+        style("MODE").bold().underlined(),
+        style("SIZE").bold().underlined(),
+        style("PATH").bold().underlined(),
+    );
+
+    for entry in entries.iter().rev() {
+        let status = if entry.restored_at.is_some() {
+            style("restored").green()
+        } else if entry.purged_at.is_some() {
+            style("purged").dim()
+        } else if entry.is_restorable() {
+            style("restorable").cyan()
+        } else {
+            style("gone").dim()
+        };
+        println!(
+            "{:>5}  {:<16}  {:<10}  {:>10}  {} {}",
+            entry.id,
+            entry
+                .timestamp
+                .with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M"),
+            entry.mode,
+            style(format_bytes(entry.size_bytes)).cyan(),
+            entry.path.display(),
+            status,
+        );
+    }
+
+    println!(
+        "\n  Restore with {}",
+        style("diskard restore <id|path>").bold()
+    );
+    Ok(())
+}
+
+pub fn restore(target: &str) -> Result<()> {
+    let journal = Journal::open_default()?;
+    let Some(entry) = journal.find(target)? else {
+        anyhow::bail!("No restorable journal entry matches '{target}'");
+    };
+
+    journal::restore(&journal, &Quarantine::open_default()?, &entry)?;
+    println!(
+        "{}  Restored {} ({})",
+        style("✓").green().bold(),
+        style(entry.path.display()).cyan(),
+        format_bytes(entry.size_bytes),
+    );
+    Ok(())
+}
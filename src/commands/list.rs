@@ -1,11 +1,11 @@
 use anyhow::Result;
 use console::style;
 use diskard_core::config::Config;
-use diskard_core::recognizers::all_recognizers;
+use diskard_core::recognizers;
 
 pub fn targets() -> Result<()> {
     let config = Config::load()?;
-    let recognizers = all_recognizers();
+    let recognizers = recognizers::for_config(&config)?;
 
     println!("\n{}", style("Available recognizers:").bold());
     println!(
@@ -24,13 +24,20 @@ pub fn targets() -> Result<()> {
             style("○").red()
         };
 
+        let custom = if config.custom.iter().any(|c| c.id == r.id()) {
+            style(" (custom)").dim()
+        } else {
+            style("")
+        };
+
         println!(
-            "  {:>3}  {:<25}  {:<12}  {} {}",
+            "  {:>3}  {:<25}  {:<12}  {} {}{}",
             i + 1,
             r.id(),
             r.category(),
             r.name(),
             status,
+            custom,
         );
     }
 
@@ -43,6 +50,10 @@ pub fn targets() -> Result<()> {
         "  Disable recognizers in {}",
         style("~/.config/diskard/config.toml").dim(),
     );
+    println!(
+        "  Add your own with {} tables in the same file",
+        style("[[custom]]").dim(),
+    );
 
     Ok(())
 }
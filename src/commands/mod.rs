@@ -1,4 +1,7 @@
+pub mod cache;
 pub mod clean;
 pub mod config;
+pub mod history;
 pub mod list;
+pub mod quarantine;
 pub mod scan;
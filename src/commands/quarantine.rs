@@ -0,0 +1,131 @@
+use anyhow::Result;
+use console::style;
+use diskard_core::journal::Journal;
+use diskard_core::quarantine::Quarantine;
+use diskard_core::size::format_bytes;
+
+use crate::cli::OutputFormat;
+use crate::commands::scan::parse_duration;
+use crate::output;
+
+pub fn list(format: OutputFormat) -> Result<()> {
+    let quarantine = Quarantine::open_default()?;
+    let entries = quarantine.entries()?;
+
+    if let OutputFormat::Json = format {
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+        return Ok(());
+    }
+
+    if entries.is_empty() {
+        println!("{}", style("Quarantine is empty.").dim());
+        return Ok(());
+    }
+
+    println!(
+        "\n{:>5}  {:<16}  {:>10}  {}",
+        style("ID").bold().underlined(),
+        style("QUARANTINED").bold().underlined(),
+        style("SIZE").bold().underlined(),
+        style("ORIGINAL PATH").bold().underlined(),
+    );
+
+    for entry in entries.iter().rev() {
+        println!(
+            "{:>5}  {:<16}  {:>10}  {}",
+            entry.id,
+            entry
+                .quarantined_at
+                .with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M"),
+            style(format_bytes(entry.size_bytes)).cyan(),
+            entry.original_path.display(),
+        );
+    }
+
+    let total: u64 = entries.iter().map(|e| e.size_bytes).sum();
+    println!(
+        "\n{}  {} items holding {}",
+        style("==>").green().bold(),
+        entries.len(),
+        style(format_bytes(total)).cyan().bold(),
+    );
+    println!(
+        "    Restore with {}, free the space with {}",
+        style("diskard quarantine restore <id|path>").bold(),
+        style("diskard quarantine purge --older-than 7d").bold(),
+    );
+    Ok(())
+}
+
+pub fn restore(target: &str) -> Result<()> {
+    let quarantine = Quarantine::open_default()?;
+    let Some(entry) = quarantine.find(target)? else {
+        anyhow::bail!("No quarantined item matches '{target}'");
+    };
+
+    quarantine.restore(&entry)?;
+
+    // Keep `diskard history` in step with the quarantine
+    let journal = Journal::open_default()?;
+    if let Some(recorded) = journal.find_by_location(&entry.stored_path)? {
+        journal.mark_restored(recorded.id)?;
+    }
+
+    println!(
+        "{}  Restored {} ({})",
+        style("✓").green().bold(),
+        style(entry.original_path.display()).cyan(),
+        format_bytes(entry.size_bytes),
+    );
+    Ok(())
+}
+
+pub fn purge(older_than: Option<String>, yes: bool) -> Result<()> {
+    let older_duration = match older_than {
+        Some(s) => parse_duration(&s)?,
+        None => std::time::Duration::ZERO,
+    };
+
+    let quarantine = Quarantine::open_default()?;
+    let expired: Vec<_> = quarantine
+        .entries()?
+        .into_iter()
+        .filter(|e| e.age() >= older_duration)
+        .collect();
+
+    if expired.is_empty() {
+        println!("{}", style("Nothing to purge.").dim());
+        return Ok(());
+    }
+
+    let total: u64 = expired.iter().map(|e| e.size_bytes).sum();
+    if !yes {
+        print!(
+            "\n{} PERMANENTLY DELETE {} quarantined items ({})? [y/N] ",
+            style("?").yellow().bold(),
+            expired.len(),
+            format_bytes(total),
+        );
+        use std::io::{self, Write};
+        io::stdout().flush()?;
+
+        let mut input = String::new();
+        io::stdin().read_line(&mut input)?;
+        if !input.trim().eq_ignore_ascii_case("y") {
+            println!("{}", style("Cancelled.").dim());
+            return Ok(());
+        }
+    }
+
+    let result = quarantine.purge(older_duration, Some(&Journal::open_default()?))?;
+    println!(
+        "\n{}  Purged {} items, freed {}",
+        style("✓").green().bold(),
+        result.purged_count,
+        style(format_bytes(result.freed_bytes)).cyan().bold(),
+    );
+    output::print_errors("errors", &result.errors);
+
+    Ok(())
+}
@@ -1,10 +1,15 @@
 use anyhow::Result;
-use diskard_core::config::Config;
-use diskard_core::recognizers::all_recognizers;
+use diskard_core::config::{self, Config};
+use diskard_core::error::{ErrorKind, ErrorRecord};
+use diskard_core::recognizers;
 use diskard_core::scanner::{self, ScanOptions};
+use diskard_core::size;
+use std::path::PathBuf;
 
-use crate::cli::{CategoryFilter, OutputFormat, RiskFilter, SortField};
+use crate::cli::{CategoryFilter, OutputFormat, RiskFilter, ScanArgs, SortField};
+use crate::commands;
 use crate::output;
+use crate::progress::ScanSpinner;
 
 pub fn run(
     risk: RiskFilter,
@@ -12,13 +17,15 @@ pub fn run(
     category: Option<CategoryFilter>,
     sort: SortField,
     older_than: Option<String>,
+    scan: ScanArgs,
     format: OutputFormat,
 ) -> Result<()> {
-    let config = Config::load()?;
-    let recognizers = all_recognizers();
+    let mut config = Config::load()?;
+    override_roots(&mut config, scan.roots)?;
+    let recognizers = recognizers::for_config(&config)?;
 
     let min_size_bytes = match min_size {
-        Some(s) => parse_size(&s)?,
+        Some(s) => size::parse_size(&s).map_err(anyhow::Error::msg)?,
         None => config.defaults.min_size,
     };
 
@@ -27,68 +34,65 @@ pub fn run(
         None => None,
     };
 
+    let (spinner, on_event) = match format {
+        OutputFormat::Table => ScanSpinner::start().unzip(),
+        OutputFormat::Json => (None, None),
+    };
+
     let options = ScanOptions {
         max_risk: risk.to_risk_level(),
         min_size: min_size_bytes,
         category: category.map(|c| c.to_category()),
         older_than: older_duration,
+        matching: scan.matching,
         sort: sort.to_sort_order(),
+        on_event,
+        cache: commands::cache::open(scan.no_cache),
+        ..Default::default()
     };
 
     let result = scanner::scan(&recognizers, &config, &options);
+    commands::cache::save(options.cache.as_ref());
+    if let Some(spinner) = spinner {
+        spinner.finish();
+    }
 
     match format {
         OutputFormat::Table => output::print_table(&result),
         OutputFormat::Json => output::print_json(&result),
     }
 
-    Ok(())
+    check_errors(scan.fail_on_error.as_deref(), &result.errors)
 }
 
-pub fn parse_size(s: &str) -> Result<u64> {
-    let s = s.trim().to_uppercase();
-
-    if let Ok(n) = s.parse::<u64>() {
-        return Ok(n);
-    }
-
-    let (num_str, multiplier) = if let Some(n) = s.strip_suffix("GB") {
-        (n.trim(), 1_073_741_824u64)
-    } else if let Some(n) = s.strip_suffix("MB") {
-        (n.trim(), 1_048_576u64)
-    } else if let Some(n) = s.strip_suffix("KB") {
-        (n.trim(), 1_024u64)
-    } else if let Some(n) = s.strip_suffix('B') {
-        (n.trim(), 1u64)
-    } else {
-        anyhow::bail!("Invalid size format: {s}. Use e.g. 10MB, 1GB");
+/// Fail if `--fail-on-error` was given and any of `errors` are of the kinds
+/// it names (all kinds when it names none).
+pub fn check_errors(fail_on: Option<&[ErrorKind]>, errors: &[ErrorRecord]) -> Result<()> {
+    let Some(kinds) = fail_on else {
+        return Ok(());
     };
+    let failed = errors
+        .iter()
+        .filter(|e| kinds.is_empty() || kinds.contains(&e.kind))
+        .count();
+    if failed > 0 {
+        anyhow::bail!("{failed} errors occurred");
+    }
+    Ok(())
+}
 
-    let num: f64 = num_str
-        .parse()
-        .map_err(|_| anyhow::anyhow!("Invalid size number: {num_str}"))?;
-
-    Ok((num * multiplier as f64) as u64)
+/// Replace the configured project roots with those given on the command line.
+pub fn override_roots(config: &mut Config, roots: Vec<PathBuf>) -> Result<()> {
+    if roots.is_empty() {
+        return Ok(());
+    }
+    config.scan.roots = roots
+        .iter()
+        .map(std::path::absolute)
+        .collect::<std::io::Result<_>>()?;
+    Ok(())
 }
 
 pub fn parse_duration(s: &str) -> Result<std::time::Duration> {
-    let s = s.trim().to_lowercase();
-
-    let (num_str, multiplier) = if let Some(n) = s.strip_suffix('d') {
-        (n, 86_400u64)
-    } else if let Some(n) = s.strip_suffix('h') {
-        (n, 3_600u64)
-    } else if let Some(n) = s.strip_suffix('m') {
-        (n, 60u64)
-    } else if let Some(n) = s.strip_suffix('w') {
-        (n, 604_800u64)
-    } else {
-        anyhow::bail!("Invalid duration: {s}. Use e.g. 7d, 30d, 1h, 2w");
-    };
-
-    let num: u64 = num_str
-        .parse()
-        .map_err(|_| anyhow::anyhow!("Invalid duration number: {num_str}"))?;
-
-    Ok(std::time::Duration::from_secs(num * multiplier))
+    config::parse_duration(s).map_err(anyhow::Error::msg)
 }
@@ -1,9 +1,10 @@
 mod cli;
 mod commands;
 mod output;
+mod progress;
 
 use clap::{CommandFactory, Parser};
-use cli::{Cli, Command, ConfigAction, ListCommand};
+use cli::{CacheAction, Cli, Command, ConfigAction, ListCommand, QuarantineAction};
 
 fn main() -> anyhow::Result<()> {
     let cli = Cli::parse();
@@ -17,7 +18,7 @@ fn main() -> anyhow::Result<()> {
     // Handle `diskard -i` shortcut
     #[cfg(feature = "tui")]
     if cli.interactive {
-        return run_interactive(None, None);
+        return run_interactive(None, None, false);
     }
 
     let Some(command) = cli.command else {
@@ -32,20 +33,31 @@ fn main() -> anyhow::Result<()> {
             category,
             sort,
             older_than,
+            scan,
         } => {
-            commands::scan::run(risk, min_size, category, sort, older_than, cli.format)?;
+            commands::scan::run(risk, min_size, category, sort, older_than, scan, cli.format)?;
         }
         Command::Clean {
-            dry_run,
-            permanent,
+            delete,
             risk,
             category,
             older_than,
-            yes,
-            ..
+            scan,
         } => {
-            commands::clean::run(dry_run, permanent, risk, category, older_than, yes)?;
+            commands::clean::run(delete, risk, category, older_than, scan, cli.format)?;
         }
+        Command::History => commands::history::history(cli.format)?,
+        Command::Restore { target } => commands::history::restore(&target)?,
+        Command::Quarantine { action } => match action {
+            QuarantineAction::List => commands::quarantine::list(cli.format)?,
+            QuarantineAction::Restore { target } => commands::quarantine::restore(&target)?,
+            QuarantineAction::Purge { older_than, yes } => {
+                commands::quarantine::purge(older_than, yes)?
+            }
+        },
+        Command::Cache { action } => match action {
+            CacheAction::Clear => commands::cache::clear()?,
+        },
         Command::List { what } => match what {
             ListCommand::Targets => commands::list::targets()?,
         },
@@ -55,8 +67,12 @@ fn main() -> anyhow::Result<()> {
             ConfigAction::Path => commands::config::path()?,
         },
         #[cfg(feature = "tui")]
-        Command::Interactive { risk, category } => {
-            run_interactive(Some(risk), category)?;
+        Command::Interactive {
+            risk,
+            category,
+            no_cache,
+        } => {
+            run_interactive(Some(risk), category, no_cache)?;
         }
         Command::Completions { shell } => {
             let mut cmd = Cli::command();
@@ -71,23 +87,33 @@ fn main() -> anyhow::Result<()> {
 fn run_interactive(
     risk: Option<cli::RiskFilter>,
     category: Option<cli::CategoryFilter>,
+    no_cache: bool,
 ) -> anyhow::Result<()> {
     use diskard_core::config::Config;
-    use diskard_core::recognizers::all_recognizers;
+    use diskard_core::recognizers;
     use diskard_core::scanner::{self, ScanOptions};
+    use std::sync::{mpsc, Arc};
 
     let config = Config::load()?;
-    let recognizers = all_recognizers();
+    let recognizers = recognizers::for_config(&config)?;
+    let (sender, receiver) = mpsc::channel();
     let options = ScanOptions {
         max_risk: risk
             .map(|r| r.to_risk_level())
             .unwrap_or(diskard_core::finding::RiskLevel::Moderate),
         category: category.map(|c| c.to_category()),
+        on_event: Some(Arc::new(move |event| {
+            let _ = sender.send(event);
+        })),
+        cache: commands::cache::open(no_cache),
         ..Default::default()
     };
 
-    println!("Scanning...");
-    let result = scanner::scan(&recognizers, &config, &options);
-    diskard_tui::run(result.findings)?;
+    // The sender lives in `options`, so the TUI sees the channel close when the scan ends.
+    std::thread::spawn(move || {
+        scanner::scan(&recognizers, &config, &options);
+        commands::cache::save(options.cache.as_ref());
+    });
+    diskard_tui::run_streaming(receiver)?;
     Ok(())
 }
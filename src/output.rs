@@ -1,4 +1,5 @@
 use console::style;
+use diskard_core::error::ErrorRecord;
 use diskard_core::finding::RiskLevel;
 use diskard_core::scanner::ScanResult;
 use diskard_core::size::format_bytes;
@@ -7,6 +8,8 @@ use diskard_core::size::format_bytes;
 pub fn print_table(result: &ScanResult) {
     if result.findings.is_empty() {
         println!("{}", style("No reclaimable space found.").dim());
+        print_excluded(result);
+        print_errors("errors during scan", &result.errors);
         return;
     }
 
@@ -41,6 +44,20 @@ pub fn print_table(result: &ScanResult) {
             "",
             style(finding.path.display()).dim(),
         );
+        // Sparse files look much larger than the space they free
+        if finding.apparent_bytes > finding.size_bytes + finding.size_bytes / 4 {
+            println!(
+                "{:>10}  {:>8}  {:<20}  {}",
+                "",
+                "",
+                "",
+                style(format!(
+                    "({} apparent, mostly sparse)",
+                    format_bytes(finding.apparent_bytes)
+                ))
+                .dim(),
+            );
+        }
     }
 
     // Summary
@@ -50,21 +67,64 @@ pub fn print_table(result: &ScanResult) {
         style("==>").green().bold(),
         style(format_bytes(result.total_reclaimable)).cyan().bold(),
     );
+    let width = result
+        .mounts
+        .iter()
+        .map(|m| m.mount_point.display().to_string().len())
+        .max()
+        .unwrap_or(0);
+    for mount in &result.mounts {
+        println!(
+            "     {:<width$}  {:>10} reclaimable  {} free of {}",
+            mount.mount_point.display(),
+            style(format_bytes(mount.reclaimable_bytes)).cyan(),
+            format_bytes(mount.free_bytes),
+            format_bytes(mount.total_bytes),
+        );
+    }
     println!(
         "{}  Scanned in {:.1}s",
         style("==>").green().bold(),
         result.scan_duration.as_secs_f64(),
     );
 
-    if !result.errors.is_empty() {
+    print_excluded(result);
+
+    print_errors("errors during scan", &result.errors);
+}
+
+/// List errors under a heading such as "errors during scan", if any.
+pub fn print_errors(heading: &str, errors: &[ErrorRecord]) {
+    if errors.is_empty() {
+        return;
+    }
+    println!("\n{}  {} {heading}:", style("⚠").yellow(), errors.len());
+    for err in errors {
         println!(
-            "\n{}  {} errors during scan:",
-            style("⚠").yellow(),
-            result.errors.len(),
+            "    {} {}",
+            style(err).red(),
+            style(format!("[{}]", err.kind)).dim()
+        );
+    }
+}
+
+/// List findings left out because their project is active.
+fn print_excluded(result: &ScanResult) {
+    if result.excluded.is_empty() {
+        return;
+    }
+    println!(
+        "\n{}  {} in active projects left out:",
+        style("🛡").cyan(),
+        result.excluded.len(),
+    );
+    for excluded in &result.excluded {
+        println!(
+            "    {:>10}  {}  {}",
+            format_bytes(excluded.finding.size_bytes),
+            excluded.finding.path.display(),
+            style(&excluded.reason).dim(),
         );
-        for err in &result.errors {
-            println!("    {}", style(err).red());
-        }
     }
 }
 
@@ -74,8 +134,10 @@ pub fn print_json(result: &ScanResult) {
         "findings": result.findings,
         "total_reclaimable_bytes": result.total_reclaimable,
         "total_reclaimable_human": format_bytes(result.total_reclaimable),
+        "mounts": result.mounts,
         "scan_duration_ms": result.scan_duration.as_millis(),
         "errors": result.errors,
+        "excluded": result.excluded,
     });
     println!("{}", serde_json::to_string_pretty(&output).unwrap());
 }
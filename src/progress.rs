@@ -0,0 +1,206 @@
+use console::{style, Term};
+use diskard_core::cleaner::{CleanEvent, CleanEventHandler};
+use diskard_core::scanner::{EventHandler, ScanEvent};
+use diskard_core::size::format_bytes;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+const FRAMES: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
+/// Redraws one line on stderr from shared state until stopped.
+struct Ticker {
+    done: Arc<AtomicBool>,
+    ticker: Option<JoinHandle<()>>,
+}
+
+impl Ticker {
+    /// Start drawing `line(state, frame)`. Returns `None` when stderr is not
+    /// a terminal.
+    fn start<S: Send + 'static>(
+        state: Arc<Mutex<S>>,
+        line: fn(&S, &str) -> String,
+    ) -> Option<Self> {
+        let term = Term::stderr();
+        if !term.is_term() {
+            return None;
+        }
+
+        let done = Arc::new(AtomicBool::new(false));
+        let ticker = {
+            let done = Arc::clone(&done);
+            std::thread::spawn(move || {
+                let mut frame = 0;
+                while !done.load(Ordering::Relaxed) {
+                    if let Ok(state) = state.lock() {
+                        frame += 1;
+                        let spinner = style(FRAMES[frame % FRAMES.len()]).cyan().to_string();
+                        let line = line(&state, &spinner);
+                        let width = term.size().1 as usize;
+                        let _ = term.clear_line();
+                        let _ = term.write_str(&console::truncate_str(&line, width, "…"));
+                    }
+                    std::thread::sleep(Duration::from_millis(80));
+                }
+                let _ = term.clear_line();
+            })
+        };
+
+        Some(Self {
+            done,
+            ticker: Some(ticker),
+        })
+    }
+
+    fn stop(&mut self) {
+        self.done.store(true, Ordering::Relaxed);
+        if let Some(ticker) = self.ticker.take() {
+            let _ = ticker.join();
+        }
+    }
+}
+
+impl Drop for Ticker {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// A one-line spinner on stderr showing which recognizers are still running.
+pub struct ScanSpinner {
+    ticker: Ticker,
+}
+
+#[derive(Default)]
+struct ScanState {
+    total: usize,
+    finished: usize,
+    found_bytes: u64,
+    running: Vec<&'static str>,
+}
+
+impl ScanSpinner {
+    /// Start the spinner, returning it with the event handler that feeds it.
+    /// Returns `None` when stderr is not a terminal.
+    pub fn start() -> Option<(Self, EventHandler)> {
+        let state = Arc::new(Mutex::new(ScanState::default()));
+        let ticker = Ticker::start(Arc::clone(&state), ScanState::line)?;
+
+        let handler: EventHandler = Arc::new(move |event| {
+            let Ok(mut state) = state.lock() else {
+                return;
+            };
+            match event {
+                ScanEvent::Started { recognizers } => state.total = recognizers,
+                ScanEvent::RecognizerStarted { name, .. } => state.running.push(name),
+                ScanEvent::FindingDiscovered { total_bytes, .. } => {
+                    state.found_bytes = total_bytes;
+                }
+                ScanEvent::RecognizerFinished { name, .. }
+                | ScanEvent::RecognizerFailed { name, .. } => {
+                    state.running.retain(|running| *running != name);
+                    state.finished += 1;
+                }
+            }
+        });
+
+        Some((Self { ticker }, handler))
+    }
+
+    /// Stop the spinner and erase its line.
+    pub fn finish(mut self) {
+        self.ticker.stop();
+    }
+}
+
+impl ScanState {
+    fn line(&self, frame: &str) -> String {
+        let mut line = format!(
+            "{frame} Scanning {} {} found",
+            style(format!("[{}/{}]", self.finished, self.total)).dim(),
+            style(format_bytes(self.found_bytes)).cyan(),
+        );
+        if !self.running.is_empty() {
+            line.push_str(&format!(
+                " {} {}",
+                style("—").dim(),
+                style(self.running.join(", ")).dim()
+            ));
+        }
+        line
+    }
+}
+
+/// A one-line spinner on stderr counting what a clean has removed.
+pub struct CleanSpinner {
+    ticker: Ticker,
+}
+
+#[derive(Default)]
+struct CleanState {
+    total: usize,
+    finished: usize,
+    total_bytes: u64,
+    files_removed: u64,
+    bytes_freed: u64,
+}
+
+impl CleanSpinner {
+    /// Start the spinner, returning it with the event handler that feeds it.
+    /// Returns `None` when stderr is not a terminal.
+    pub fn start() -> Option<(Self, CleanEventHandler)> {
+        let state = Arc::new(Mutex::new(CleanState::default()));
+        let ticker = Ticker::start(Arc::clone(&state), CleanState::line)?;
+
+        let handler: CleanEventHandler = Arc::new(move |event| {
+            let Ok(mut state) = state.lock() else {
+                return;
+            };
+            match event {
+                CleanEvent::Started {
+                    findings,
+                    total_bytes,
+                } => {
+                    state.total = findings;
+                    state.total_bytes = total_bytes;
+                }
+                CleanEvent::Progress {
+                    files_removed,
+                    bytes_freed,
+                } => {
+                    // Events from several threads may arrive out of order
+                    state.files_removed = state.files_removed.max(files_removed);
+                    state.bytes_freed = state.bytes_freed.max(bytes_freed);
+                }
+                CleanEvent::FindingFinished { .. } => state.finished += 1,
+            }
+        });
+
+        Some((Self { ticker }, handler))
+    }
+
+    /// Stop the spinner and erase its line.
+    pub fn finish(mut self) {
+        self.ticker.stop();
+    }
+}
+
+impl CleanState {
+    fn line(&self, frame: &str) -> String {
+        let mut line = format!(
+            "{frame} Cleaning {} {} of {} freed",
+            style(format!("[{}/{}]", self.finished, self.total)).dim(),
+            style(format_bytes(self.bytes_freed)).cyan(),
+            format_bytes(self.total_bytes),
+        );
+        if self.files_removed > 0 {
+            line.push_str(&format!(
+                " {} {}",
+                style("—").dim(),
+                style(format!("{} files removed", self.files_removed)).dim()
+            ));
+        }
+        line
+    }
+}
@@ -1,12 +1,21 @@
-use diskard_core::cleaner::{self, DeleteMode};
-use diskard_core::config::{Config, IgnoreConfig, RecognizerConfig};
-use diskard_core::error::Result;
-use diskard_core::finding::{Category, Finding, RiskLevel};
-use diskard_core::recognizer::Recognizer;
-use diskard_core::recognizers::all_recognizers;
-use diskard_core::scanner::{self, ScanOptions, SortOrder};
+use diskard_core::cache::ScanCache;
+use diskard_core::cleaner::{self, CleanEvent, CleanOptions, DeleteMode, InUse};
+use diskard_core::config::{
+    ActiveProtection, Config, IgnoreConfig, RecognizerConfig, RecognizerSettings,
+};
+use diskard_core::error::{ErrorKind, Result};
+use diskard_core::finding::{Category, Finding, NativeAction, RiskLevel};
+use diskard_core::journal::{self, Journal};
+use diskard_core::paths::{Locations, Platform};
+use diskard_core::processes;
+use diskard_core::project::ProjectDetector;
+use diskard_core::quarantine::Quarantine;
+use diskard_core::recognizer::{Recognizer, ScanContext};
+use diskard_core::recognizers::{self, all_recognizers};
+use diskard_core::scanner::{self, ScanEvent, ScanOptions, SortOrder};
 use diskard_core::size;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, SystemTime};
 use tempfile::TempDir;
 
@@ -25,10 +34,15 @@ fn make_finding(
     Finding {
         path,
         category,
+        recognizer_id: "test",
         risk,
         size_bytes: size,
+        apparent_bytes: size,
         description: format!("test finding ({category})"),
         last_modified,
+        native: None,
+        extra_paths: Vec::new(),
+        projects: Vec::new(),
     }
 }
 
@@ -50,7 +64,7 @@ impl Recognizer for FakeRecognizer {
     fn category(&self) -> Category {
         self.category
     }
-    fn scan(&self) -> Result<Vec<Finding>> {
+    fn scan(&self, _ctx: &ScanContext) -> Result<Vec<Finding>> {
         Ok(self.findings.clone())
     }
 }
@@ -73,6 +87,70 @@ fn fake_recognizers(findings: Vec<(Category, Vec<Finding>)>) -> Vec<Box<dyn Reco
         .collect()
 }
 
+/// Run the built-in recognizer with `id` against `locations`.
+fn scan_recognizer(id: &str, locations: Locations) -> Vec<Finding> {
+    scan_recognizer_with(id, locations, Config::default())
+}
+
+/// Run the built-in recognizer with `id` against `locations` and `config`.
+fn scan_recognizer_with(id: &str, locations: Locations, config: Config) -> Vec<Finding> {
+    let recognizers: Vec<Box<dyn Recognizer>> = all_recognizers()
+        .into_iter()
+        .filter(|r| r.id() == id)
+        .collect();
+    assert_eq!(recognizers.len(), 1, "no recognizer with id {id}");
+    let ctx = ScanContext::new(locations, config);
+    let result = scanner::scan_with_context(&recognizers, &ctx, &ScanOptions::default());
+    assert!(result.errors.is_empty(), "scan errors: {:?}", result.errors);
+    result.findings
+}
+
+/// A config whose project roots are `roots`, searched `max_depth` levels deep.
+fn config_with_roots(roots: &[&str], max_depth: usize) -> Config {
+    let mut config = Config::default();
+    config.scan.roots = roots.iter().map(PathBuf::from).collect();
+    config.scan.max_depth = max_depth;
+    config
+}
+
+/// Create `dir` under `root` containing a single file of `len` bytes.
+fn populate(root: &Path, dir: &str, len: usize) -> PathBuf {
+    let path = root.join(dir);
+    std::fs::create_dir_all(&path).unwrap();
+    std::fs::write(path.join("blob"), vec![0u8; len]).unwrap();
+    path
+}
+
+/// Backdate the modification and access times of `path` and everything under it.
+fn backdate(path: &Path, age: Duration) {
+    let when = SystemTime::now() - age;
+    let times = std::fs::FileTimes::new()
+        .set_accessed(when)
+        .set_modified(when);
+    for entry in walkdir(path) {
+        std::fs::File::open(&entry)
+            .unwrap()
+            .set_times(times)
+            .unwrap();
+    }
+}
+
+/// `path` and everything under it, deepest first.
+fn walkdir(path: &Path) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    if path.is_dir() {
+        for entry in std::fs::read_dir(path).unwrap() {
+            paths.extend(walkdir(&entry.unwrap().path()));
+        }
+    }
+    paths.push(path.to_path_buf());
+    paths
+}
+
+fn finding_paths(findings: &[Finding]) -> Vec<PathBuf> {
+    findings.iter().map(|f| f.path.clone()).collect()
+}
+
 // ---------------------------------------------------------------------------
 // Recognizer registry tests
 // ---------------------------------------------------------------------------
@@ -102,386 +180,3004 @@ fn test_recognizer_names_nonempty() {
 }
 
 // ---------------------------------------------------------------------------
-// Scanner tests (with fake recognizers)
+// Platform path tests (temp home directory)
 // ---------------------------------------------------------------------------
 
 #[test]
-fn test_scanner_runs_without_panic() {
-    let recognizers = all_recognizers();
-    let config = Config::default();
-    let options = ScanOptions::default();
-    let result = scanner::scan(&recognizers, &config, &options);
-    assert!(result.scan_duration.as_nanos() > 0);
+fn test_pip_cache_linux_xdg_default() {
+    let home = TempDir::new().unwrap();
+    let pip = populate(home.path(), ".cache/pip", 100);
+    populate(home.path(), "Library/Caches/pip", 100);
+
+    let findings = scan_recognizer("pip-cache", Locations::new(home.path(), Platform::Linux));
+    assert_eq!(finding_paths(&findings), vec![pip]);
 }
 
 #[test]
-fn test_scanner_respects_risk_filter() {
-    let findings = vec![
-        make_finding("/tmp/a".into(), Category::Node, RiskLevel::Safe, 100, None),
-        make_finding(
-            "/tmp/b".into(),
-            Category::Node,
-            RiskLevel::Moderate,
-            200,
-            None,
-        ),
-        make_finding("/tmp/c".into(), Category::Node, RiskLevel::Risky, 300, None),
-    ];
-    let recs = fake_recognizers(vec![(Category::Node, findings)]);
-    let config = Config::default();
-
-    // Filter: safe only
-    let opts = ScanOptions {
-        max_risk: RiskLevel::Safe,
-        ..Default::default()
-    };
-    let result = scanner::scan(&recs, &config, &opts);
-    assert_eq!(result.findings.len(), 1);
-    assert_eq!(result.findings[0].risk, RiskLevel::Safe);
+fn test_pip_cache_macos_library_caches() {
+    let home = TempDir::new().unwrap();
+    let pip = populate(home.path(), "Library/Caches/pip", 100);
+    populate(home.path(), ".cache/pip", 100);
 
-    // Filter: up to moderate
-    let opts = ScanOptions {
-        max_risk: RiskLevel::Moderate,
-        ..Default::default()
-    };
-    let result = scanner::scan(&recs, &config, &opts);
-    assert_eq!(result.findings.len(), 2);
-    for f in &result.findings {
-        assert!(f.risk <= RiskLevel::Moderate);
-    }
+    let findings = scan_recognizer("pip-cache", Locations::new(home.path(), Platform::MacOs));
+    assert_eq!(finding_paths(&findings), vec![pip]);
 }
 
 #[test]
-fn test_scanner_min_size_filter() {
-    let findings = vec![
-        make_finding(
-            "/tmp/small".into(),
-            Category::Generic,
-            RiskLevel::Safe,
-            50,
-            None,
-        ),
-        make_finding(
-            "/tmp/big".into(),
-            Category::Generic,
-            RiskLevel::Safe,
-            5000,
-            None,
-        ),
-    ];
-    let recs = fake_recognizers(vec![(Category::Generic, findings)]);
-    let config = Config::default();
-    let opts = ScanOptions {
-        min_size: 1000,
-        ..Default::default()
-    };
-    let result = scanner::scan(&recs, &config, &opts);
-    assert_eq!(result.findings.len(), 1);
-    assert_eq!(result.findings[0].size_bytes, 5000);
+fn test_pip_cache_respects_xdg_cache_home_and_override() {
+    let home = TempDir::new().unwrap();
+    let xdg = populate(home.path(), "xdg/pip", 100);
+    let explicit = populate(home.path(), "explicit-pip", 100);
+
+    let loc = Locations::new(home.path(), Platform::Linux)
+        .with_var("XDG_CACHE_HOME", home.path().join("xdg"));
+    assert_eq!(finding_paths(&scan_recognizer("pip-cache", loc)), vec![xdg]);
+
+    let loc = Locations::new(home.path(), Platform::Linux)
+        .with_var("XDG_CACHE_HOME", home.path().join("xdg"))
+        .with_var("PIP_CACHE_DIR", &explicit);
+    assert_eq!(
+        finding_paths(&scan_recognizer("pip-cache", loc)),
+        vec![explicit]
+    );
 }
 
-#[test]
-fn test_scanner_category_filter() {
-    let node_findings = vec![make_finding(
-        "/tmp/node".into(),
-        Category::Node,
-        RiskLevel::Safe,
-        100,
-        None,
-    )];
-    let xcode_findings = vec![make_finding(
-        "/tmp/xcode".into(),
-        Category::Xcode,
-        RiskLevel::Safe,
-        200,
-        None,
-    )];
-    let recs = fake_recognizers(vec![
-        (Category::Node, node_findings),
-        (Category::Xcode, xcode_findings),
-    ]);
-    let config = Config::default();
-    let opts = ScanOptions {
-        category: Some(Category::Xcode),
-        ..Default::default()
-    };
-    let result = scanner::scan(&recs, &config, &opts);
-    assert_eq!(result.findings.len(), 1);
-    assert_eq!(result.findings[0].category, Category::Xcode);
+/// Make `dir` under `root` a virtualenv holding `len` bytes of packages.
+fn venv(root: &Path, dir: &str, len: usize) -> PathBuf {
+    populate(root, &format!("{dir}/lib/python3.12/site-packages"), len);
+    let venv = root.join(dir);
+    std::fs::write(venv.join("pyvenv.cfg"), "home = /usr/bin\n").unwrap();
+    venv
 }
 
 #[test]
-fn test_scanner_older_than_filter() {
-    let old = Duration::from_secs(60 * 60 * 24 * 30); // 30 days
-    let recent = Duration::from_secs(60); // 1 minute
-    let findings = vec![
-        make_finding(
-            "/tmp/old".into(),
-            Category::Generic,
-            RiskLevel::Safe,
-            100,
-            Some(old),
-        ),
-        make_finding(
-            "/tmp/new".into(),
-            Category::Generic,
-            RiskLevel::Safe,
-            200,
-            Some(recent),
-        ),
-    ];
-    let recs = fake_recognizers(vec![(Category::Generic, findings)]);
-    let config = Config::default();
-    let opts = ScanOptions {
-        older_than: Some(Duration::from_secs(60 * 60 * 24)), // 1 day
-        ..Default::default()
-    };
-    let result = scanner::scan(&recs, &config, &opts);
-    assert_eq!(result.findings.len(), 1);
-    assert!(result.findings[0].path.to_str().unwrap().contains("old"));
+fn test_python_venvs_and_tool_caches_in_projects() {
+    let home = TempDir::new().unwrap();
+    let app = home.path().join("code/app");
+    std::fs::create_dir_all(&app).unwrap();
+    std::fs::write(app.join("pyproject.toml"), "[project]").unwrap();
+    let dot_venv = venv(&app, ".venv", 4096);
+    let pytest = populate(&app, ".pytest_cache", 1024);
+    let mypy = populate(&app, ".mypy_cache", 1024);
+    let tox = populate(&app, ".tox", 1024);
+    let top = populate(&app, "app/__pycache__", 1024);
+    let nested = populate(&app, "app/sub/__pycache__", 1024);
+    // Virtualenv packages and nested projects are left to their own findings
+    let env = venv(&app, "env", 1024);
+    populate(&env, "lib/python3.12/site-packages/__pycache__", 1024);
+    let plugin = populate(&app, "plugin/__pycache__", 1024);
+    std::fs::write(app.join("plugin/setup.py"), "").unwrap();
+    // A virtualenv outside any project, found by its pyvenv.cfg
+    let tools = venv(home.path(), "code/tools/py312", 4096);
+
+    let config = config_with_roots(&["~/code"], 6);
+    let locations = || Locations::new(home.path(), Platform::Linux);
+    let venvs = scan_recognizer_with("python-venvs", locations(), config.clone());
+    let mut paths = finding_paths(&venvs);
+    paths.sort();
+    assert_eq!(paths, vec![dot_venv, env, tools]);
+    assert!(venvs.iter().all(|f| f.risk == RiskLevel::Moderate));
+
+    let caches = scan_recognizer_with("python-caches", locations(), config);
+    let mut paths = finding_paths(&caches);
+    paths.sort();
+    assert_eq!(paths, vec![mypy, pytest, tox, top.clone(), plugin]);
+    assert!(caches.iter().all(|f| f.risk == RiskLevel::Safe));
+    let pycache = caches.iter().find(|f| f.path == top).unwrap();
+    assert_eq!(pycache.extra_paths, vec![nested.clone()]);
+    assert!(pycache
+        .description
+        .starts_with("2 __pycache__ directories in app"));
+
+    cleaner::clean(std::slice::from_ref(pycache), DeleteMode::Permanent).unwrap();
+    assert!(!top.exists() && !nested.exists());
+    assert!(app.join("app/sub").exists());
 }
 
 #[test]
-fn test_scanner_sort_by_size() {
-    let findings = vec![
-        make_finding(
-            "/tmp/small".into(),
-            Category::Generic,
-            RiskLevel::Safe,
-            10,
-            None,
-        ),
-        make_finding(
-            "/tmp/big".into(),
-            Category::Generic,
-            RiskLevel::Safe,
-            9999,
-            None,
-        ),
-        make_finding(
-            "/tmp/mid".into(),
-            Category::Generic,
-            RiskLevel::Safe,
-            500,
-            None,
-        ),
-    ];
-    let recs = fake_recognizers(vec![(Category::Generic, findings)]);
-    let config = Config::default();
-    let opts = ScanOptions {
-        sort: SortOrder::Size,
-        ..Default::default()
+fn test_python_global_caches_and_environments() {
+    let home = TempDir::new().unwrap();
+    let h = home.path();
+    let uv = populate(h, ".cache/uv", 1024);
+    let poetry_cache = populate(h, ".cache/pypoetry/cache", 1024);
+    let poetry_env = populate(h, ".cache/pypoetry/virtualenvs/app-x1-py3.12", 1024);
+    let pipx_cache = populate(h, ".local/share/pipx/.cache", 1024);
+    let pipx_app = populate(h, ".local/share/pipx/venvs/black", 1024);
+    let old_python = populate(h, ".pyenv/versions/3.9.18", 1024);
+    populate(h, ".pyenv/versions/3.12.4", 1024);
+    std::fs::write(h.join(".pyenv/version"), "3.12.4\n").unwrap();
+    let conda_pkgs = populate(h, "miniconda3/pkgs", 1024);
+    let conda_env = populate(h, "miniconda3/envs/ml", 1024);
+
+    let loc = || Locations::new(h, Platform::Linux);
+    let scanned = |id: &str| {
+        scan_recognizer(id, loc())
+            .into_iter()
+            .map(|f| (f.path, f.risk))
+            .collect::<Vec<_>>()
     };
-    let result = scanner::scan(&recs, &config, &opts);
-    let sizes: Vec<u64> = result.findings.iter().map(|f| f.size_bytes).collect();
+    assert_eq!(scanned("uv-cache"), vec![(uv, RiskLevel::Safe)]);
+    let mut poetry = scanned("poetry-cache");
+    poetry.sort();
     assert_eq!(
-        sizes,
-        vec![9999, 500, 10],
-        "Should be sorted descending by size"
+        poetry,
+        vec![
+            (poetry_cache, RiskLevel::Safe),
+            (poetry_env, RiskLevel::Moderate)
+        ]
+    );
+    let mut pipx = scanned("pipx");
+    pipx.sort();
+    assert_eq!(
+        pipx,
+        vec![(pipx_cache, RiskLevel::Safe), (pipx_app, RiskLevel::Risky)]
+    );
+    // The global version stays
+    assert_eq!(
+        scanned("pyenv-versions"),
+        vec![(old_python, RiskLevel::Risky)]
+    );
+    let mut conda = scanned("conda");
+    conda.sort();
+    assert_eq!(
+        conda,
+        vec![(conda_env, RiskLevel::Risky), (conda_pkgs, RiskLevel::Safe)]
     );
+
+    let uv_dir = h.join("elsewhere/uv");
+    populate(h, "elsewhere/uv", 1024);
+    let findings = scan_recognizer("uv-cache", loc().with_var("UV_CACHE_DIR", &uv_dir));
+    assert_eq!(finding_paths(&findings), vec![uv_dir]);
 }
 
 #[test]
-fn test_scanner_sort_by_risk() {
-    let findings = vec![
-        make_finding(
-            "/tmp/safe".into(),
-            Category::Generic,
-            RiskLevel::Safe,
-            100,
-            None,
-        ),
-        make_finding(
-            "/tmp/risky".into(),
-            Category::Generic,
-            RiskLevel::Risky,
-            100,
-            None,
-        ),
-        make_finding(
-            "/tmp/moderate".into(),
-            Category::Generic,
-            RiskLevel::Moderate,
-            100,
-            None,
-        ),
-    ];
-    let recs = fake_recognizers(vec![(Category::Generic, findings)]);
-    let config = Config::default();
-    let opts = ScanOptions {
-        sort: SortOrder::Risk,
-        ..Default::default()
-    };
-    let result = scanner::scan(&recs, &config, &opts);
-    let risks: Vec<RiskLevel> = result.findings.iter().map(|f| f.risk).collect();
-    assert_eq!(
-        risks,
-        vec![RiskLevel::Risky, RiskLevel::Moderate, RiskLevel::Safe],
-        "Should be sorted descending by risk"
+fn test_homebrew_cache_linux() {
+    let home = TempDir::new().unwrap();
+    let brew = populate(home.path(), ".cache/Homebrew", 100);
+
+    let findings = scan_recognizer(
+        "homebrew-cache",
+        Locations::new(home.path(), Platform::Linux),
     );
+    assert!(finding_paths(&findings).contains(&brew));
 }
 
 #[test]
-fn test_scanner_total_reclaimable() {
-    let findings = vec![
-        make_finding(
-            "/tmp/a".into(),
-            Category::Generic,
-            RiskLevel::Safe,
-            100,
-            None,
-        ),
-        make_finding(
-            "/tmp/b".into(),
-            Category::Generic,
-            RiskLevel::Safe,
-            250,
-            None,
-        ),
-    ];
-    let recs = fake_recognizers(vec![(Category::Generic, findings)]);
-    let config = Config::default();
-    let result = scanner::scan(&recs, &config, &ScanOptions::default());
-    assert_eq!(result.total_reclaimable, 350);
+fn test_docker_rootless_data_linux() {
+    let home = TempDir::new().unwrap();
+    let docker = populate(home.path(), ".local/share/docker", 100);
+
+    let findings = scan_recognizer("docker-data", Locations::new(home.path(), Platform::Linux));
+    assert_eq!(finding_paths(&findings), vec![docker]);
+    assert_eq!(findings[0].risk, RiskLevel::Risky);
 }
 
 #[test]
-fn test_scanner_empty_recognizers() {
-    let recs: Vec<Box<dyn Recognizer>> = vec![];
-    let config = Config::default();
-    let result = scanner::scan(&recs, &config, &ScanOptions::default());
-    assert!(result.findings.is_empty());
-    assert_eq!(result.total_reclaimable, 0);
+fn test_huggingface_respects_hf_home() {
+    let home = TempDir::new().unwrap();
+    populate(
+        home.path(),
+        ".cache/huggingface/hub/models--a--b/blobs",
+        100,
+    );
+    let hf = home.path().join("models/hf");
+    let repo = populate(&hf, "hub/models--org--model/blobs", 100);
+
+    let loc = Locations::new(home.path(), Platform::Linux).with_var("HF_HOME", &hf);
+    let findings = scan_recognizer("huggingface-cache", loc);
+    assert_eq!(finding_paths(&findings), vec![repo.parent().unwrap()]);
+}
+
+#[test]
+fn test_huggingface_uses_xdg_cache_on_macos() {
+    let home = TempDir::new().unwrap();
+    let hf = populate(home.path(), ".cache/huggingface/datasets", 100);
+
+    let findings = scan_recognizer(
+        "huggingface-cache",
+        Locations::new(home.path(), Platform::MacOs),
+    );
+    assert_eq!(finding_paths(&findings), vec![hf]);
+}
+
+/// Lay out a repo in a HuggingFace hub cache: blobs of the given sizes,
+/// revisions whose files link to blobs, and refs naming revisions.
+#[cfg(unix)]
+fn hub_repo(
+    hub: &Path,
+    name: &str,
+    blobs: &[(&str, usize)],
+    revisions: &[(&str, &[(&str, &str)])],
+    refs: &[(&str, &str)],
+) -> PathBuf {
+    let repo = hub.join(name);
+    std::fs::create_dir_all(repo.join("blobs")).unwrap();
+    for (blob, len) in blobs {
+        std::fs::write(repo.join("blobs").join(blob), vec![1u8; *len]).unwrap();
+    }
+    for (revision, files) in revisions {
+        for (file, blob) in *files {
+            let link = repo.join("snapshots").join(revision).join(file);
+            std::fs::create_dir_all(link.parent().unwrap()).unwrap();
+            let depth = file.matches('/').count() + 2;
+            let target = format!("{}blobs/{blob}", "../".repeat(depth));
+            std::os::unix::fs::symlink(target, link).unwrap();
+        }
+    }
+    for (branch, revision) in refs {
+        let path = repo.join("refs").join(branch);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, revision).unwrap();
+    }
+    repo
+}
+
+#[cfg(unix)]
+#[test]
+fn test_huggingface_findings_per_repo_and_detached_revision() {
+    let home = TempDir::new().unwrap();
+    let hub = home.path().join(".cache/huggingface/hub");
+    let repo = hub_repo(
+        &hub,
+        "models--acme--tiny",
+        &[("shared", 8192), ("old", 16384), ("new", 4096)],
+        &[
+            ("aaa", &[("config.json", "shared"), ("model.bin", "new")]),
+            (
+                "bbb",
+                &[
+                    ("config.json", "shared"),
+                    ("model.bin", "old"),
+                    ("onnx/model.bin", "old"),
+                ],
+            ),
+            ("ccc", &[("config.json", "shared")]),
+            ("ddd", &[("config.json", "shared")]),
+        ],
+        &[("main", "aaa\n"), ("pr/1", "ccc")],
+    );
+    let blobs = repo.join("blobs");
+    backdate(&blobs.join("shared"), Duration::from_secs(30 * 86_400));
+    backdate(&blobs.join("old"), Duration::from_secs(30 * 86_400));
+
+    let loc = Locations::new(home.path(), Platform::Linux);
+    let findings = scan_recognizer("huggingface-cache", loc.clone());
+    // ddd shares its only blob, so deleting it would free nothing
+    let bbb = repo.join("snapshots/bbb");
+    assert_eq!(finding_paths(&findings), vec![repo.clone(), bbb.clone()]);
+    assert!(findings[0].description.contains("acme/tiny"));
+    assert_eq!(findings[0].size_bytes, size::dir_size(&repo));
+
+    let detached = &findings[1];
+    assert_eq!(detached.risk, RiskLevel::Safe);
+    assert_eq!(detached.extra_paths, vec![blobs.join("old")]);
+    assert_eq!(detached.size_bytes, size::dir_size(&blobs.join("old")));
+    let month_ago = SystemTime::now() - Duration::from_secs(29 * 86_400);
+    assert!(detached.last_modified.unwrap() < month_ago);
+    assert!(findings[0].last_modified.unwrap() > month_ago);
+
+    // The revision inside the repo doesn't add to what can be reclaimed
+    let recognizers: Vec<Box<dyn Recognizer>> = all_recognizers()
+        .into_iter()
+        .filter(|r| r.id() == "huggingface-cache")
+        .collect();
+    let ctx = ScanContext::new(loc, Config::default());
+    let result = scanner::scan_with_context(&recognizers, &ctx, &ScanOptions::default());
+    assert_eq!(result.total_reclaimable, findings[0].size_bytes);
+
+    // Deleting the revision takes its own blobs but leaves shared ones
+    let cleaned = cleaner::clean(&findings[1..], DeleteMode::Permanent).unwrap();
+    assert!(cleaned.errors.is_empty());
+    assert!(cleaned.freed_bytes >= 16384);
+    assert!(!bbb.exists());
+    assert!(!blobs.join("old").exists());
+    assert!(repo.join("snapshots/aaa/config.json").exists());
+    assert!(repo.join("snapshots/aaa/model.bin").exists());
+}
+
+#[test]
+fn test_ollama_respects_ollama_models() {
+    let home = TempDir::new().unwrap();
+    std::fs::create_dir_all(home.path().join(".ollama/models/blobs")).unwrap();
+    std::fs::write(
+        home.path().join(".ollama/models/blobs/sha256-a"),
+        [0u8; 100],
+    )
+    .unwrap();
+    let models = home.path().join("big-disk/ollama");
+    std::fs::create_dir_all(models.join("blobs")).unwrap();
+    std::fs::write(models.join("blobs/sha256-b"), [0u8; 100]).unwrap();
+
+    let loc = Locations::new(home.path(), Platform::Linux).with_var("OLLAMA_MODELS", &models);
+    let findings = scan_recognizer("ollama-models", loc);
+    assert_eq!(
+        finding_paths(&findings),
+        vec![models.join("blobs/sha256-b")]
+    );
+}
+
+/// Write an Ollama manifest at `manifests/<name>` under `models` listing the
+/// config and layer blobs, creating each blob of the given size.
+fn ollama_model(models: &Path, name: &str, blobs: &[(&str, usize)]) -> PathBuf {
+    let digests: Vec<String> = blobs
+        .iter()
+        .map(|(blob, len)| {
+            std::fs::create_dir_all(models.join("blobs")).unwrap();
+            std::fs::write(models.join(format!("blobs/sha256-{blob}")), vec![1u8; *len]).unwrap();
+            format!("sha256:{blob}")
+        })
+        .collect();
+    let manifest = serde_json::json!({
+        "schemaVersion": 2,
+        "config": { "digest": digests[0], "size": blobs[0].1 },
+        "layers": digests[1..]
+            .iter()
+            .map(|digest| serde_json::json!({ "digest": digest }))
+            .collect::<Vec<_>>(),
+    });
+    let path = models.join("manifests").join(name);
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(&path, manifest.to_string()).unwrap();
+    path
+}
+
+#[test]
+fn test_ollama_findings_per_model_and_orphan_blob() {
+    let home = TempDir::new().unwrap();
+    let models = home.path().join(".ollama/models");
+    let blob = |name: &str| models.join(format!("blobs/sha256-{name}"));
+    let coder = ollama_model(
+        &models,
+        "registry.ollama.ai/acme/coder/q4",
+        &[("cfg3", 100), ("coder", 8192), ("license", 1000)],
+    );
+    let small = ollama_model(
+        &models,
+        "registry.ollama.ai/library/llama3/8b",
+        &[("cfg1", 100), ("weights", 16384), ("license", 1000)],
+    );
+    let latest = ollama_model(
+        &models,
+        "registry.ollama.ai/library/llama3/latest",
+        &[("cfg2", 100), ("weights", 16384), ("license", 1000)],
+    );
+    let instruct = ollama_model(
+        &models,
+        "registry.ollama.ai/library/llama3/8b-instruct",
+        &[("cfg4", 100), ("instruct", 2048)],
+    );
+    std::fs::write(blob("stale"), vec![0u8; 4096]).unwrap();
+    // A pull may still be writing the new partial download
+    std::fs::write(blob("new-partial"), vec![0u8; 2048]).unwrap();
+    std::fs::write(blob("old-partial"), vec![0u8; 2048]).unwrap();
+    backdate(&blob("old-partial"), Duration::from_secs(2 * 86_400));
+    // Manifests that aren't tags or don't parse still keep their blobs
+    let odd = ollama_model(
+        &models,
+        "registry.ollama.ai/library/odd",
+        &[("odd", 100), ("odd-layer", 100)],
+    );
+    std::fs::write(blob("kept"), vec![0u8; 100]).unwrap();
+    let corrupt = models.join("manifests/registry.ollama.ai/library/broken/latest");
+    std::fs::create_dir_all(corrupt.parent().unwrap()).unwrap();
+    std::fs::write(&corrupt, r#"{"layers": [{"digest": "sha256:kept"#).unwrap();
+
+    // Findings come largest first, so look them up by path
+    let loc = || Locations::new(home.path(), Platform::Linux);
+    let scan_paths = || {
+        let findings = scan_recognizer("ollama-models", loc());
+        let mut paths = finding_paths(&findings);
+        paths.sort();
+        (findings, paths)
+    };
+    let at = |findings: &[Finding], path: &Path| {
+        findings.iter().find(|f| f.path == path).unwrap().clone()
+    };
+    let (findings, paths) = scan_paths();
+    assert_eq!(
+        paths,
+        vec![
+            blob("old-partial"),
+            blob("stale"),
+            coder.clone(),
+            small.clone(),
+            instruct.clone(),
+            latest.clone(),
+        ]
+    );
+    assert!(odd.exists());
+    let found = at(&findings, &coder);
+    assert!(found.description.contains("acme/coder:q4"));
+    assert_eq!(found.extra_paths, vec![blob("cfg3"), blob("coder")]);
+    assert_eq!(
+        found.size_bytes,
+        [&coder, &blob("cfg3"), &blob("coder")]
+            .iter()
+            .map(|p| size::dir_size(p))
+            .sum::<u64>()
+    );
+    // Layers both llama3 tags use belong to neither
+    let found = at(&findings, &small);
+    assert_eq!(found.extra_paths, vec![blob("cfg1")]);
+    assert!(found.description.contains("shared with other models"));
+    assert_eq!(
+        found.native.as_ref().unwrap().argv(&small),
+        vec!["ollama", "rm", "llama3:8b"]
+    );
+    assert_eq!(found.risk, RiskLevel::Moderate);
+    assert_eq!(at(&findings, &blob("stale")).risk, RiskLevel::Safe);
+    assert_eq!(
+        at(&findings, &blob("old-partial")).risk,
+        RiskLevel::Moderate
+    );
+
+    // One model can be picked out by name, but not by part of another's
+    let recognizers: Vec<Box<dyn Recognizer>> = all_recognizers()
+        .into_iter()
+        .filter(|r| r.id() == "ollama-models")
+        .collect();
+    let ctx = ScanContext::new(loc(), Config::default());
+    let options = ScanOptions {
+        matching: vec!["LLAMA3:8b".into()],
+        ..Default::default()
+    };
+    let result = scanner::scan_with_context(&recognizers, &ctx, &options);
+    assert_eq!(finding_paths(&result.findings), vec![small.clone()]);
+
+    // Deleting one tag leaves the layers the other still needs
+    cleaner::clean(&result.findings, DeleteMode::Permanent).unwrap();
+    assert!(!small.exists());
+    assert!(!blob("cfg1").exists());
+    assert!(blob("weights").exists());
+
+    // The other tag is now the only one using the weights, so they go with
+    // it; the license stays for the model still using it
+    let (findings, _) = scan_paths();
+    assert_eq!(
+        at(&findings, &latest).extra_paths,
+        vec![blob("cfg2"), blob("weights")]
+    );
+    cleaner::clean(&[at(&findings, &latest)], DeleteMode::Permanent).unwrap();
+    assert!(!latest.exists());
+    assert!(!blob("weights").exists());
+    assert!(blob("license").exists());
+    let (_, paths) = scan_paths();
+    assert_eq!(
+        paths,
+        vec![
+            blob("old-partial"),
+            blob("stale"),
+            coder.clone(),
+            instruct.clone()
+        ]
+    );
+
+    // With a manifest it can't read, nothing can be called an orphan
+    std::fs::write(&corrupt, [0xff, 0xfe, 0x00]).unwrap();
+    let (_, paths) = scan_paths();
+    assert_eq!(paths, vec![coder, instruct]);
+}
+
+#[test]
+fn test_cargo_registry_respects_cargo_home() {
+    let home = TempDir::new().unwrap();
+    let cargo_home = home.path().join("opt/cargo");
+    let registry = populate(&cargo_home, "registry/cache", 100);
+
+    let loc = Locations::new(home.path(), Platform::Linux).with_var("CARGO_HOME", &cargo_home);
+    let findings = scan_recognizer("cargo-target", loc);
+    assert_eq!(finding_paths(&findings), vec![registry]);
+}
+
+#[test]
+fn test_cargo_home_caches_and_unlocked_crates() {
+    let home = TempDir::new().unwrap();
+    let h = home.path();
+    let index = "index.crates.io-6f17d22bba15001f";
+    let cache = populate(h, &format!(".cargo/registry/cache/{index}"), 100);
+    let src = populate(
+        h,
+        &format!(".cargo/registry/src/{index}/serde-1.0.100"),
+        100,
+    );
+    populate(
+        h,
+        &format!(".cargo/registry/src/{index}/serde-1.0.200"),
+        100,
+    );
+    std::fs::write(cache.join("serde-1.0.100.crate"), vec![0u8; 100]).unwrap();
+    std::fs::write(cache.join("serde-1.0.200.crate"), vec![0u8; 100]).unwrap();
+    populate(h, ".cargo/git/checkouts/tokio-1a2b/abc123", 100);
+    populate(h, ".cargo/git/db/tokio-1a2b", 100);
+    let project = h.join("code/app");
+    std::fs::create_dir_all(&project).unwrap();
+    std::fs::write(project.join("Cargo.toml"), "[package]").unwrap();
+    std::fs::write(
+        project.join("Cargo.lock"),
+        "version = 4\n\n[[package]]\nname = \"serde\"\nversion = \"1.0.200\"\n",
+    )
+    .unwrap();
+
+    let loc = || Locations::new(h, Platform::Linux);
+    let findings = scan_recognizer_with("cargo-target", loc(), config_with_roots(&["~/code"], 3));
+    let mut found: Vec<(PathBuf, RiskLevel)> =
+        findings.iter().map(|f| (f.path.clone(), f.risk)).collect();
+    found.sort();
+    let stale = cache.join("serde-1.0.100.crate");
+    assert_eq!(
+        found,
+        vec![
+            (h.join(".cargo/git/checkouts"), RiskLevel::Safe),
+            (h.join(".cargo/git/db"), RiskLevel::Moderate),
+            (h.join(".cargo/registry/cache"), RiskLevel::Safe),
+            (stale.clone(), RiskLevel::Safe),
+            (h.join(".cargo/registry/src"), RiskLevel::Safe),
+        ]
+    );
+    let unlocked = findings.iter().find(|f| f.path == stale).unwrap();
+    assert_eq!(unlocked.extra_paths, vec![src.clone()]);
+    assert!(unlocked.description.contains("serde-1.0.100"));
+
+    cleaner::clean(std::slice::from_ref(unlocked), DeleteMode::Permanent).unwrap();
+    assert!(!stale.exists() && !src.exists());
+    assert!(cache.join("serde-1.0.200.crate").exists());
+
+    // Without any lockfile to go on, no crate version is singled out
+    let findings = scan_recognizer("cargo-target", loc());
+    assert_eq!(findings.len(), 4);
+}
+
+#[test]
+fn test_rustup_toolchains_keep_default_and_pinned() {
+    let home = TempDir::new().unwrap();
+    let h = home.path();
+    let host = "x86_64-unknown-linux-gnu";
+    let rustup = h.join("rustup");
+    let toolchain = |name: &str| populate(&rustup, &format!("toolchains/{name}-{host}"), 100);
+    toolchain("stable");
+    toolchain("1.75");
+    toolchain("nightly-2024-01-01");
+    let old = toolchain("1.70");
+    let beta = toolchain("beta");
+    std::fs::write(
+        rustup.join("settings.toml"),
+        format!(
+            "default_toolchain = \"stable-{host}\"\n\n[overrides]\n\"/srv/app\" = \"nightly-2024-01-01-{host}\"\n"
+        ),
+    )
+    .unwrap();
+    let pinned = h.join("code/pinned");
+    std::fs::create_dir_all(&pinned).unwrap();
+    std::fs::write(
+        pinned.join("rust-toolchain.toml"),
+        "[toolchain]\nchannel = \"1.75\"\n",
+    )
+    .unwrap();
+    // The older file may hold just the channel
+    let legacy = h.join("code/legacy");
+    std::fs::create_dir_all(&legacy).unwrap();
+    std::fs::write(legacy.join("rust-toolchain"), "beta\n").unwrap();
+
+    let loc = || Locations::new(h, Platform::Linux).with_var("RUSTUP_HOME", &rustup);
+    let findings = scan_recognizer_with(
+        "rustup-toolchains",
+        loc(),
+        config_with_roots(&["~/code/pinned"], 2),
+    );
+    let mut paths = finding_paths(&findings);
+    paths.sort();
+    assert_eq!(paths, vec![old.clone(), beta]);
+    let found = findings.iter().find(|f| f.path == old).unwrap();
+    assert_eq!(found.risk, RiskLevel::Moderate);
+    assert_eq!(
+        found.native.as_ref().unwrap().argv(&old),
+        vec!["rustup", "toolchain", "uninstall", &format!("1.70-{host}")]
+    );
+
+    let findings = scan_recognizer_with(
+        "rustup-toolchains",
+        loc(),
+        config_with_roots(&["~/code"], 2),
+    );
+    assert_eq!(finding_paths(&findings), vec![old]);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_pyenv_versions_keep_global_and_pinned() {
+    let home = TempDir::new().unwrap();
+    let h = home.path();
+    let version = |name: &str| populate(h, &format!(".pyenv/versions/{name}"), 100);
+    version("3.12.4");
+    let old = version("3.8.0");
+    let unscanned = version("3.9.18");
+    let with_env = version("3.11.4");
+    version("3.13.1");
+    std::fs::write(h.join(".pyenv/version"), "3.12.4\n").unwrap();
+    // pyenv-virtualenv links each environment from versions/
+    populate(&with_env, "envs/tools", 100);
+    std::os::unix::fs::symlink(with_env.join("envs/tools"), h.join(".pyenv/versions/tools"))
+        .unwrap();
+    for (project, pin) in [
+        ("code/app", "tools\n"),
+        ("code/cli", "# newest 3.13\n3.13\n"),
+        ("elsewhere/old", "3.9.18\n"),
+    ] {
+        std::fs::create_dir_all(h.join(project)).unwrap();
+        std::fs::write(h.join(project).join(".python-version"), pin).unwrap();
+    }
+
+    let findings = scan_recognizer_with(
+        "pyenv-versions",
+        Locations::new(h, Platform::Linux),
+        config_with_roots(&["~/code"], 2),
+    );
+    let mut paths = finding_paths(&findings);
+    paths.sort();
+    assert_eq!(paths, vec![old, unscanned]);
+    assert!(findings.iter().all(|f| f.risk == RiskLevel::Risky));
+}
+
+#[test]
+fn test_gradle_respects_gradle_user_home() {
+    let home = TempDir::new().unwrap();
+    let gradle_home = home.path().join("gradle-home");
+    let caches = populate(&gradle_home, "caches", 100);
+    populate(home.path(), ".gradle/caches", 100);
+
+    let loc =
+        Locations::new(home.path(), Platform::Linux).with_var("GRADLE_USER_HOME", &gradle_home);
+    let findings = scan_recognizer("gradle-cache", loc);
+    assert_eq!(finding_paths(&findings), vec![caches]);
+}
+
+#[test]
+fn test_xcode_skipped_off_macos() {
+    let home = TempDir::new().unwrap();
+    let derived = populate(home.path(), "Library/Developer/Xcode/DerivedData", 100);
+
+    let linux = scan_recognizer(
+        "xcode-derived-data",
+        Locations::new(home.path(), Platform::Linux),
+    );
+    assert!(linux.is_empty());
+
+    let mac = scan_recognizer(
+        "xcode-derived-data",
+        Locations::new(home.path(), Platform::MacOs),
+    );
+    assert_eq!(finding_paths(&mac), vec![derived]);
+}
+
+// ---------------------------------------------------------------------------
+// Project scan root tests
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_project_roots_expand_tilde_and_drop_nested() {
+    let home = TempDir::new().unwrap();
+    std::fs::create_dir_all(home.path().join("work/sub")).unwrap();
+    std::fs::create_dir_all(home.path().join("code")).unwrap();
+
+    let config = config_with_roots(&["~/work", "~/work/sub", "~/code", "~/missing"], 5);
+    let ctx = ScanContext::new(Locations::new(home.path(), Platform::Linux), config);
+    assert_eq!(
+        ctx.project_roots(),
+        vec![home.path().join("code"), home.path().join("work")]
+    );
+}
+
+#[test]
+fn test_cargo_target_found_under_configured_root() {
+    let home = TempDir::new().unwrap();
+    let project = home.path().join("code/acme/widget");
+    std::fs::create_dir_all(&project).unwrap();
+    std::fs::write(project.join("Cargo.toml"), "[package]").unwrap();
+    let target = populate(&project, "target", 2_000_000);
+
+    let findings = scan_recognizer_with(
+        "cargo-target",
+        Locations::new(home.path(), Platform::Linux),
+        config_with_roots(&["~/code"], 4),
+    );
+    assert_eq!(finding_paths(&findings), vec![target]);
+
+    // Default roots do not include ~/code
+    let findings = scan_recognizer("cargo-target", Locations::new(home.path(), Platform::Linux));
+    assert!(findings.is_empty());
+}
+
+#[test]
+fn test_projects_found_in_dirs_named_like_artifacts() {
+    let home = TempDir::new().unwrap();
+    let project = home.path().join("code/venv/tool");
+    std::fs::create_dir_all(&project).unwrap();
+    std::fs::write(project.join("Cargo.toml"), "[package]").unwrap();
+    let target = populate(&project, "target", 2_000_000);
+    // A real virtualenv is still not searched
+    let app = home.path().join("code/app");
+    std::fs::create_dir_all(&app).unwrap();
+    std::fs::write(app.join("pyproject.toml"), "[project]").unwrap();
+    let env = venv(&app, "venv", 4096);
+    let hidden = env.join("lib/python3.12/site-packages/pkg");
+    std::fs::create_dir_all(&hidden).unwrap();
+    std::fs::write(hidden.join("Cargo.toml"), "[package]").unwrap();
+    populate(&hidden, "target", 2_000_000);
+
+    // Scanned together, so the virtualenv detector's `venv` is in play
+    let recognizers: Vec<Box<dyn Recognizer>> = all_recognizers()
+        .into_iter()
+        .filter(|r| ["cargo-target", "python-venvs"].contains(&r.id()))
+        .collect();
+    let ctx = ScanContext::new(
+        Locations::new(home.path(), Platform::Linux),
+        config_with_roots(&["~/code"], 8),
+    );
+    let result = scanner::scan_with_context(&recognizers, &ctx, &ScanOptions::default());
+    let mut paths = finding_paths(&result.findings);
+    paths.sort();
+    assert_eq!(paths, vec![env, target]);
+}
+
+/// Write a `Cargo.toml` with `manifest` into `dir`.
+fn cargo_manifest(dir: &Path, manifest: &str) {
+    std::fs::create_dir_all(dir).unwrap();
+    std::fs::write(dir.join("Cargo.toml"), manifest).unwrap();
+}
+
+#[test]
+fn test_cargo_target_split_by_profile_and_triple() {
+    let home = TempDir::new().unwrap();
+    let project = home.path().join("code/app");
+    cargo_manifest(&project, "[package]");
+    let target = project.join("target");
+    populate(&target, "debug/deps", 2_000_000);
+    populate(&target, "debug/incremental/app-1x2y", 2_000_000);
+    populate(&target, "release/deps", 2_000_000);
+    populate(&target, "doc/app", 2_000_000);
+    populate(&target, "package", 2_000_000);
+    populate(&target, "aarch64-apple-darwin/release/deps", 2_000_000);
+    populate(&target, "aarch64-apple-darwin/debug/incremental", 2_000_000);
+    // Anything else is counted together
+    populate(&target, "criterion/parse", 2_000_000);
+    populate(&target, "tmp", 100);
+    std::fs::write(target.join("CACHEDIR.TAG"), "Signature").unwrap();
+
+    let findings = scan_recognizer_with(
+        "cargo-target",
+        Locations::new(home.path(), Platform::Linux),
+        config_with_roots(&["~/code"], 3),
+    );
+    let mut found: Vec<(PathBuf, RiskLevel)> =
+        findings.iter().map(|f| (f.path.clone(), f.risk)).collect();
+    found.sort();
+    assert_eq!(
+        found,
+        vec![
+            (target.join("aarch64-apple-darwin"), RiskLevel::Moderate),
+            (
+                target.join("aarch64-apple-darwin/debug/incremental"),
+                RiskLevel::Safe
+            ),
+            (target.join("criterion"), RiskLevel::Moderate),
+            (target.join("debug"), RiskLevel::Moderate),
+            (target.join("debug/incremental"), RiskLevel::Safe),
+            (target.join("doc"), RiskLevel::Safe),
+            (target.join("package"), RiskLevel::Safe),
+            (target.join("release"), RiskLevel::Moderate),
+        ]
+    );
+    let at = |part: &str| {
+        findings
+            .iter()
+            .find(|f| f.path == target.join(part))
+            .unwrap()
+    };
+    assert_eq!(at("release").description, "Rust release build of app");
+    assert_eq!(
+        at("criterion").extra_paths,
+        vec![target.join("tmp"), target.join("CACHEDIR.TAG")]
+    );
+    assert_eq!(
+        at("criterion").description,
+        "Other build output for app: criterion, tmp, CACHEDIR.TAG"
+    );
+
+    // Each part `cargo clean` can clear on its own carries that command
+    let manifest = target.join("../Cargo.toml").display().to_string();
+    let native = |part: &str| {
+        at(part)
+            .native
+            .as_ref()
+            .map(|native| native.argv(&target.join(part)))
+    };
+    let cargo_clean = |args: &[&str]| {
+        let mut argv = vec!["cargo", "clean", "--manifest-path", &manifest];
+        argv.extend(args);
+        Some(argv.into_iter().map(String::from).collect::<Vec<_>>())
+    };
+    assert_eq!(native("debug"), cargo_clean(&["--profile", "dev"]));
+    assert_eq!(native("release"), cargo_clean(&["--profile", "release"]));
+    assert_eq!(native("doc"), cargo_clean(&["--doc"]));
+    assert_eq!(native("aarch64-apple-darwin"), None);
+    assert_eq!(native("debug/incremental"), None);
+
+    // Clearing the incremental cache leaves the build it belongs to
+    cleaner::clean(
+        std::slice::from_ref(at("debug/incremental")),
+        DeleteMode::Permanent,
+    )
+    .unwrap();
+    assert!(!target.join("debug/incremental").exists());
+    assert!(target.join("debug/deps").exists());
+}
+
+#[test]
+fn test_cargo_target_shared_by_workspace_and_target_dir() {
+    let home = TempDir::new().unwrap();
+    let h = home.path();
+    let workspace = h.join("code/ws");
+    cargo_manifest(&workspace, "[workspace]\nmembers = [\"crates/*\"]\n");
+    cargo_manifest(&workspace.join("crates/core"), "[package]");
+    cargo_manifest(&workspace.join("crates/cli"), "[package]");
+    let shared = populate(&workspace, "target/debug/deps", 2_000_000);
+    // Left from before `core` joined the workspace
+    let stale = populate(&workspace, "crates/core/target/debug/deps", 2_000_000);
+    // Builds moved elsewhere by config
+    let tool = h.join("code/tool");
+    cargo_manifest(&tool, "[package]");
+    std::fs::create_dir_all(tool.join(".cargo")).unwrap();
+    std::fs::write(
+        tool.join(".cargo/config.toml"),
+        "[build]\ntarget-dir = \"../../builds/tool\"\n",
+    )
+    .unwrap();
+    populate(h, "builds/tool/release/deps", 2_000_000);
+    let configured = h
+        .join("builds/tool")
+        .canonicalize()
+        .unwrap()
+        .join("release/deps");
+
+    let config = || config_with_roots(&["~/code"], 4);
+    let loc = || Locations::new(h, Platform::Linux);
+    let findings = scan_recognizer_with("cargo-target", loc(), config());
+    let mut paths = finding_paths(&findings);
+    paths.sort();
+    let profile = |deps: &Path| deps.parent().unwrap().to_path_buf();
+    assert_eq!(
+        paths,
+        vec![profile(&configured), profile(&stale), profile(&shared),]
+    );
+    let ws = findings
+        .iter()
+        .find(|f| f.path == profile(&shared))
+        .unwrap();
+    assert_eq!(ws.description, "Rust debug build of ws");
+    assert!(ws.native.is_some());
+    // `cargo clean` would clear the workspace's target, not the leftover one
+    let leftover = findings.iter().find(|f| f.path == profile(&stale)).unwrap();
+    assert!(leftover.native.is_none());
+
+    // One `$CARGO_TARGET_DIR` serves every project, and is reported once
+    let env_target = h.join("cargo-target");
+    let everything = populate(&env_target, "debug/deps", 2_000_000);
+    let findings = scan_recognizer_with(
+        "cargo-target",
+        loc().with_var("CARGO_TARGET_DIR", &env_target),
+        config(),
+    );
+    let mut paths = finding_paths(&findings);
+    paths.sort();
+    assert_eq!(
+        paths,
+        vec![profile(&everything), profile(&stale), profile(&shared)]
+    );
+    let env = findings
+        .iter()
+        .find(|f| f.path == profile(&everything))
+        .unwrap();
+    // The workspace counts once, however many members it has
+    assert_eq!(env.description, "Rust debug build of 2 projects");
+}
+
+#[test]
+fn test_cargo_target_age_follows_project_activity() {
+    let home = TempDir::new().unwrap();
+    let project = home.path().join("code/widget");
+    populate(&project, "src", 10);
+    std::fs::write(project.join("Cargo.toml"), "[package]").unwrap();
+    let target = populate(&project, "target", 2_000_000);
+    backdate(&project, Duration::from_secs(60 * 86_400));
+
+    let stale = ScanOptions {
+        older_than: Some(Duration::from_secs(30 * 86_400)),
+        ..Default::default()
+    };
+    let scan = |options: &ScanOptions| {
+        let recognizers: Vec<Box<dyn Recognizer>> = all_recognizers()
+            .into_iter()
+            .filter(|r| r.id() == "cargo-target")
+            .collect();
+        let ctx = ScanContext::new(
+            Locations::new(home.path(), Platform::Linux),
+            config_with_roots(&["~/code"], 3),
+        );
+        scanner::scan_with_context(&recognizers, &ctx, options).findings
+    };
+
+    let findings = scan(&stale);
+    assert_eq!(finding_paths(&findings), vec![target.clone()]);
+    let age = SystemTime::now()
+        .duration_since(findings[0].last_modified.unwrap())
+        .unwrap();
+    assert!(age > Duration::from_secs(59 * 86_400));
+
+    // Editing a source file makes the build artifacts current again
+    std::fs::write(project.join("src/blob"), "edited").unwrap();
+    assert!(scan(&stale).is_empty());
+    assert_eq!(finding_paths(&scan(&ScanOptions::default())), vec![target]);
+}
+
+#[test]
+fn test_recognizer_settings_override_risk_and_min_size() {
+    let home = TempDir::new().unwrap();
+    let project = home.path().join("code/small");
+    std::fs::create_dir_all(&project).unwrap();
+    std::fs::write(project.join("Cargo.toml"), "[package]").unwrap();
+    let target = populate(&project, "target", 100_000);
+    let locations = || Locations::new(home.path(), Platform::Linux);
+
+    // Below the built-in 1 MiB cut-off
+    let mut config = config_with_roots(&["~/code"], 3);
+    let findings = scan_recognizer_with("cargo-target", locations(), config.clone());
+    assert!(findings.is_empty());
+
+    config.recognizers.settings.insert(
+        "cargo-target".into(),
+        RecognizerSettings {
+            risk: Some(RiskLevel::Safe),
+            min_size: Some(64 * 1024),
+            ..Default::default()
+        },
+    );
+    let findings = scan_recognizer_with("cargo-target", locations(), config.clone());
+    assert_eq!(finding_paths(&findings), vec![target]);
+    assert_eq!(findings[0].risk, RiskLevel::Safe);
+
+    // A recognizer's min_size also applies to findings it doesn't filter itself
+    let pip = populate(home.path(), ".cache/pip", 10_000);
+    assert_eq!(
+        finding_paths(&scan_recognizer_with(
+            "pip-cache",
+            locations(),
+            config.clone()
+        )),
+        vec![pip]
+    );
+    config.recognizers.settings.insert(
+        "pip-cache".into(),
+        RecognizerSettings {
+            min_size: Some(1_048_576),
+            ..Default::default()
+        },
+    );
+    assert!(scan_recognizer_with("pip-cache", locations(), config).is_empty());
+}
+
+#[test]
+fn test_recognizer_settings_roots_replace_scan_roots() {
+    let home = TempDir::new().unwrap();
+    for (dir, marker) in [
+        ("code/rusty", "Cargo.toml"),
+        ("code/web", "package.json"),
+        ("rust/other", "Cargo.toml"),
+        ("rust/site", "package.json"),
+    ] {
+        let project = home.path().join(dir);
+        std::fs::create_dir_all(&project).unwrap();
+        std::fs::write(project.join(marker), "{}").unwrap();
+        let artifacts = if marker == "Cargo.toml" {
+            "target"
+        } else {
+            "node_modules"
+        };
+        populate(&project, artifacts, 2_000_000);
+    }
+
+    let mut config = config_with_roots(&["~/code"], 3);
+    config.recognizers.settings.insert(
+        "cargo-target".into(),
+        RecognizerSettings {
+            roots: Some(vec![PathBuf::from("~/rust"), PathBuf::from("~/code/rusty")]),
+            ..Default::default()
+        },
+    );
+    let recognizers: Vec<Box<dyn Recognizer>> = all_recognizers()
+        .into_iter()
+        .filter(|r| r.id() == "cargo-target" || r.id() == "node-modules")
+        .collect();
+    let ctx = ScanContext::new(Locations::new(home.path(), Platform::Linux), config);
+    let result = scanner::scan_with_context(&recognizers, &ctx, &ScanOptions::default());
+
+    let mut paths = finding_paths(&result.findings);
+    paths.sort();
+    assert_eq!(
+        paths,
+        vec![
+            home.path().join("code/rusty/target"),
+            home.path().join("code/web/node_modules"),
+            home.path().join("rust/other/target"),
+        ]
+    );
+}
+
+#[test]
+fn test_vscode_keep_latest_extension_versions() {
+    let home = TempDir::new().unwrap();
+    for version in ["1.0.0", "1.1.0", "1.2.0"] {
+        populate(
+            home.path(),
+            &format!(".vscode/extensions/acme.tool-{version}"),
+            100,
+        );
+    }
+    let locations = || Locations::new(home.path(), Platform::MacOs);
+
+    let findings = scan_recognizer("vscode-extensions", locations());
+    assert_eq!(findings.len(), 2);
+
+    let mut config = Config::default();
+    config.recognizers.settings.insert(
+        "vscode-extensions".into(),
+        RecognizerSettings {
+            keep_latest: Some(2),
+            ..Default::default()
+        },
+    );
+    let findings = scan_recognizer_with("vscode-extensions", locations(), config);
+    assert_eq!(
+        finding_paths(&findings),
+        vec![home.path().join(".vscode/extensions/acme.tool-1.0.0")]
+    );
+}
+
+/// A Cargo project at `~/code/<name>` with a source file and a 2 MB target,
+/// everything last touched `age` ago.
+fn cargo_project(home: &Path, name: &str, age: Duration) -> PathBuf {
+    let project = home.join("code").join(name);
+    populate(&project, "src", 10);
+    std::fs::write(project.join("Cargo.toml"), "[package]").unwrap();
+    populate(&project, "target", 2_000_000);
+    backdate(&project, age);
+    project
+}
+
+fn protecting(within: &str, mode: &str) -> Config {
+    let mut config = config_with_roots(&["~/code"], 3);
+    config.scan.protect_active_within = Some(within.into());
+    config.scan.protect_active = mode.into();
+    config
+}
+
+#[test]
+fn test_recent_edits_raise_risk_of_project_artifacts() {
+    let home = TempDir::new().unwrap();
+    let idle = cargo_project(home.path(), "idle", Duration::from_secs(10 * 86_400));
+    let busy = cargo_project(home.path(), "busy", Duration::from_secs(10 * 86_400));
+    std::fs::write(busy.join("src/blob"), "edited").unwrap();
+
+    let mut findings = scan_recognizer_with(
+        "cargo-target",
+        Locations::new(home.path(), Platform::Linux),
+        protecting("3d", "raise"),
+    );
+    findings.sort_by(|a, b| a.path.cmp(&b.path));
+    assert_eq!(
+        finding_paths(&findings),
+        vec![busy.join("target"), idle.join("target")]
+    );
+    assert_eq!(findings[0].risk, RiskLevel::Risky);
+    assert!(
+        findings[0]
+            .description
+            .ends_with("(active: src/blob edited 0m ago)"),
+        "{}",
+        findings[0].description
+    );
+    assert_eq!(findings[1].risk, RiskLevel::Moderate);
+}
+
+#[test]
+fn test_shared_target_dir_protected_by_any_active_project() {
+    let home = TempDir::new().unwrap();
+    let idle = cargo_project(home.path(), "idle", Duration::from_secs(10 * 86_400));
+    let busy = cargo_project(home.path(), "busy", Duration::from_secs(10 * 86_400));
+    let shared = populate(home.path(), "builds/debug/deps", 2_000_000);
+    backdate(
+        &home.path().join("builds"),
+        Duration::from_secs(10 * 86_400),
+    );
+    let locations = || {
+        Locations::new(home.path(), Platform::Linux)
+            .with_var("CARGO_TARGET_DIR", home.path().join("builds"))
+    };
+    let debug = shared.parent().unwrap().to_path_buf();
+
+    let findings = scan_recognizer_with("cargo-target", locations(), protecting("3d", "raise"));
+    let found = findings.iter().find(|f| f.path == debug).unwrap();
+    assert_eq!(found.risk, RiskLevel::Moderate);
+    assert_eq!(found.projects, vec![busy.clone(), idle.clone()]);
+
+    // The target dir lies outside both, but editing either one protects it
+    std::fs::write(busy.join("src/blob"), "edited").unwrap();
+    let findings = scan_recognizer_with("cargo-target", locations(), protecting("3d", "raise"));
+    let found = findings.iter().find(|f| f.path == debug).unwrap();
+    assert_eq!(found.risk, RiskLevel::Risky);
+    assert!(found.description.contains("(active: src/blob edited"));
+}
+
+#[test]
+fn test_git_activity_excludes_project_artifacts() {
+    let home = TempDir::new().unwrap();
+    let project = cargo_project(home.path(), "repo", Duration::from_secs(30 * 86_400));
+    std::fs::create_dir_all(project.join(".git/logs")).unwrap();
+    std::fs::write(project.join(".git/HEAD"), "ref: refs/heads/main\n").unwrap();
+    backdate(&project, Duration::from_secs(30 * 86_400));
+
+    let recognizers: Vec<Box<dyn Recognizer>> = all_recognizers()
+        .into_iter()
+        .filter(|r| r.id() == "cargo-target")
+        .collect();
+    let scan = || {
+        let ctx = ScanContext::new(
+            Locations::new(home.path(), Platform::Linux),
+            protecting("1w", "exclude"),
+        );
+        scanner::scan_with_context(&recognizers, &ctx, &ScanOptions::default())
+    };
+
+    let result = scan();
+    assert_eq!(
+        finding_paths(&result.findings),
+        vec![project.join("target")]
+    );
+    assert!(result.excluded.is_empty());
+
+    // A commit appends to the HEAD reflog
+    std::fs::write(project.join(".git/logs/HEAD"), "commit\n").unwrap();
+    let result = scan();
+    assert!(result.findings.is_empty());
+    assert_eq!(result.excluded.len(), 1);
+    assert_eq!(result.excluded[0].finding.path, project.join("target"));
+    assert_eq!(result.excluded[0].reason, "git activity 0m ago");
+}
+
+#[test]
+fn test_uncommitted_changes_protect_project() {
+    let home = TempDir::new().unwrap();
+    let project = cargo_project(home.path(), "repo", Duration::ZERO);
+    let git = |args: &[&str]| {
+        std::process::Command::new("git")
+            .args(["-c", "user.name=t", "-c", "user.email=t@t"])
+            .args(args)
+            .current_dir(&project)
+            .output()
+            .is_ok_and(|out| out.status.success())
+    };
+    if !git(&["init", "-q"]) {
+        return;
+    }
+    std::fs::write(project.join(".gitignore"), "target\n").unwrap();
+    assert!(git(&["add", "."]));
+    assert!(git(&["commit", "-qm", "init"]));
+
+    let scan = || {
+        scan_recognizer_with(
+            "cargo-target",
+            Locations::new(home.path(), Platform::Linux),
+            protecting("1d", "raise"),
+        )
+    };
+    backdate(&project, Duration::from_secs(30 * 86_400));
+    // Backdating changed the times git recorded; refresh them as `git status` would
+    assert!(git(&["update-index", "--refresh", "-q"]));
+    backdate(&project.join(".git"), Duration::from_secs(30 * 86_400));
+    assert_eq!(scan()[0].risk, RiskLevel::Moderate);
+
+    // A new file git doesn't know about yet
+    std::fs::write(project.join("notes.md"), "untracked").unwrap();
+    backdate(&project.join("notes.md"), Duration::from_secs(30 * 86_400));
+    let findings = scan();
+    assert_eq!(findings[0].risk, RiskLevel::Risky);
+    assert!(findings[0]
+        .description
+        .ends_with("(active: uncommitted changes)"));
+    std::fs::remove_file(project.join("notes.md")).unwrap();
+    assert_eq!(scan()[0].risk, RiskLevel::Moderate);
+
+    std::fs::write(project.join("src/blob"), "uncommitted edit").unwrap();
+    backdate(&project.join("src"), Duration::from_secs(30 * 86_400));
+    let findings = scan();
+    assert_eq!(findings[0].risk, RiskLevel::Risky);
+    assert!(findings[0]
+        .description
+        .ends_with("(active: uncommitted changes)"));
+}
+
+#[test]
+fn test_cache_age_is_newest_use_in_tree() {
+    let home = TempDir::new().unwrap();
+    let pip = populate(home.path(), ".cache/pip", 100);
+    populate(&pip, "http/a", 100);
+    backdate(&pip, Duration::from_secs(90 * 86_400));
+    backdate(&pip.join("http/a/blob"), Duration::from_secs(10 * 86_400));
+
+    let findings = scan_recognizer("pip-cache", Locations::new(home.path(), Platform::Linux));
+    let age = SystemTime::now()
+        .duration_since(findings[0].last_modified.unwrap())
+        .unwrap();
+    assert!(age >= Duration::from_secs(10 * 86_400));
+    assert!(age < Duration::from_secs(11 * 86_400));
+}
+
+#[test]
+fn test_scan_cache_matches_uncached_walk() {
+    let root = TempDir::new().unwrap();
+    for (dir, len) in [("a", 100), ("a/b", 200), ("a/b/c", 300), ("d", 400)] {
+        populate(root.path(), dir, len);
+    }
+    backdate(&root.path().join("a/b"), Duration::from_secs(5 * 86_400));
+
+    let uncached = size::dir_usage(root.path());
+    let cache = ScanCache::in_memory();
+    let first = cache.enter(|| size::dir_usage(root.path()));
+    let second = cache.enter(|| size::dir_usage(root.path()));
+
+    assert_eq!(uncached.apparent_bytes, 1000);
+    assert_eq!(first, uncached);
+    assert_eq!(second, uncached);
+    assert_eq!(cache.len(), 5);
+}
+
+#[test]
+fn test_scan_cache_rereads_only_changed_directories() {
+    let root = TempDir::new().unwrap();
+    populate(root.path(), "a", 100);
+    populate(root.path(), "b", 100);
+    let cache = ScanCache::in_memory();
+    let cached_size = || cache.enter(|| size::dir_usage(root.path()).apparent_bytes);
+    assert_eq!(cached_size(), 200);
+
+    // Rewriting a file in place leaves its directory's mtime alone, so the
+    // recorded size stands; a new entry changes the mtime and is picked up.
+    std::fs::write(root.path().join("a/blob"), vec![0u8; 500]).unwrap();
+    assert_eq!(cached_size(), 200);
+    std::fs::write(root.path().join("b/new"), vec![0u8; 50]).unwrap();
+    assert_eq!(cached_size(), 250);
+    assert_eq!(size::dir_usage(root.path()).apparent_bytes, 650);
+
+    std::fs::remove_dir_all(root.path().join("b")).unwrap();
+    assert_eq!(cached_size(), 100);
+    assert_eq!(cache.len(), 2);
+}
+
+#[test]
+fn test_scan_cache_persists_between_scans() {
+    let home = TempDir::new().unwrap();
+    let pip = populate(home.path(), ".cache/pip", 100);
+    populate(&pip, "http/a", 200);
+    let path = home.path().join("state/scan-cache.json");
+
+    let recognizers: Vec<Box<dyn Recognizer>> = all_recognizers()
+        .into_iter()
+        .filter(|r| r.id() == "pip-cache")
+        .collect();
+    let ctx = ScanContext::new(
+        Locations::new(home.path(), Platform::Linux),
+        Config::default(),
+    );
+    let scan = |cache: &ScanCache| {
+        let options = ScanOptions {
+            cache: Some(cache.clone()),
+            ..Default::default()
+        };
+        let result = scanner::scan_with_context(&recognizers, &ctx, &options);
+        result.findings[0].apparent_bytes
+    };
+
+    let cache = ScanCache::at(&path);
+    assert!(cache.is_empty());
+    assert_eq!(scan(&cache), 300);
+    cache.save().unwrap();
+
+    let reopened = ScanCache::at(&path);
+    assert_eq!(reopened.len(), cache.len());
+    std::fs::write(pip.join("blob"), vec![0u8; 1000]).unwrap();
+    assert_eq!(scan(&reopened), 300);
+
+    assert!(reopened.clear().unwrap());
+    assert!(!path.exists());
+    assert_eq!(scan(&ScanCache::at(&path)), 1200);
+}
+
+#[test]
+fn test_scan_cache_not_trusted_for_age() {
+    let home = TempDir::new().unwrap();
+    let pip = populate(home.path(), ".cache/pip", 100);
+    backdate(&pip, Duration::from_secs(60 * 86_400));
+
+    let recognizers: Vec<Box<dyn Recognizer>> = all_recognizers()
+        .into_iter()
+        .filter(|r| r.id() == "pip-cache")
+        .collect();
+    let ctx = ScanContext::new(
+        Locations::new(home.path(), Platform::Linux),
+        Config::default(),
+    );
+    let cache = ScanCache::in_memory();
+    let scan = |older_than: Option<Duration>| {
+        let options = ScanOptions {
+            older_than,
+            cache: Some(cache.clone()),
+            ..Default::default()
+        };
+        scanner::scan_with_context(&recognizers, &ctx, &options).findings
+    };
+    let month = Some(Duration::from_secs(30 * 86_400));
+    assert_eq!(scan(month).len(), 1);
+
+    // Used in place: the directory's mtime and its record stay the same
+    std::fs::write(pip.join("blob"), vec![0u8; 100]).unwrap();
+    assert_eq!(scan(None).len(), 1);
+    assert!(scan(month).is_empty());
+}
+
+#[test]
+fn test_scan_cache_ignores_corrupt_file() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("scan-cache.json");
+    std::fs::write(&path, "not json").unwrap();
+
+    let cache = ScanCache::at(&path);
+    assert!(cache.is_empty());
+    cache.enter(|| size::dir_size(dir.path()));
+    cache.save().unwrap();
+    assert_eq!(ScanCache::at(&path).len(), 1);
+}
+
+#[test]
+fn test_project_max_depth_limits_search() {
+    let home = TempDir::new().unwrap();
+    let project = home.path().join("work/a/b/c");
+    std::fs::create_dir_all(&project).unwrap();
+    std::fs::write(project.join("package.json"), "{}").unwrap();
+    let node_modules = populate(&project, "node_modules", 2_000_000);
+
+    let shallow = scan_recognizer_with(
+        "node-modules",
+        Locations::new(home.path(), Platform::Linux),
+        config_with_roots(&["~/work"], 3),
+    );
+    assert!(shallow.is_empty());
+
+    let deep = scan_recognizer_with(
+        "node-modules",
+        Locations::new(home.path(), Platform::Linux),
+        config_with_roots(&["~/work"], 4),
+    );
+    assert_eq!(finding_paths(&deep), vec![node_modules]);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_project_follow_symlinks() {
+    let home = TempDir::new().unwrap();
+    let real = home.path().join("elsewhere/app");
+    std::fs::create_dir_all(&real).unwrap();
+    std::fs::write(real.join("package.json"), "{}").unwrap();
+    populate(&real, "node_modules", 2_000_000);
+    std::fs::create_dir_all(home.path().join("work")).unwrap();
+    std::os::unix::fs::symlink(&real, home.path().join("work/app")).unwrap();
+
+    let mut config = config_with_roots(&["~/work"], 5);
+    let not_followed = scan_recognizer_with(
+        "node-modules",
+        Locations::new(home.path(), Platform::Linux),
+        config.clone(),
+    );
+    assert!(not_followed.is_empty());
+
+    config.scan.follow_symlinks = true;
+    let followed = scan_recognizer_with(
+        "node-modules",
+        Locations::new(home.path(), Platform::Linux),
+        config,
+    );
+    assert_eq!(followed.len(), 1);
+}
+
+// ---------------------------------------------------------------------------
+// Shared project walker tests
+// ---------------------------------------------------------------------------
+
+/// A project detector that records which directories it was handed.
+struct MarkerProbe {
+    seen: std::sync::Mutex<Vec<PathBuf>>,
+}
+
+impl Recognizer for MarkerProbe {
+    fn name(&self) -> &'static str {
+        "Marker probe"
+    }
+    fn id(&self) -> &'static str {
+        "marker-probe"
+    }
+    fn category(&self) -> Category {
+        Category::Generic
+    }
+    fn scan(&self, _ctx: &ScanContext) -> Result<Vec<Finding>> {
+        Ok(vec![])
+    }
+    fn project_detector(&self) -> Option<&dyn ProjectDetector> {
+        Some(self)
+    }
+}
+
+impl ProjectDetector for MarkerProbe {
+    fn markers(&self) -> &[&'static str] {
+        &["probe.marker"]
+    }
+    fn artifact_dirs(&self) -> &[&'static str] {
+        &["probe-out"]
+    }
+    fn detect(&self, _ctx: &ScanContext, projects: &[PathBuf]) -> Vec<Finding> {
+        self.seen.lock().unwrap().extend_from_slice(projects);
+        vec![]
+    }
+}
+
+#[test]
+fn test_project_walker_dispatches_markers_and_prunes_artifacts() {
+    let home = TempDir::new().unwrap();
+    let work = home.path().join("work");
+    for dir in ["a", "b/c", "a/probe-out/nested", ".hidden/d"] {
+        std::fs::create_dir_all(work.join(dir)).unwrap();
+        std::fs::write(work.join(dir).join("probe.marker"), "").unwrap();
+    }
+
+    let probe = MarkerProbe {
+        seen: std::sync::Mutex::new(Vec::new()),
+    };
+    let ctx = ScanContext::new(
+        Locations::new(home.path(), Platform::Linux),
+        config_with_roots(&["~/work"], 5),
+    );
+    let detectors: Vec<&dyn ProjectDetector> = vec![&probe];
+    let projects = diskard_core::project::find_projects(&ctx, &detectors);
+
+    let mut found = projects[0].clone();
+    found.sort();
+    assert_eq!(found, vec![work.join("a"), work.join("b/c")]);
+}
+
+#[test]
+fn test_ignore_patterns_and_files_prune_project_walk() {
+    let home = TempDir::new().unwrap();
+    let work = home.path().join("work");
+    for dir in [
+        "a",
+        "vendor/x",
+        "b/vendor/y",
+        "keep-out/z",
+        "partial/q",
+        "partial/r",
+        "above/s",
+    ] {
+        std::fs::create_dir_all(work.join(dir)).unwrap();
+        std::fs::write(work.join(dir).join("probe.marker"), "").unwrap();
+    }
+    std::fs::write(work.join("keep-out/.diskardignore"), "# keep it all\n").unwrap();
+    std::fs::write(work.join("partial/.diskardignore"), "q\n").unwrap();
+    // Read once above the root the walk starts from
+    std::fs::write(home.path().join(".diskardignore"), "work/above\n").unwrap();
+
+    let probe = MarkerProbe {
+        seen: std::sync::Mutex::new(Vec::new()),
+    };
+    let mut config = config_with_roots(&["~/work"], 5);
+    config.ignore.patterns = vec!["**/vendor".into()];
+    let ctx = ScanContext::new(Locations::new(home.path(), Platform::Linux), config);
+    let detectors: Vec<&dyn ProjectDetector> = vec![&probe];
+    let projects = diskard_core::project::find_projects(&ctx, &detectors);
+
+    let mut found = projects[0].clone();
+    found.sort();
+    assert_eq!(found, vec![work.join("a"), work.join("partial/r")]);
+}
+
+#[test]
+fn test_diskardignore_negation_overrides_config_patterns() {
+    let home = TempDir::new().unwrap();
+    let mut targets = Vec::new();
+    for name in ["plain", "live"] {
+        let project = home.path().join("code").join(name);
+        std::fs::create_dir_all(&project).unwrap();
+        std::fs::write(project.join("Cargo.toml"), "[package]").unwrap();
+        targets.push(populate(&project, "target", 2_000_000));
+    }
+    std::fs::write(home.path().join("code/live/.diskardignore"), "!target\n").unwrap();
+
+    let mut config = config_with_roots(&["~/code"], 3);
+    config.ignore.patterns = vec!["target".into()];
+    let findings = scan_recognizer_with(
+        "cargo-target",
+        Locations::new(home.path(), Platform::Linux),
+        config,
+    );
+    assert_eq!(finding_paths(&findings), vec![targets[1].clone()]);
+}
+
+#[test]
+fn test_project_walker_feeds_every_detector_in_one_pass() {
+    let home = TempDir::new().unwrap();
+    let project = home.path().join("work/fullstack");
+    std::fs::create_dir_all(&project).unwrap();
+    std::fs::write(project.join("Cargo.toml"), "[package]").unwrap();
+    std::fs::write(project.join("package.json"), "{}").unwrap();
+    std::fs::write(project.join(".DS_Store"), vec![0u8; 64]).unwrap();
+    let target = populate(&project, "target", 2_000_000);
+    let node_modules = populate(&project, "node_modules", 2_000_000);
+
+    // A package.json inside node_modules must not produce a nested finding
+    let nested = node_modules.join("left-pad");
+    std::fs::create_dir_all(&nested).unwrap();
+    std::fs::write(nested.join("package.json"), "{}").unwrap();
+    populate(&nested, "node_modules", 2_000_000);
+
+    let recognizers: Vec<Box<dyn Recognizer>> = all_recognizers()
+        .into_iter()
+        .filter(|r| r.project_detector().is_some())
+        .collect();
+    let ctx = ScanContext::new(
+        Locations::new(home.path(), Platform::Linux),
+        config_with_roots(&["~/work"], 5),
+    );
+    let result = scanner::scan_with_context(&recognizers, &ctx, &ScanOptions::default());
+
+    let mut paths = finding_paths(&result.findings);
+    paths.sort();
+    let mut expected = vec![home.path().join("**/.DS_Store"), node_modules, target];
+    expected.sort();
+    assert_eq!(paths, expected);
+}
+
+// ---------------------------------------------------------------------------
+// Scanner tests (with fake recognizers)
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_scanner_runs_without_panic() {
+    let recognizers = all_recognizers();
+    let config = Config::default();
+    let options = ScanOptions::default();
+    let result = scanner::scan(&recognizers, &config, &options);
+    assert!(result.scan_duration.as_nanos() > 0);
+}
+
+/// A recognizer that panics while scanning.
+struct PanickingRecognizer;
+
+impl Recognizer for PanickingRecognizer {
+    fn name(&self) -> &'static str {
+        "Panicking"
+    }
+    fn id(&self) -> &'static str {
+        "panicking"
+    }
+    fn category(&self) -> Category {
+        Category::Generic
+    }
+    fn scan(&self, _ctx: &ScanContext) -> Result<Vec<Finding>> {
+        panic!("recognizer bug");
+    }
+}
+
+#[test]
+fn test_parallel_scan_matches_sequential() {
+    let home = TempDir::new().unwrap();
+    for name in ["a", "b", "c"] {
+        let project = home.path().join("code").join(name);
+        std::fs::create_dir_all(&project).unwrap();
+        std::fs::write(project.join("Cargo.toml"), "[package]").unwrap();
+        std::fs::write(project.join("package.json"), "{}").unwrap();
+        std::fs::write(project.join(".DS_Store"), "x").unwrap();
+        for i in 0..20 {
+            populate(&project, &format!("target/debug/deps/{i}"), 100_000);
+            populate(&project, &format!("node_modules/pkg{i}"), 100_000);
+        }
+    }
+    populate(home.path(), ".cache/pip/http", 5000);
+    populate(home.path(), ".npm/_cacache", 5000);
+
+    let ctx = ScanContext::new(
+        Locations::new(home.path(), Platform::Linux),
+        config_with_roots(&["~/code"], 3),
+    );
+    let recognizers = all_recognizers();
+    let summarize = |options: &ScanOptions| {
+        let result = scanner::scan_with_context(&recognizers, &ctx, options);
+        assert!(result.errors.is_empty(), "scan errors: {:?}", result.errors);
+        let mut found: Vec<(PathBuf, u64)> = result
+            .findings
+            .into_iter()
+            .map(|f| (f.path, f.size_bytes))
+            .collect();
+        found.sort();
+        found
+    };
+
+    let sequential = summarize(&ScanOptions {
+        sequential: true,
+        ..Default::default()
+    });
+    assert_eq!(sequential.len(), 9);
+
+    // A single I/O thread shared by every walk must still make progress
+    for io_threads in [1, 2, 8] {
+        let parallel = summarize(&ScanOptions {
+            io_threads,
+            ..Default::default()
+        });
+        assert_eq!(parallel, sequential, "io_threads = {io_threads}");
+    }
+}
+
+#[test]
+fn test_parallel_scan_reports_panicking_recognizer() {
+    let mut recognizers = fake_recognizers(vec![(
+        Category::Node,
+        vec![make_finding(
+            PathBuf::from("/fake/node_modules"),
+            Category::Node,
+            RiskLevel::Safe,
+            1000,
+            None,
+        )],
+    )]);
+    recognizers.push(Box::new(PanickingRecognizer));
+
+    let result = scanner::scan(&recognizers, &Config::default(), &ScanOptions::default());
+    assert_eq!(result.findings.len(), 1);
+    assert_eq!(result.errors.len(), 1);
+    assert_eq!(result.errors[0].recognizer_id, Some("panicking"));
+    assert_eq!(result.errors[0].kind, ErrorKind::Other);
+    assert!(result.errors[0].message.contains("panicking"));
+}
+
+/// Scan options that record every event into the returned log.
+fn recording_options(options: ScanOptions) -> (ScanOptions, Arc<Mutex<Vec<ScanEvent>>>) {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&log);
+    let options = ScanOptions {
+        on_event: Some(Arc::new(move |event| sink.lock().unwrap().push(event))),
+        ..options
+    };
+    (options, log)
+}
+
+#[test]
+fn test_scan_events_report_progress() {
+    let mut recognizers = fake_recognizers(vec![
+        (
+            Category::Node,
+            vec![
+                make_finding(
+                    PathBuf::from("/fake/a"),
+                    Category::Node,
+                    RiskLevel::Safe,
+                    300,
+                    None,
+                ),
+                make_finding(
+                    PathBuf::from("/fake/b"),
+                    Category::Node,
+                    RiskLevel::Risky,
+                    200,
+                    None,
+                ),
+            ],
+        ),
+        (
+            Category::Rust,
+            vec![make_finding(
+                PathBuf::from("/fake/c"),
+                Category::Rust,
+                RiskLevel::Safe,
+                100,
+                None,
+            )],
+        ),
+    ]);
+    recognizers.push(Box::new(PanickingRecognizer));
+
+    for sequential in [true, false] {
+        let (options, log) = recording_options(ScanOptions {
+            max_risk: RiskLevel::Moderate,
+            sequential,
+            ..Default::default()
+        });
+        let result = scanner::scan(&recognizers, &Config::default(), &options);
+        let events = log.lock().unwrap();
+
+        assert!(matches!(events[0], ScanEvent::Started { recognizers: 3 }));
+        let started = events
+            .iter()
+            .filter(|e| matches!(e, ScanEvent::RecognizerStarted { .. }))
+            .count();
+        assert_eq!(started, 3);
+
+        // Only findings that pass the filters are reported
+        let discovered: Vec<&Finding> = events
+            .iter()
+            .filter_map(|e| match e {
+                ScanEvent::FindingDiscovered { finding, .. } => Some(finding),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(discovered.len(), result.findings.len());
+        let last_total = events
+            .iter()
+            .filter_map(|e| match e {
+                ScanEvent::FindingDiscovered { total_bytes, .. } => Some(*total_bytes),
+                _ => None,
+            })
+            .max();
+        assert_eq!(last_total, Some(result.total_reclaimable));
+
+        assert!(events.iter().any(|e| matches!(
+            e,
+            ScanEvent::RecognizerFinished {
+                id: "fake-0",
+                findings: 1,
+                bytes: 300,
+                ..
+            }
+        )));
+        assert!(events.iter().any(|e| matches!(
+            e,
+            ScanEvent::RecognizerFailed {
+                id: "panicking",
+                ..
+            }
+        )));
+    }
+}
+
+#[test]
+fn test_sequential_scan_events_are_ordered_per_recognizer() {
+    let recognizers = fake_recognizers(vec![(
+        Category::Node,
+        vec![make_finding(
+            PathBuf::from("/fake/a"),
+            Category::Node,
+            RiskLevel::Safe,
+            10,
+            None,
+        )],
+    )]);
+    let (options, log) = recording_options(ScanOptions {
+        sequential: true,
+        ..Default::default()
+    });
+    scanner::scan(&recognizers, &Config::default(), &options);
+
+    let kinds: Vec<&str> = log
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|e| match e {
+            ScanEvent::Started { .. } => "start",
+            ScanEvent::RecognizerStarted { .. } => "recognizer",
+            ScanEvent::FindingDiscovered { .. } => "finding",
+            ScanEvent::RecognizerFinished { .. } => "finished",
+            ScanEvent::RecognizerFailed { .. } => "failed",
+        })
+        .collect();
+    assert_eq!(kinds, ["start", "recognizer", "finding", "finished"]);
+}
+
+#[test]
+fn test_scanner_respects_risk_filter() {
+    let findings = vec![
+        make_finding("/tmp/a".into(), Category::Node, RiskLevel::Safe, 100, None),
+        make_finding(
+            "/tmp/b".into(),
+            Category::Node,
+            RiskLevel::Moderate,
+            200,
+            None,
+        ),
+        make_finding("/tmp/c".into(), Category::Node, RiskLevel::Risky, 300, None),
+    ];
+    let recs = fake_recognizers(vec![(Category::Node, findings)]);
+    let config = Config::default();
+
+    // Filter: safe only
+    let opts = ScanOptions {
+        max_risk: RiskLevel::Safe,
+        ..Default::default()
+    };
+    let result = scanner::scan(&recs, &config, &opts);
+    assert_eq!(result.findings.len(), 1);
+    assert_eq!(result.findings[0].risk, RiskLevel::Safe);
+
+    // Filter: up to moderate
+    let opts = ScanOptions {
+        max_risk: RiskLevel::Moderate,
+        ..Default::default()
+    };
+    let result = scanner::scan(&recs, &config, &opts);
+    assert_eq!(result.findings.len(), 2);
+    for f in &result.findings {
+        assert!(f.risk <= RiskLevel::Moderate);
+    }
+}
+
+#[test]
+fn test_scanner_min_size_filter() {
+    let findings = vec![
+        make_finding(
+            "/tmp/small".into(),
+            Category::Generic,
+            RiskLevel::Safe,
+            50,
+            None,
+        ),
+        make_finding(
+            "/tmp/big".into(),
+            Category::Generic,
+            RiskLevel::Safe,
+            5000,
+            None,
+        ),
+    ];
+    let recs = fake_recognizers(vec![(Category::Generic, findings)]);
+    let config = Config::default();
+    let opts = ScanOptions {
+        min_size: 1000,
+        ..Default::default()
+    };
+    let result = scanner::scan(&recs, &config, &opts);
+    assert_eq!(result.findings.len(), 1);
+    assert_eq!(result.findings[0].size_bytes, 5000);
+}
+
+#[test]
+fn test_scanner_category_filter() {
+    let node_findings = vec![make_finding(
+        "/tmp/node".into(),
+        Category::Node,
+        RiskLevel::Safe,
+        100,
+        None,
+    )];
+    let xcode_findings = vec![make_finding(
+        "/tmp/xcode".into(),
+        Category::Xcode,
+        RiskLevel::Safe,
+        200,
+        None,
+    )];
+    let recs = fake_recognizers(vec![
+        (Category::Node, node_findings),
+        (Category::Xcode, xcode_findings),
+    ]);
+    let config = Config::default();
+    let opts = ScanOptions {
+        category: Some(Category::Xcode),
+        ..Default::default()
+    };
+    let result = scanner::scan(&recs, &config, &opts);
+    assert_eq!(result.findings.len(), 1);
+    assert_eq!(result.findings[0].category, Category::Xcode);
+}
+
+#[test]
+fn test_scanner_older_than_filter() {
+    let old = Duration::from_secs(60 * 60 * 24 * 30); // 30 days
+    let recent = Duration::from_secs(60); // 1 minute
+    let findings = vec![
+        make_finding(
+            "/tmp/old".into(),
+            Category::Generic,
+            RiskLevel::Safe,
+            100,
+            Some(old),
+        ),
+        make_finding(
+            "/tmp/new".into(),
+            Category::Generic,
+            RiskLevel::Safe,
+            200,
+            Some(recent),
+        ),
+    ];
+    let recs = fake_recognizers(vec![(Category::Generic, findings)]);
+    let config = Config::default();
+    let opts = ScanOptions {
+        older_than: Some(Duration::from_secs(60 * 60 * 24)), // 1 day
+        ..Default::default()
+    };
+    let result = scanner::scan(&recs, &config, &opts);
+    assert_eq!(result.findings.len(), 1);
+    assert!(result.findings[0].path.to_str().unwrap().contains("old"));
+}
+
+#[test]
+fn test_scanner_sort_by_size() {
+    let findings = vec![
+        make_finding(
+            "/tmp/small".into(),
+            Category::Generic,
+            RiskLevel::Safe,
+            10,
+            None,
+        ),
+        make_finding(
+            "/tmp/big".into(),
+            Category::Generic,
+            RiskLevel::Safe,
+            9999,
+            None,
+        ),
+        make_finding(
+            "/tmp/mid".into(),
+            Category::Generic,
+            RiskLevel::Safe,
+            500,
+            None,
+        ),
+    ];
+    let recs = fake_recognizers(vec![(Category::Generic, findings)]);
+    let config = Config::default();
+    let opts = ScanOptions {
+        sort: SortOrder::Size,
+        ..Default::default()
+    };
+    let result = scanner::scan(&recs, &config, &opts);
+    let sizes: Vec<u64> = result.findings.iter().map(|f| f.size_bytes).collect();
+    assert_eq!(
+        sizes,
+        vec![9999, 500, 10],
+        "Should be sorted descending by size"
+    );
+}
+
+#[test]
+fn test_scanner_sort_by_risk() {
+    let findings = vec![
+        make_finding(
+            "/tmp/safe".into(),
+            Category::Generic,
+            RiskLevel::Safe,
+            100,
+            None,
+        ),
+        make_finding(
+            "/tmp/risky".into(),
+            Category::Generic,
+            RiskLevel::Risky,
+            100,
+            None,
+        ),
+        make_finding(
+            "/tmp/moderate".into(),
+            Category::Generic,
+            RiskLevel::Moderate,
+            100,
+            None,
+        ),
+    ];
+    let recs = fake_recognizers(vec![(Category::Generic, findings)]);
+    let config = Config::default();
+    let opts = ScanOptions {
+        sort: SortOrder::Risk,
+        ..Default::default()
+    };
+    let result = scanner::scan(&recs, &config, &opts);
+    let risks: Vec<RiskLevel> = result.findings.iter().map(|f| f.risk).collect();
+    assert_eq!(
+        risks,
+        vec![RiskLevel::Risky, RiskLevel::Moderate, RiskLevel::Safe],
+        "Should be sorted descending by risk"
+    );
+}
+
+#[test]
+fn test_scanner_total_reclaimable() {
+    let findings = vec![
+        make_finding(
+            "/tmp/a".into(),
+            Category::Generic,
+            RiskLevel::Safe,
+            100,
+            None,
+        ),
+        make_finding(
+            "/tmp/b".into(),
+            Category::Generic,
+            RiskLevel::Safe,
+            250,
+            None,
+        ),
+    ];
+    let recs = fake_recognizers(vec![(Category::Generic, findings)]);
+    let config = Config::default();
+    let result = scanner::scan(&recs, &config, &ScanOptions::default());
+    assert_eq!(result.total_reclaimable, 350);
+}
+
+#[test]
+fn test_scanner_empty_recognizers() {
+    let recs: Vec<Box<dyn Recognizer>> = vec![];
+    let config = Config::default();
+    let result = scanner::scan(&recs, &config, &ScanOptions::default());
+    assert!(result.findings.is_empty());
+    assert_eq!(result.total_reclaimable, 0);
+}
+
+// ---------------------------------------------------------------------------
+// Cleaner tests (with temp dirs)
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_cleaner_dry_run() {
+    let tmp = TempDir::new().unwrap();
+    let file_path = tmp.path().join("testfile.txt");
+    std::fs::write(&file_path, "hello world").unwrap();
+
+    let findings = vec![Finding {
+        path: file_path.clone(),
+        category: Category::Generic,
+        recognizer_id: "test",
+        risk: RiskLevel::Safe,
+        size_bytes: 11,
+        apparent_bytes: 11,
+        description: "test".into(),
+        last_modified: None,
+        native: None,
+        extra_paths: Vec::new(),
+        projects: Vec::new(),
+    }];
+
+    let result = cleaner::clean(&findings, DeleteMode::DryRun).unwrap();
+    assert_eq!(result.deleted_count, 1);
+    assert_eq!(result.freed_bytes, 11);
+    assert!(file_path.exists(), "Dry run should NOT delete the file");
+}
+
+#[test]
+fn test_cleaner_permanent_delete_file() {
+    let tmp = TempDir::new().unwrap();
+    let file_path = tmp.path().join("to_delete.txt");
+    std::fs::write(&file_path, "goodbye").unwrap();
+    assert!(file_path.exists());
+    let on_disk = size::dir_size(&file_path);
+
+    let findings = vec![Finding {
+        path: file_path.clone(),
+        category: Category::Generic,
+        recognizer_id: "test",
+        risk: RiskLevel::Safe,
+        size_bytes: 7,
+        apparent_bytes: 7,
+        description: "test".into(),
+        last_modified: None,
+        native: None,
+        extra_paths: Vec::new(),
+        projects: Vec::new(),
+    }];
+
+    let result = cleaner::clean(&findings, DeleteMode::Permanent).unwrap();
+    assert_eq!(result.deleted_count, 1);
+    assert_eq!(result.freed_bytes, on_disk);
+    assert!(
+        !file_path.exists(),
+        "Permanent delete should remove the file"
+    );
+}
+
+#[test]
+fn test_cleaner_permanent_delete_directory() {
+    let tmp = TempDir::new().unwrap();
+    let dir_path = tmp.path().join("subdir");
+    std::fs::create_dir(&dir_path).unwrap();
+    std::fs::write(dir_path.join("a.txt"), "aaa").unwrap();
+    std::fs::write(dir_path.join("b.txt"), "bbb").unwrap();
+    assert!(dir_path.exists());
+
+    let findings = vec![Finding {
+        path: dir_path.clone(),
+        category: Category::Generic,
+        recognizer_id: "test",
+        risk: RiskLevel::Safe,
+        size_bytes: 6,
+        apparent_bytes: 6,
+        description: "test dir".into(),
+        last_modified: None,
+        native: None,
+        extra_paths: Vec::new(),
+        projects: Vec::new(),
+    }];
+
+    let result = cleaner::clean(&findings, DeleteMode::Permanent).unwrap();
+    assert_eq!(result.deleted_count, 1);
+    assert!(
+        !dir_path.exists(),
+        "Permanent delete should remove the directory"
+    );
+}
+
+#[test]
+fn test_cleaner_counts_nested_findings_once() {
+    let tmp = TempDir::new().unwrap();
+    let outer = populate(tmp.path(), "cache", 3000);
+    let inner = populate(&outer, "nested", 5000);
+    let findings = vec![
+        make_finding(
+            inner.clone(),
+            Category::Generic,
+            RiskLevel::Safe,
+            size::dir_size(&inner),
+            None,
+        ),
+        make_finding(
+            outer.clone(),
+            Category::Generic,
+            RiskLevel::Safe,
+            size::dir_size(&outer),
+            None,
+        ),
+    ];
+
+    let dry_run = cleaner::clean(&findings, DeleteMode::DryRun).unwrap();
+    assert_eq!(dry_run.deleted_count, 2);
+    assert!(inner.exists());
+
+    let permanent = cleaner::clean(&findings, DeleteMode::Permanent).unwrap();
+    assert!(permanent.errors.is_empty(), "{:?}", permanent.errors);
+    assert!(!outer.exists());
+    assert_eq!(dry_run.freed_bytes, permanent.freed_bytes);
+}
+
+#[test]
+fn test_cleaner_nonexistent_path_is_ok() {
+    let findings = vec![Finding {
+        path: PathBuf::from("/nonexistent/path/that/does/not/exist"),
+        category: Category::Generic,
+        recognizer_id: "test",
+        risk: RiskLevel::Safe,
+        size_bytes: 999,
+        apparent_bytes: 999,
+        description: "ghost".into(),
+        last_modified: None,
+        native: None,
+        extra_paths: Vec::new(),
+        projects: Vec::new(),
+    }];
+
+    let result = cleaner::clean(&findings, DeleteMode::Permanent).unwrap();
+    assert_eq!(result.deleted_count, 1);
+    assert!(result.errors.is_empty());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_cleaner_reports_permission_denied() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let tmp = TempDir::new().unwrap();
+    let dir = populate(tmp.path(), "app/node_modules", 10);
+    let locked = dir.join("locked");
+    std::fs::create_dir(&locked).unwrap();
+    std::fs::write(locked.join("file"), b"x").unwrap();
+    std::fs::set_permissions(&locked, std::fs::Permissions::from_mode(0o555)).unwrap();
+    let unlock = || std::fs::set_permissions(&locked, std::fs::Permissions::from_mode(0o755));
+
+    // Privileged users can delete regardless
+    if std::fs::write(locked.join("probe"), b"").is_ok() {
+        unlock().unwrap();
+        return;
+    }
+
+    let blob = size::dir_size(&dir.join("blob"));
+    let mut finding = make_finding(dir.clone(), Category::Node, RiskLevel::Safe, 10, None);
+    finding.recognizer_id = "node-modules";
+    let result = cleaner::clean(&[finding], DeleteMode::Permanent).unwrap();
+    let left = size::dir_size(&locked);
+    unlock().unwrap();
+
+    // What could go went; the rest is reported
+    assert_eq!(result.deleted_count, 0);
+    assert_eq!(result.freed_bytes, blob);
+    assert!(!dir.join("blob").exists());
+    assert!(locked.join("file").exists());
+    assert_eq!(result.remaining.len(), 1);
+    assert_eq!(result.remaining[0].path, dir);
+    assert_eq!(result.remaining[0].freed_bytes, blob);
+    assert_eq!(result.remaining[0].remaining_bytes, left);
+    assert_eq!(result.remaining[0].failed_entries, 1);
+
+    assert_eq!(result.errors.len(), 1);
+    let error = &result.errors[0];
+    assert_eq!(error.kind, ErrorKind::PermissionDenied);
+    assert_eq!(error.path.as_ref(), Some(&dir));
+    assert_eq!(error.recognizer_id, Some("node-modules"));
+
+    let json = serde_json::to_value(error).unwrap();
+    assert_eq!(json["kind"], "permission_denied");
+    assert_eq!(json["recognizer_id"], "node-modules");
+}
+
+/// A tree `width` directories wide and `depth` deep, with two files in each.
+fn populate_tree(root: &Path, width: usize, depth: usize) -> usize {
+    std::fs::create_dir_all(root).unwrap();
+    std::fs::write(root.join("a.js"), vec![0u8; 100]).unwrap();
+    std::fs::write(root.join("b.js"), vec![0u8; 5000]).unwrap();
+    if depth == 0 {
+        return 2;
+    }
+    2 + (0..width)
+        .map(|i| populate_tree(&root.join(format!("pkg{i}")), width, depth - 1))
+        .sum::<usize>()
+}
+
+#[test]
+fn test_clean_removes_trees_concurrently_with_progress() {
+    let tmp = TempDir::new().unwrap();
+    let roots: Vec<PathBuf> = (0..3)
+        .map(|i| tmp.path().join(format!("app{i}/node_modules")))
+        .collect();
+    let files: usize = roots.iter().map(|root| populate_tree(root, 4, 3)).sum();
+    let on_disk: u64 = roots.iter().map(|root| size::dir_size(root)).sum();
+    let findings: Vec<Finding> = roots
+        .iter()
+        .map(|root| make_finding(root.clone(), Category::Node, RiskLevel::Safe, 1, None))
+        .collect();
+
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let log = Arc::clone(&events);
+    let options = CleanOptions {
+        mode: DeleteMode::Permanent,
+        on_event: Some(Arc::new(move |event| log.lock().unwrap().push(event))),
+        ..Default::default()
+    };
+    let result = cleaner::clean_with(&findings, &options).unwrap();
+    assert_eq!(result.deleted_count, 3);
+    assert_eq!(result.freed_bytes, on_disk);
+    assert!(result.errors.is_empty());
+    assert!(roots.iter().all(|root| !root.exists()));
+    assert!(tmp.path().join("app0").exists());
+
+    let events = events.lock().unwrap();
+    assert!(matches!(
+        events[0],
+        CleanEvent::Started {
+            findings: 3,
+            total_bytes: 3
+        }
+    ));
+    let (mut most_files, mut most_bytes) = (0, 0);
+    for event in events.iter() {
+        if let CleanEvent::Progress {
+            files_removed,
+            bytes_freed,
+        } = event
+        {
+            most_files = most_files.max(*files_removed);
+            most_bytes = most_bytes.max(*bytes_freed);
+        }
+    }
+    assert_eq!(most_files, files as u64);
+    assert_eq!(most_bytes, on_disk);
+    let finished = events
+        .iter()
+        .filter(|event| matches!(event, CleanEvent::FindingFinished { .. }))
+        .count();
+    assert_eq!(finished, 3);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_remove_tree_counts_hard_links_once() {
+    let tmp = TempDir::new().unwrap();
+    let dir = populate(tmp.path(), "cache", 8192);
+    std::fs::hard_link(dir.join("blob"), dir.join("blob-link")).unwrap();
+    std::fs::write(tmp.path().join("shared"), vec![1u8; 8192]).unwrap();
+    std::fs::hard_link(tmp.path().join("shared"), dir.join("shared-link")).unwrap();
+    std::os::unix::fs::symlink(tmp.path().join("shared"), dir.join("symlink")).unwrap();
+    let blob = size::allocated_bytes(&dir.join("blob").metadata().unwrap());
+    let shared = size::allocated_bytes(&tmp.path().join("shared").metadata().unwrap());
+
+    let removal = cleaner::remove_tree(&dir);
+    assert!(removal.failures.is_empty());
+    assert!(!dir.exists());
+    // The linked-in file survives elsewhere, so it frees nothing
+    assert!(tmp.path().join("shared").exists());
+    assert!(removal.freed_bytes >= blob);
+    assert!(removal.freed_bytes < blob + shared);
+}
+
+/// Write an executable shell script named `name` into `dir`.
+#[cfg(unix)]
+fn stub_tool(dir: &Path, name: &str, script: &str) -> PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::create_dir_all(dir).unwrap();
+    let path = dir.join(name);
+    std::fs::write(&path, format!("#!/bin/sh\n{script}\n")).unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    path
+}
+
+#[cfg(unix)]
+#[test]
+fn test_native_clean_runs_command_or_falls_back() {
+    let tmp = TempDir::new().unwrap();
+    let tool = stub_tool(
+        &tmp.path().join("bin"),
+        "purge",
+        r#"[ "$(pwd)" = "$2" ] || exit 9; rm -rf "$1"/*"#,
+    );
+    let native = |path: &Path| {
+        let mut finding =
+            make_finding(path.to_path_buf(), Category::Node, RiskLevel::Safe, 1, None);
+        finding.native = Some(NativeAction::new(format!(
+            "{} {{path}} {{project}}",
+            tool.display()
+        )));
+        finding
+    };
+    let cache = populate(tmp.path(), "app/cache", 8192);
+    let missing_tool = populate(tmp.path(), "app/missing", 4096);
+    let plain = populate(tmp.path(), "app/plain", 4096);
+    let on_disk = size::dir_size(&cache);
+    let mut findings = vec![native(&cache), native(&missing_tool)];
+    findings[1].native = Some(NativeAction::new("diskard-no-such-tool {path}"));
+    findings.push(make_finding(
+        plain.clone(),
+        Category::Node,
+        RiskLevel::Safe,
+        1,
+        None,
+    ));
+
+    let journal = Journal::at(tmp.path().join("journal.jsonl"));
+    let options = CleanOptions {
+        mode: DeleteMode::Native,
+        native_fallback: DeleteMode::Permanent,
+        journal: Some(journal.clone()),
+        ..Default::default()
+    };
+    let result = cleaner::clean_with(&findings, &options).unwrap();
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    assert_eq!(result.deleted_count, 3);
+    // The tool empties its cache but leaves the directory
+    assert!(cache.exists());
+    assert_eq!(std::fs::read_dir(&cache).unwrap().count(), 0);
+    assert!(result.freed_bytes >= on_disk);
+    // Without a usable command, findings are deleted the fallback way
+    assert!(!missing_tool.exists());
+    assert!(!plain.exists());
+
+    let modes: Vec<DeleteMode> = journal.entries().unwrap().iter().map(|e| e.mode).collect();
+    assert_eq!(
+        modes,
+        vec![
+            DeleteMode::Native,
+            DeleteMode::Permanent,
+            DeleteMode::Permanent
+        ]
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_native_clean_reports_failures_and_timeouts() {
+    let tmp = TempDir::new().unwrap();
+    let bin = tmp.path().join("bin");
+    let failing = stub_tool(&bin, "failing", "echo 'cache is locked' >&2; exit 3");
+    // What the tool starts is stopped along with it
+    let marker = tmp.path().join("marker");
+    let slow = stub_tool(
+        &bin,
+        "slow",
+        &format!("(sleep 1; touch '{}') & sleep 30", marker.display()),
+    );
+    let findings: Vec<Finding> = [(&failing, "a"), (&slow, "b")]
+        .into_iter()
+        .map(|(tool, dir)| {
+            let path = populate(tmp.path(), dir, 1024);
+            let mut finding = make_finding(path, Category::Node, RiskLevel::Safe, 1, None);
+            finding.native = Some(NativeAction::new(tool.display().to_string()));
+            finding
+        })
+        .collect();
+
+    let options = CleanOptions {
+        mode: DeleteMode::Native,
+        native_timeout: Duration::from_millis(300),
+        ..Default::default()
+    };
+    let started = std::time::Instant::now();
+    let result = cleaner::clean_with(&findings, &options).unwrap();
+    assert!(started.elapsed() < Duration::from_secs(10));
+    assert_eq!(result.deleted_count, 0);
+    assert_eq!(result.errors.len(), 2);
+    assert!(result.errors.iter().all(|e| e.kind == ErrorKind::Native));
+    assert!(result.errors[0].message.contains("cache is locked"));
+    assert!(result.errors[1].message.contains("timed out"));
+    assert!(findings.iter().all(|f| f.path.exists()));
+    std::thread::sleep(Duration::from_millis(1500));
+    assert!(!marker.exists());
+
+    // A lone path has no command to run
+    let error = cleaner::delete_path(
+        &findings[0].path,
+        DeleteMode::Native,
+        findings[0].recognizer_id,
+    )
+    .unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::Native);
+    assert!(findings[0].path.exists());
+}
+
+#[test]
+fn test_native_actions_from_recognizers_and_config() {
+    let home = TempDir::new().unwrap();
+    let project = home.path().join("code/app");
+    std::fs::create_dir_all(&project).unwrap();
+    std::fs::write(project.join("Cargo.toml"), "[package]").unwrap();
+    let target = populate(&project, "target", 2_000_000);
+    let locations = || Locations::new(home.path(), Platform::Linux);
+
+    let mut config = config_with_roots(&["~/code"], 3);
+    let findings = scan_recognizer_with("cargo-target", locations(), config.clone());
+    let manifest = project.join("Cargo.toml");
+    assert_eq!(
+        findings[0].native.as_ref().unwrap().argv(&target),
+        vec![
+            "cargo".to_string(),
+            "clean".into(),
+            "--manifest-path".into(),
+            manifest.display().to_string(),
+        ]
+    );
+
+    config.recognizers.settings.insert(
+        "cargo-target".into(),
+        RecognizerSettings {
+            native: Some("cargo-sweep --time 30 {project}".into()),
+            ..Default::default()
+        },
+    );
+    let findings = scan_recognizer_with("cargo-target", locations(), config);
+    assert_eq!(
+        findings[0].native.as_ref().unwrap().argv(&target),
+        vec![
+            "cargo-sweep".to_string(),
+            "--time".into(),
+            "30".into(),
+            project.display().to_string(),
+        ]
+    );
+}
+
+/// `diskard clean --native` runs the tool it finds on `PATH`.
+#[cfg(unix)]
+#[test]
+fn test_clean_native_uses_tool_on_path() {
+    let tmp = TempDir::new().unwrap();
+    let home = tmp.path().join("home");
+    let cache = populate(&home, ".npm/_cacache", 8192);
+    let bin = tmp.path().join("bin");
+    stub_tool(
+        &bin,
+        "npm",
+        r#"echo "$@" > "$HOME/npm-args"; rm -rf "$HOME/.npm/_cacache""#,
+    );
+    let path = std::env::join_paths(
+        std::iter::once(bin).chain(std::env::split_paths(&std::env::var_os("PATH").unwrap())),
+    )
+    .unwrap();
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_diskard"))
+        .args([
+            "clean",
+            "--native",
+            "--category",
+            "node",
+            "--yes",
+            "--format",
+            "json",
+        ])
+        .env("HOME", &home)
+        .env("PATH", path)
+        .env("XDG_CONFIG_HOME", tmp.path().join("config"))
+        .env("XDG_DATA_HOME", tmp.path().join("data"))
+        .env("XDG_CACHE_HOME", tmp.path().join("cache"))
+        .env_remove("npm_config_cache")
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["mode"], "native");
+    assert_eq!(report["deleted_count"], 1);
+    assert!(report["freed_bytes"].as_u64().unwrap() > 0);
+    assert_eq!(
+        std::fs::read_to_string(home.join("npm-args"))
+            .unwrap()
+            .trim(),
+        "cache clean --force"
+    );
+    assert!(!cache.exists());
+    assert!(home.join(".npm").exists());
+}
+
+#[test]
+fn test_cleaner_empty_findings() {
+    let result = cleaner::clean(&[], DeleteMode::Permanent).unwrap();
+    assert_eq!(result.deleted_count, 0);
+    assert_eq!(result.freed_bytes, 0);
+    assert!(result.errors.is_empty());
+}
+
+#[test]
+fn test_cleaner_multiple_findings() {
+    let tmp = TempDir::new().unwrap();
+    let f1 = tmp.path().join("f1.txt");
+    let f2 = tmp.path().join("f2.txt");
+    let f3 = tmp.path().join("f3.txt");
+    std::fs::write(&f1, "one").unwrap();
+    std::fs::write(&f2, "two").unwrap();
+    std::fs::write(&f3, "three").unwrap();
+    let on_disk: u64 = [&f1, &f2, &f3].iter().map(|f| size::dir_size(f)).sum();
+
+    let findings: Vec<Finding> = [(&f1, 3u64), (&f2, 3), (&f3, 5)]
+        .iter()
+        .map(|(p, s)| Finding {
+            path: p.to_path_buf(),
+            category: Category::Generic,
+            recognizer_id: "test",
+            risk: RiskLevel::Safe,
+            size_bytes: *s,
+            apparent_bytes: *s,
+            description: "test".into(),
+            last_modified: None,
+            native: None,
+            extra_paths: Vec::new(),
+            projects: Vec::new(),
+        })
+        .collect();
+
+    let result = cleaner::clean(&findings, DeleteMode::Permanent).unwrap();
+    assert_eq!(result.deleted_count, 3);
+    assert_eq!(result.freed_bytes, on_disk);
+    assert!(!f1.exists());
+    assert!(!f2.exists());
+    assert!(!f3.exists());
+}
+
+// ---------------------------------------------------------------------------
+// Journal tests
+// ---------------------------------------------------------------------------
+
+fn journaled(mode: DeleteMode, journal: &Journal) -> CleanOptions {
+    CleanOptions {
+        mode,
+        journal: Some(journal.clone()),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_clean_appends_to_journal() {
+    let tmp = TempDir::new().unwrap();
+    let journal = Journal::at(tmp.path().join("state/journal.jsonl"));
+    let dir = populate(tmp.path(), "node_modules", 10);
+    let file = tmp.path().join("cache.bin");
+    std::fs::write(&file, "abc").unwrap();
+
+    let mut findings = vec![
+        make_finding(dir.clone(), Category::Node, RiskLevel::Safe, 10, None),
+        make_finding(file.clone(), Category::Generic, RiskLevel::Safe, 3, None),
+    ];
+    findings[0].recognizer_id = "node-modules";
+
+    cleaner::clean_with(&findings, &journaled(DeleteMode::Permanent, &journal)).unwrap();
+
+    let entries = journal.entries().unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].id, 1);
+    assert_eq!(entries[0].path, dir);
+    assert_eq!(entries[0].recognizer_id, "node-modules");
+    assert_eq!(entries[0].mode, DeleteMode::Permanent);
+    assert_eq!(entries[0].size_bytes, 10);
+    assert_eq!(entries[1].id, 2);
+    assert_eq!(entries[1].path, file);
+    assert!(entries.iter().all(|e| !e.is_restorable()));
+
+    // IDs keep increasing across clean operations
+    let again = populate(tmp.path(), "again", 1);
+    let findings = vec![make_finding(
+        again,
+        Category::Generic,
+        RiskLevel::Safe,
+        1,
+        None,
+    )];
+    cleaner::clean_with(&findings, &journaled(DeleteMode::Permanent, &journal)).unwrap();
+    assert_eq!(journal.entries().unwrap().last().unwrap().id, 3);
+}
+
+#[test]
+fn test_dry_run_and_missing_paths_are_not_journaled() {
+    let tmp = TempDir::new().unwrap();
+    let journal = Journal::at(tmp.path().join("journal.jsonl"));
+    let dir = populate(tmp.path(), "keep", 10);
+
+    let findings = vec![make_finding(
+        dir.clone(),
+        Category::Generic,
+        RiskLevel::Safe,
+        10,
+        None,
+    )];
+    cleaner::clean_with(&findings, &journaled(DeleteMode::DryRun, &journal)).unwrap();
+    assert!(dir.exists());
+
+    let ghost = vec![make_finding(
+        tmp.path().join("ghost"),
+        Category::Generic,
+        RiskLevel::Safe,
+        10,
+        None,
+    )];
+    cleaner::clean_with(&ghost, &journaled(DeleteMode::Permanent, &journal)).unwrap();
+
+    assert!(journal.entries().unwrap().is_empty());
+    assert!(!journal.path().exists());
+}
+
+#[test]
+fn test_journal_find_by_id_and_path() {
+    let tmp = TempDir::new().unwrap();
+    let journal = Journal::at(tmp.path().join("journal.jsonl"));
+    let dir = populate(tmp.path(), "target", 10);
+
+    let findings = vec![make_finding(
+        dir.clone(),
+        Category::Rust,
+        RiskLevel::Moderate,
+        10,
+        None,
+    )];
+    cleaner::clean_with(&findings, &journaled(DeleteMode::Permanent, &journal)).unwrap();
+
+    assert_eq!(journal.find("1").unwrap().unwrap().path, dir);
+    assert!(journal.find("42").unwrap().is_none());
+    // Lookup by path only matches entries that can still be restored
+    assert!(journal.find(dir.to_str().unwrap()).unwrap().is_none());
+}
+
+#[test]
+fn test_restore_refuses_permanent_deletes() {
+    let tmp = TempDir::new().unwrap();
+    let journal = Journal::at(tmp.path().join("journal.jsonl"));
+    let dir = populate(tmp.path(), "gone", 10);
+
+    let findings = vec![make_finding(
+        dir,
+        Category::Generic,
+        RiskLevel::Safe,
+        10,
+        None,
+    )];
+    cleaner::clean_with(&findings, &journaled(DeleteMode::Permanent, &journal)).unwrap();
+
+    let entry = journal.find("1").unwrap().unwrap();
+    let quarantine = Quarantine::at(tmp.path().join("quarantine"));
+    let err = journal::restore(&journal, &quarantine, &entry).unwrap_err();
+    assert!(err.to_string().contains("cannot be restored"));
+    assert!(journal.entries().unwrap()[0].restored_at.is_none());
+}
+
+#[test]
+fn test_journal_mark_restored() {
+    let tmp = TempDir::new().unwrap();
+    let journal = Journal::at(tmp.path().join("journal.jsonl"));
+    let dir = populate(tmp.path(), "x", 1);
+    let findings = vec![make_finding(
+        dir,
+        Category::Generic,
+        RiskLevel::Safe,
+        1,
+        None,
+    )];
+    cleaner::clean_with(&findings, &journaled(DeleteMode::Permanent, &journal)).unwrap();
+
+    journal.mark_restored(1).unwrap();
+    assert!(journal.entries().unwrap()[0].restored_at.is_some());
+    assert!(journal.mark_restored(7).is_err());
+    let files: Vec<_> = std::fs::read_dir(tmp.path()).unwrap().flatten().collect();
+    assert_eq!(files.len(), 1, "{files:?}");
+}
+
+#[test]
+fn test_clean_result_survives_journal_failure() {
+    let tmp = TempDir::new().unwrap();
+    // A directory where the journal file should be can't be read or appended to
+    let journal = Journal::at(tmp.path().join("journal.jsonl"));
+    std::fs::create_dir(journal.path()).unwrap();
+    let dir = populate(tmp.path(), "x", 10);
+    let findings = vec![make_finding(
+        dir.clone(),
+        Category::Generic,
+        RiskLevel::Safe,
+        10,
+        None,
+    )];
+
+    let result =
+        cleaner::clean_with(&findings, &journaled(DeleteMode::Permanent, &journal)).unwrap();
+    assert!(!dir.exists());
+    assert_eq!(result.deleted_count, 1);
+    assert_eq!(result.errors.len(), 1);
+    assert!(result.errors[0]
+        .message
+        .starts_with("Cleaned, but not recorded in the journal"));
 }
 
 // ---------------------------------------------------------------------------
-// Cleaner tests (with temp dirs)
+// Quarantine tests
 // ---------------------------------------------------------------------------
 
 #[test]
-fn test_cleaner_dry_run() {
+fn test_quarantine_store_and_restore() {
     let tmp = TempDir::new().unwrap();
-    let file_path = tmp.path().join("testfile.txt");
-    std::fs::write(&file_path, "hello world").unwrap();
-
-    let findings = vec![Finding {
-        path: file_path.clone(),
-        category: Category::Generic,
-        risk: RiskLevel::Safe,
-        size_bytes: 11,
-        description: "test".into(),
-        last_modified: None,
-    }];
-
-    let result = cleaner::clean(&findings, DeleteMode::DryRun).unwrap();
-    assert_eq!(result.deleted_count, 1);
-    assert_eq!(result.freed_bytes, 11);
-    assert!(file_path.exists(), "Dry run should NOT delete the file");
+    let quarantine = Quarantine::at(tmp.path().join("quarantine"));
+    let dir = populate(tmp.path(), "project/target", 10);
+
+    let entry = quarantine.store(&dir, 10, "cargo-target").unwrap();
+    assert!(!dir.exists());
+    assert!(entry.stored_path.starts_with(quarantine.root()));
+    assert_eq!(entry.stored_path.file_name().unwrap(), "target");
+    assert!(entry.stored_path.join("blob").exists());
+    assert_eq!(entry.id, 1);
+
+    let entries = quarantine.entries().unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].original_path, dir);
+    assert_eq!(entries[0].recognizer_id, "cargo-target");
+    assert_eq!(entries[0].size_bytes, 10);
+
+    let found = quarantine.find(dir.to_str().unwrap()).unwrap().unwrap();
+    assert_eq!(found.id, entry.id);
+
+    quarantine.restore(&found).unwrap();
+    assert!(dir.join("blob").exists());
+    assert!(quarantine.entries().unwrap().is_empty());
+    assert!(!entry.stored_path.exists());
 }
 
 #[test]
-fn test_cleaner_permanent_delete_file() {
+fn test_quarantine_restore_refuses_existing_path() {
     let tmp = TempDir::new().unwrap();
-    let file_path = tmp.path().join("to_delete.txt");
-    std::fs::write(&file_path, "goodbye").unwrap();
-    assert!(file_path.exists());
+    let quarantine = Quarantine::at(tmp.path().join("quarantine"));
+    let dir = populate(tmp.path(), "node_modules", 10);
 
-    let findings = vec![Finding {
-        path: file_path.clone(),
-        category: Category::Generic,
-        risk: RiskLevel::Safe,
-        size_bytes: 7,
-        description: "test".into(),
-        last_modified: None,
-    }];
+    let entry = quarantine.store(&dir, 10, "node-modules").unwrap();
+    populate(tmp.path(), "node_modules", 5);
 
-    let result = cleaner::clean(&findings, DeleteMode::Permanent).unwrap();
-    assert_eq!(result.deleted_count, 1);
-    assert_eq!(result.freed_bytes, 7);
-    assert!(
-        !file_path.exists(),
-        "Permanent delete should remove the file"
-    );
+    let err = quarantine.restore(&entry).unwrap_err();
+    assert!(err.to_string().contains("already exists"));
+    assert!(entry.stored_path.exists());
+    assert_eq!(quarantine.entries().unwrap().len(), 1);
 }
 
 #[test]
-fn test_cleaner_permanent_delete_directory() {
+fn test_quarantine_ids_do_not_collide() {
     let tmp = TempDir::new().unwrap();
-    let dir_path = tmp.path().join("subdir");
-    std::fs::create_dir(&dir_path).unwrap();
-    std::fs::write(dir_path.join("a.txt"), "aaa").unwrap();
-    std::fs::write(dir_path.join("b.txt"), "bbb").unwrap();
-    assert!(dir_path.exists());
+    let quarantine = Quarantine::at(tmp.path().join("quarantine"));
+    let a = populate(tmp.path(), "a/cache", 1);
+    let b = populate(tmp.path(), "b/cache", 1);
+
+    let first = quarantine.store(&a, 1, "test").unwrap();
+    let second = quarantine.store(&b, 1, "test").unwrap();
+    assert_ne!(first.id, second.id);
+    assert_ne!(first.stored_path, second.stored_path);
+    assert!(first.stored_path.exists());
+    assert!(second.stored_path.exists());
+}
 
-    let findings = vec![Finding {
-        path: dir_path.clone(),
-        category: Category::Generic,
-        risk: RiskLevel::Safe,
-        size_bytes: 6,
-        description: "test dir".into(),
-        last_modified: None,
-    }];
+#[test]
+fn test_quarantine_purge_respects_age() {
+    let tmp = TempDir::new().unwrap();
+    let quarantine = Quarantine::at(tmp.path().join("quarantine"));
+    let dir = populate(tmp.path(), "cache", 10);
+    let entry = quarantine.store(&dir, 10, "test").unwrap();
+
+    let result = quarantine
+        .purge(Duration::from_secs(7 * 86_400), None)
+        .unwrap();
+    assert_eq!(result.purged_count, 0);
+    assert!(entry.stored_path.exists());
+
+    let result = quarantine.purge(Duration::ZERO, None).unwrap();
+    assert_eq!(result.purged_count, 1);
+    assert_eq!(result.freed_bytes, 10);
+    assert!(result.errors.is_empty());
+    assert!(!entry.stored_path.exists());
+    assert!(quarantine.entries().unwrap().is_empty());
+}
 
-    let result = cleaner::clean(&findings, DeleteMode::Permanent).unwrap();
+#[test]
+fn test_clean_quarantine_mode_is_journaled_and_restorable() {
+    let tmp = TempDir::new().unwrap();
+    let journal = Journal::at(tmp.path().join("journal.jsonl"));
+    let quarantine = Quarantine::at(tmp.path().join("quarantine"));
+    let dir = populate(tmp.path(), "app/node_modules", 10);
+
+    let mut finding = make_finding(dir.clone(), Category::Node, RiskLevel::Safe, 10, None);
+    finding.recognizer_id = "node-modules";
+    let options = CleanOptions {
+        mode: DeleteMode::Quarantine,
+        journal: Some(journal.clone()),
+        quarantine: Some(quarantine.clone()),
+        ..Default::default()
+    };
+    let result = cleaner::clean_with(&[finding], &options).unwrap();
     assert_eq!(result.deleted_count, 1);
-    assert!(
-        !dir_path.exists(),
-        "Permanent delete should remove the directory"
-    );
+    assert_eq!(result.freed_bytes, 10);
+    assert!(!dir.exists());
+
+    let held = quarantine.entries().unwrap();
+    assert_eq!(held.len(), 1);
+    assert_eq!(held[0].recognizer_id, "node-modules");
+
+    let recorded = journal.find("1").unwrap().unwrap();
+    assert_eq!(recorded.mode, DeleteMode::Quarantine);
+    assert_eq!(recorded.trash_location.as_ref(), Some(&held[0].stored_path));
+    assert!(recorded.is_restorable());
+
+    journal::restore(&journal, &quarantine, &recorded).unwrap();
+    assert!(dir.join("blob").exists());
+    assert!(quarantine.entries().unwrap().is_empty());
+    assert!(journal.entries().unwrap()[0].restored_at.is_some());
 }
 
 #[test]
-fn test_cleaner_nonexistent_path_is_ok() {
-    let findings = vec![Finding {
-        path: PathBuf::from("/nonexistent/path/that/does/not/exist"),
-        category: Category::Generic,
-        risk: RiskLevel::Safe,
-        size_bytes: 999,
-        description: "ghost".into(),
-        last_modified: None,
-    }];
+fn test_quarantine_purge_marks_journal() {
+    let tmp = TempDir::new().unwrap();
+    let journal = Journal::at(tmp.path().join("journal.jsonl"));
+    let quarantine = Quarantine::at(tmp.path().join("quarantine"));
+    let dir = populate(tmp.path(), "app/node_modules", 10);
+
+    let finding = make_finding(dir.clone(), Category::Node, RiskLevel::Safe, 10, None);
+    let options = CleanOptions {
+        quarantine: Some(quarantine.clone()),
+        ..journaled(DeleteMode::Quarantine, &journal)
+    };
+    cleaner::clean_with(&[finding], &options).unwrap();
+    let stored = quarantine.entries().unwrap()[0].stored_path.clone();
 
-    let result = cleaner::clean(&findings, DeleteMode::Permanent).unwrap();
-    assert_eq!(result.deleted_count, 1);
+    let result = quarantine.purge(Duration::ZERO, Some(&journal)).unwrap();
+    assert_eq!(result.purged_count, 1);
     assert!(result.errors.is_empty());
+
+    // History shows the item as purged and no longer offers it for restore
+    let recorded = &journal.entries().unwrap()[0];
+    assert!(recorded.purged_at.is_some());
+    assert!(!recorded.is_restorable());
+    assert!(journal.find(&dir.to_string_lossy()).unwrap().is_none());
+    assert!(journal.find_by_location(&stored).unwrap().is_none());
+    assert!(journal::restore(&journal, &quarantine, recorded).is_err());
+}
+
+// ---------------------------------------------------------------------------
+// In-use tests
+// ---------------------------------------------------------------------------
+
+/// A `sleep` running inside `dir`, killed when dropped.
+#[cfg(target_os = "linux")]
+struct Sleeper(std::process::Child);
+
+#[cfg(target_os = "linux")]
+impl Sleeper {
+    fn spawn_in(dir: &Path) -> Option<Self> {
+        let child = std::process::Command::new("sleep")
+            .arg("30")
+            .current_dir(dir)
+            .spawn()
+            .ok()?;
+        Some(Self(child))
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Drop for Sleeper {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
 }
 
+#[cfg(target_os = "linux")]
 #[test]
-fn test_cleaner_empty_findings() {
-    let result = cleaner::clean(&[], DeleteMode::Permanent).unwrap();
-    assert_eq!(result.deleted_count, 0);
-    assert_eq!(result.freed_bytes, 0);
-    assert!(result.errors.is_empty());
+fn test_processes_using_finds_working_directory() {
+    let tmp = TempDir::new().unwrap();
+    let busy = populate(tmp.path(), "busy/node_modules", 10);
+    let idle = populate(tmp.path(), "idle/node_modules", 10);
+    let Some(sleeper) = Sleeper::spawn_in(&busy) else {
+        return;
+    };
+
+    let uses = processes::processes_using(&[&busy, &idle, tmp.path()]);
+    assert_eq!(uses.len(), 3);
+    assert!(uses[0].iter().any(|u| u.pid == sleeper.0.id()));
+    assert!(uses[0].iter().all(|u| u.pid != std::process::id()));
+    assert!(uses[1].is_empty());
+    assert!(uses[2].iter().any(|u| u.name == "sleep"));
 }
 
+#[cfg(target_os = "linux")]
 #[test]
-fn test_cleaner_multiple_findings() {
+fn test_clean_refuses_findings_in_use() {
     let tmp = TempDir::new().unwrap();
-    let f1 = tmp.path().join("f1.txt");
-    let f2 = tmp.path().join("f2.txt");
-    let f3 = tmp.path().join("f3.txt");
-    std::fs::write(&f1, "one").unwrap();
-    std::fs::write(&f2, "two").unwrap();
-    std::fs::write(&f3, "three").unwrap();
+    let busy = populate(tmp.path(), "busy/node_modules", 10);
+    let idle = populate(tmp.path(), "idle/node_modules", 10);
+    let Some(_sleeper) = Sleeper::spawn_in(&busy) else {
+        return;
+    };
+    let findings = [
+        make_finding(busy.clone(), Category::Node, RiskLevel::Safe, 10, None),
+        make_finding(idle.clone(), Category::Node, RiskLevel::Safe, 10, None),
+    ];
 
-    let findings: Vec<Finding> = [(&f1, 3u64), (&f2, 3), (&f3, 5)]
-        .iter()
-        .map(|(p, s)| Finding {
-            path: p.to_path_buf(),
-            category: Category::Generic,
-            risk: RiskLevel::Safe,
-            size_bytes: *s,
-            description: "test".into(),
-            last_modified: None,
-        })
-        .collect();
+    let options = CleanOptions {
+        mode: DeleteMode::Permanent,
+        ..Default::default()
+    };
+    let result = cleaner::clean_with(&findings, &options).unwrap();
+    assert_eq!(result.deleted_count, 1);
+    assert!(busy.exists());
+    assert!(!idle.exists());
+    assert_eq!(result.errors.len(), 1);
+    assert_eq!(result.errors[0].path.as_ref(), Some(&busy));
+    assert_eq!(result.errors[0].kind, ErrorKind::InUse);
+    assert!(result.errors[0].message.contains("sleep (pid"));
+
+    let options = CleanOptions {
+        mode: DeleteMode::Permanent,
+        in_use: InUse::Warn,
+        ..Default::default()
+    };
+    let result = cleaner::clean_with(&findings[..1], &options).unwrap();
+    assert_eq!(result.deleted_count, 1);
+    assert!(result.errors.is_empty());
+    assert!(!busy.exists());
+    assert_eq!(result.warnings.len(), 1);
+    assert!(result.warnings[0].message.starts_with("In use by sleep"));
+}
 
-    let result = cleaner::clean(&findings, DeleteMode::Permanent).unwrap();
-    assert_eq!(result.deleted_count, 3);
-    assert_eq!(result.freed_bytes, 11);
-    assert!(!f1.exists());
-    assert!(!f2.exists());
-    assert!(!f3.exists());
+#[test]
+fn test_config_delete_mode() {
+    let mut config = Config::default();
+    assert_eq!(config.delete_mode(), DeleteMode::Trash);
+    config.defaults.delete_mode = "quarantine".into();
+    assert_eq!(config.delete_mode(), DeleteMode::Quarantine);
+    config.defaults.delete_mode = "Permanent".into();
+    assert_eq!(config.delete_mode(), DeleteMode::Permanent);
 }
 
 // ---------------------------------------------------------------------------
@@ -495,6 +3191,7 @@ fn test_config_default() {
     assert_eq!(config.defaults.risk_tolerance, "moderate");
     assert_eq!(config.max_risk(), RiskLevel::Moderate);
     assert!(config.is_recognizer_enabled("xcode-derived-data"));
+    assert!(config.scan.same_file_system);
 }
 
 #[test]
@@ -509,11 +3206,29 @@ risk_tolerance = "safe"
 delete_mode = "permanent"
 min_size = 1024
 
+[scan]
+roots = ["~/work", "/srv/repos"]
+max_depth = 3
+follow_symlinks = true
+same_file_system = false
+protect_active_within = "3d"
+protect_active = "exclude"
+
 [ignore]
 paths = ["/tmp/keep-this"]
+patterns = ["**/vendor/**", "!~/work/vendor/scratch"]
 
 [recognizers]
 disabled = ["docker-data"]
+
+[recognizers.cargo-target]
+risk = "safe"
+min_size = "50MB"
+roots = ["~/rust"]
+
+[recognizers.vscode-extensions]
+keep_latest = 2
+min_size = 4096
 "#,
     )
     .unwrap();
@@ -522,9 +3237,93 @@ disabled = ["docker-data"]
     assert_eq!(config.max_risk(), RiskLevel::Safe);
     assert_eq!(config.defaults.delete_mode, "permanent");
     assert_eq!(config.defaults.min_size, 1024);
+    assert_eq!(
+        config.scan.roots,
+        vec![PathBuf::from("~/work"), PathBuf::from("/srv/repos")]
+    );
+    assert_eq!(config.scan.max_depth, 3);
+    assert!(config.scan.follow_symlinks);
+    assert!(!config.scan.same_file_system);
+    assert_eq!(
+        config.active_protection(),
+        Some(ActiveProtection {
+            within: Duration::from_secs(3 * 86_400),
+            exclude: true,
+        })
+    );
     assert!(config.is_path_ignored(std::path::Path::new("/tmp/keep-this/subdir")));
+    assert_eq!(
+        config.ignore.patterns,
+        vec!["**/vendor/**", "!~/work/vendor/scratch"]
+    );
     assert!(!config.is_recognizer_enabled("docker-data"));
     assert!(config.is_recognizer_enabled("xcode-derived-data"));
+    assert!(config.is_recognizer_enabled("cargo-target"));
+
+    let cargo = config.recognizer_settings("cargo-target");
+    assert_eq!(cargo.risk, Some(RiskLevel::Safe));
+    assert_eq!(cargo.min_size, Some(50 * 1_048_576));
+    assert_eq!(cargo.roots, Some(vec![PathBuf::from("~/rust")]));
+    let vscode = config.recognizer_settings("vscode-extensions");
+    assert_eq!(vscode.keep_latest, Some(2));
+    assert_eq!(vscode.min_size, Some(4096));
+    assert_eq!(
+        config.recognizer_settings("npm-cache"),
+        &RecognizerSettings::default()
+    );
+}
+
+#[test]
+fn test_config_rejects_bad_active_protection() {
+    let tmp = TempDir::new().unwrap();
+    let config_path = tmp.path().join("config.toml");
+    for scan in [
+        "protect_active_within = \"soon\"",
+        "protect_active_within = \"3d\"\nprotect_active = \"delete\"",
+    ] {
+        std::fs::write(&config_path, format!("[scan]\n{scan}\n")).unwrap();
+        assert!(Config::load_from(&config_path).is_err(), "accepted {scan}");
+    }
+    assert_eq!(Config::default().active_protection(), None);
+}
+
+#[test]
+fn test_config_rejects_bad_ignore_pattern() {
+    let tmp = TempDir::new().unwrap();
+    let config_path = tmp.path().join("config.toml");
+    std::fs::write(&config_path, "[ignore]\npatterns = [\"!~/work/[x\"]\n").unwrap();
+    assert!(matches!(
+        Config::load_from(&config_path),
+        Err(diskard_core::error::Error::Config(_))
+    ));
+}
+
+#[test]
+fn test_config_rejects_bad_recognizer_settings() {
+    let tmp = TempDir::new().unwrap();
+    let config_path = tmp.path().join("config.toml");
+    for table in [
+        "risk = \"extreme\"",
+        "min_size = \"lots\"",
+        "keep_latest = -1",
+        "unknown = true",
+    ] {
+        std::fs::write(
+            &config_path,
+            format!("[recognizers.cargo-target]\n{table}\n"),
+        )
+        .unwrap();
+        assert!(Config::load_from(&config_path).is_err(), "accepted {table}");
+    }
+
+    // Keeping no versions would delete the installed one
+    std::fs::write(
+        &config_path,
+        "[recognizers.vscode-extensions]\nkeep_latest = 0\n",
+    )
+    .unwrap();
+    let error = Config::load_from(&config_path).unwrap_err().to_string();
+    assert!(error.contains("vscode-extensions"), "{error}");
 }
 
 #[test]
@@ -532,6 +3331,7 @@ fn test_config_path_ignore() {
     let config = Config {
         ignore: IgnoreConfig {
             paths: vec![PathBuf::from("/home/user/important")],
+            ..Default::default()
         },
         ..Default::default()
     };
@@ -547,7 +3347,10 @@ fn test_config_disabled_recognizers() {
     disabled.insert("homebrew-cache".to_string());
 
     let config = Config {
-        recognizers: RecognizerConfig { disabled },
+        recognizers: RecognizerConfig {
+            disabled,
+            ..Default::default()
+        },
         ..Default::default()
     };
     assert!(!config.is_recognizer_enabled("npm-cache"));
@@ -577,6 +3380,157 @@ fn test_config_risk_tolerance_parsing() {
     assert_eq!(config.max_risk(), RiskLevel::Safe);
 }
 
+/// Parse `toml` as a config file and build its recognizers.
+fn custom_recognizers(toml: &str) -> Result<(Config, Vec<Box<dyn Recognizer>>)> {
+    let tmp = TempDir::new().unwrap();
+    let config_path = tmp.path().join("config.toml");
+    std::fs::write(&config_path, toml).unwrap();
+    let config = Config::load_from(&config_path)?;
+    let recognizers = recognizers::for_config(&config)?;
+    Ok((config, recognizers))
+}
+
+#[test]
+fn test_custom_recognizer_globs_expand_home_and_env() {
+    let home = TempDir::new().unwrap();
+    let tools = TempDir::new().unwrap();
+    populate(home.path(), ".cache/tool-a", 10);
+    populate(home.path(), ".cache/tool-b", 20);
+    populate(home.path(), ".cache/other", 30);
+    populate(tools.path(), "cache", 40);
+    std::fs::create_dir_all(home.path().join(".cache/tool-empty")).unwrap();
+
+    let (config, recognizers) = custom_recognizers(
+        r#"
+[[custom]]
+id = "tool-cache"
+name = "Tool cache"
+category = "vs code"
+paths = ["~/.cache/tool-*", "$TOOL_HOME/cache", "$UNSET_HOME/cache"]
+risk = "safe"
+description = "Tool download cache"
+"#,
+    )
+    .unwrap();
+    let custom = recognizers.last().unwrap();
+    assert_eq!(custom.id(), "tool-cache");
+    assert_eq!(custom.name(), "Tool cache");
+    assert_eq!(custom.category(), Category::VSCode);
+
+    let recognizers: Vec<_> = recognizers
+        .into_iter()
+        .filter(|r| r.id() == "tool-cache")
+        .collect();
+    let locations =
+        Locations::new(home.path(), Platform::Linux).with_var("TOOL_HOME", tools.path());
+    let ctx = ScanContext::new(locations, config);
+    let result = scanner::scan_with_context(&recognizers, &ctx, &ScanOptions::default());
+    assert!(result.errors.is_empty(), "scan errors: {:?}", result.errors);
+
+    let mut paths = finding_paths(&result.findings);
+    paths.sort();
+    let mut expected = vec![
+        home.path().join(".cache/tool-a"),
+        home.path().join(".cache/tool-b"),
+        tools.path().join("cache"),
+    ];
+    expected.sort();
+    assert_eq!(paths, expected);
+    for finding in &result.findings {
+        assert_eq!(finding.recognizer_id, "tool-cache");
+        assert_eq!(finding.risk, RiskLevel::Safe);
+        assert_eq!(finding.description, "Tool download cache");
+    }
+}
+
+#[test]
+fn test_custom_recognizer_with_markers_joins_project_walk() {
+    let home = TempDir::new().unwrap();
+    let root = home.path().join("code");
+    let project = root.join("site");
+    std::fs::create_dir_all(&project).unwrap();
+    std::fs::write(project.join("turbo.json"), "{}").unwrap();
+    populate(&project, ".turbo/cache", 100);
+    populate(&project, "apps/web/.next", 200);
+    populate(&root, "plain/.turbo/cache", 300);
+
+    let (mut config, recognizers) = custom_recognizers(
+        r#"
+[[custom]]
+id = "turbo-cache"
+name = "Turborepo cache"
+category = "node"
+markers = ["turbo.json"]
+paths = [".turbo/cache", "apps/*/.next"]
+"#,
+    )
+    .unwrap();
+    config.scan.roots = vec![root.clone()];
+    let recognizers: Vec<_> = recognizers
+        .into_iter()
+        .filter(|r| r.id() == "turbo-cache")
+        .collect();
+    assert!(recognizers[0].project_detector().is_some());
+
+    let ctx = ScanContext::new(Locations::new(home.path(), Platform::Linux), config);
+    let result = scanner::scan_with_context(&recognizers, &ctx, &ScanOptions::default());
+    let mut paths = finding_paths(&result.findings);
+    paths.sort();
+    assert_eq!(
+        paths,
+        vec![project.join(".turbo/cache"), project.join("apps/web/.next")]
+    );
+    assert!(result.findings.iter().all(|f| f.risk == RiskLevel::Moderate
+        && f.category == Category::Node
+        && f.description == "Turborepo cache for site"));
+}
+
+#[test]
+fn test_custom_recognizer_rejects_invalid_tables() {
+    let table = |fields: &str| format!("[[custom]]\nid = \"mine\"\nname = \"Mine\"\n{fields}\n");
+
+    for fields in [
+        "paths = [\"~/x\"]\ncategory = \"nonsense\"",
+        "paths = [\"~/x\"]\nrisk = \"extreme\"",
+        "paths = []",
+        "paths = [\"~/x[\"]",
+        "paths = [\"/abs/out\"]\nmarkers = [\"Makefile\"]",
+    ] {
+        let error = custom_recognizers(&table(fields)).err();
+        assert!(
+            matches!(error, Some(diskard_core::error::Error::Config(_))),
+            "accepted {fields:?}"
+        );
+    }
+
+    let duplicate = "[[custom]]\nid = \"npm-cache\"\nname = \"Mine\"\npaths = [\"~/x\"]\n";
+    assert!(custom_recognizers(duplicate).is_err());
+}
+
+#[test]
+fn test_custom_recognizer_can_be_disabled() {
+    let (config, recognizers) = custom_recognizers(
+        r#"
+[recognizers]
+disabled = ["mine"]
+
+[[custom]]
+id = "mine"
+name = "Mine"
+paths = ["~/mine"]
+"#,
+    )
+    .unwrap();
+    assert_eq!(recognizers.len(), all_recognizers().len() + 1);
+    assert!(!config.is_recognizer_enabled("mine"));
+
+    let home = TempDir::new().unwrap();
+    populate(home.path(), "mine", 10);
+    let ctx = ScanContext::new(Locations::new(home.path(), Platform::Linux), config);
+    let result = scanner::scan_with_context(&recognizers, &ctx, &ScanOptions::default());
+    assert!(result.findings.iter().all(|f| f.recognizer_id != "mine"));
+}
+
 #[test]
 fn test_scanner_respects_ignored_paths() {
     let findings = vec![
@@ -599,6 +3553,7 @@ fn test_scanner_respects_ignored_paths() {
     let config = Config {
         ignore: IgnoreConfig {
             paths: vec![PathBuf::from("/home/user/important")],
+            ..Default::default()
         },
         ..Default::default()
     };
@@ -626,7 +3581,10 @@ fn test_scanner_respects_disabled_recognizers() {
     let mut disabled = std::collections::HashSet::new();
     disabled.insert("fake-0".to_string());
     let config = Config {
-        recognizers: RecognizerConfig { disabled },
+        recognizers: RecognizerConfig {
+            disabled,
+            ..Default::default()
+        },
         ..Default::default()
     };
     let result = scanner::scan(&recs, &config, &ScanOptions::default());
@@ -654,10 +3612,136 @@ fn test_dir_size_with_fixture() {
     std::fs::create_dir(&sub).unwrap();
     std::fs::write(sub.join("c.txt"), vec![0u8; 500]).unwrap();
 
-    let total = size::dir_size(tmp.path());
+    let total = size::dir_usage(tmp.path()).apparent_bytes;
     assert_eq!(total, 3500, "Dir size should sum all files recursively");
 }
 
+#[cfg(unix)]
+#[test]
+fn test_dir_usage_counts_hard_links_once() {
+    let tmp = TempDir::new().unwrap();
+    populate(tmp.path(), "store", 100_000);
+    std::fs::create_dir(tmp.path().join("project")).unwrap();
+    std::fs::hard_link(
+        tmp.path().join("store/blob"),
+        tmp.path().join("project/blob"),
+    )
+    .unwrap();
+    std::fs::hard_link(
+        tmp.path().join("store/blob"),
+        tmp.path().join("store/again"),
+    )
+    .unwrap();
+
+    let blob = std::fs::metadata(tmp.path().join("store/blob")).unwrap();
+    let usage = size::dir_usage(tmp.path());
+    assert_eq!(usage.apparent_bytes, 100_000);
+    assert_eq!(usage.size_bytes, size::allocated_bytes(&blob));
+
+    // The link in project keeps the blob on disk when store goes
+    let store = size::dir_usage(&tmp.path().join("store"));
+    assert_eq!(store.size_bytes, 0);
+    assert_eq!(store.last_used, usage.last_used);
+    assert_eq!(size::dir_size(&tmp.path().join("store/blob")), 0);
+
+    let cache = ScanCache::in_memory();
+    for _ in 0..2 {
+        let cached = cache.enter(|| size::dir_usage(tmp.path()));
+        assert_eq!(cached, usage);
+        let cached = cache.enter(|| size::dir_usage(&tmp.path().join("store")));
+        assert_eq!(cached.apparent_bytes, 0);
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_scan_counts_files_linked_between_findings_once() {
+    let home = TempDir::new().unwrap();
+    let h = home.path();
+    populate(h, ".cache/pip", 1000);
+    populate(h, ".cache/uv", 2000);
+    std::fs::write(h.join(".cache/pip/wheel"), vec![1u8; 100_000]).unwrap();
+    std::fs::hard_link(h.join(".cache/pip/wheel"), h.join(".cache/uv/wheel")).unwrap();
+    // Linked from outside every finding, so never freed
+    std::fs::write(h.join(".cache/uv/installed"), vec![1u8; 50_000]).unwrap();
+    std::fs::create_dir(h.join("venv")).unwrap();
+    std::fs::hard_link(h.join(".cache/uv/installed"), h.join("venv/installed")).unwrap();
+
+    let recognizers: Vec<Box<dyn Recognizer>> = all_recognizers()
+        .into_iter()
+        .filter(|r| ["pip-cache", "uv-cache"].contains(&r.id()))
+        .collect();
+    let ctx = ScanContext::new(Locations::new(h, Platform::Linux), Config::default());
+    let result = scanner::scan_with_context(&recognizers, &ctx, &ScanOptions::default());
+    assert_eq!(result.findings.len(), 2);
+
+    let apparent: u64 = result.findings.iter().map(|f| f.apparent_bytes).sum();
+    assert_eq!(apparent, 3000);
+    let wheel = std::fs::metadata(h.join(".cache/pip/wheel")).unwrap();
+    let sizes: u64 = result.findings.iter().map(|f| f.size_bytes).sum();
+    assert_eq!(
+        result.total_reclaimable,
+        sizes + size::allocated_bytes(&wheel)
+    );
+    assert_eq!(size::dir_usage(&h.join(".cache")).apparent_bytes, 103_000);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_dir_usage_reports_sparse_files_by_allocation() {
+    let tmp = TempDir::new().unwrap();
+    let image = std::fs::File::create(tmp.path().join("disk.raw")).unwrap();
+    image.set_len(64 * 1024 * 1024).unwrap();
+    std::fs::write(tmp.path().join("data"), vec![1u8; 8192]).unwrap();
+
+    let usage = size::dir_usage(tmp.path());
+    assert_eq!(usage.apparent_bytes, 64 * 1024 * 1024 + 8192);
+    assert!(usage.size_bytes < 1024 * 1024, "{usage:?}");
+    assert_eq!(size::dir_size(tmp.path()), usage.size_bytes);
+}
+
+#[test]
+fn test_findings_grouped_by_mount() {
+    let tmp = TempDir::new().unwrap();
+    let a = populate(tmp.path(), "a", 10);
+    let b = populate(tmp.path(), "b", 10);
+    let findings = vec![
+        make_finding(a, Category::Node, RiskLevel::Safe, 1000, None),
+        make_finding(b, Category::Rust, RiskLevel::Safe, 500, None),
+        // Not created yet: resolved through its parent
+        make_finding(
+            tmp.path().join("gone/x"),
+            Category::Rust,
+            RiskLevel::Safe,
+            25,
+            None,
+        ),
+    ];
+
+    let mounts = diskard_core::mounts::group_by_mount(&findings);
+    assert_eq!(mounts.len(), 1);
+    let mount = &mounts[0];
+    assert!(tmp
+        .path()
+        .canonicalize()
+        .unwrap()
+        .starts_with(&mount.mount_point));
+    assert_eq!(mount.reclaimable_bytes, 1525);
+    assert_eq!(mount.findings, 3);
+    assert!(mount.total_bytes >= mount.free_bytes);
+    assert!(mount.total_bytes > 0);
+
+    let result = scanner::scan_with_context(
+        &fake_recognizers(vec![(Category::Node, findings)]),
+        &ScanContext::new(
+            Locations::new(tmp.path(), Platform::Linux),
+            Config::default(),
+        ),
+        &ScanOptions::default(),
+    );
+    assert_eq!(result.mounts, mounts);
+}
+
 #[test]
 fn test_dir_size_empty_dir() {
     let tmp = TempDir::new().unwrap();
@@ -686,10 +3770,15 @@ fn test_finding_size_human() {
     let finding = Finding {
         path: PathBuf::from("/tmp/test"),
         category: Category::Generic,
+        recognizer_id: "test",
         risk: RiskLevel::Safe,
         size_bytes: 1_073_741_824,
+        apparent_bytes: 1_073_741_824,
         description: "Test".into(),
         last_modified: None,
+        native: None,
+        extra_paths: Vec::new(),
+        projects: Vec::new(),
     };
     assert_eq!(finding.size_human(), "1.0 GiB");
 }
@@ -722,15 +3811,28 @@ fn test_finding_serializable() {
     let finding = Finding {
         path: PathBuf::from("/tmp/test"),
         category: Category::Xcode,
+        recognizer_id: "test",
         risk: RiskLevel::Moderate,
         size_bytes: 1024,
+        apparent_bytes: 1024,
         description: "Test finding".into(),
-        last_modified: Some(SystemTime::now()),
+        last_modified: Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000)),
+        native: None,
+        extra_paths: Vec::new(),
+        projects: Vec::new(),
     };
     let json = serde_json::to_string(&finding).unwrap();
     assert!(json.contains("\"category\":\"Xcode\""));
     assert!(json.contains("\"risk\":\"Moderate\""));
     assert!(json.contains("\"size_bytes\":1024"));
-    // last_modified is #[serde(skip)]
-    assert!(!json.contains("last_modified"));
+    assert!(json.contains("\"apparent_bytes\":1024"));
+    assert!(json.contains("\"recognizer_id\":\"test\""));
+    assert!(json.contains("\"last_modified\":\"2023-11-14T22:13:20Z\""));
+
+    let unknown = Finding {
+        last_modified: None,
+        ..finding
+    };
+    let json = serde_json::to_string(&unknown).unwrap();
+    assert!(json.contains("\"last_modified\":null"));
 }